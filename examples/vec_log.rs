@@ -1,12 +1,16 @@
-#![feature(vec_push_within_capacity)]
+#![cfg_attr(not(feature = "defmt"), feature(vec_push_within_capacity))]
+#[cfg(not(feature = "defmt"))]
 use log::LevelFilter;
+#[cfg(not(feature = "defmt"))]
 use rand::random;
+#[cfg(not(feature = "defmt"))]
 use sp_log2::{ColorChoice, CombinedLogger, ConfigBuilder, Format, TermLogger, TerminalMode};
 
 extern crate sp_log2;
-#[macro_use]
+#[cfg_attr(not(feature = "defmt"), macro_use)]
 extern crate tacky_borders_logger;
 
+#[cfg(not(feature = "defmt"))]
 fn initialize_logger() -> anyhow::Result<()> {
     let mut config_builder = ConfigBuilder::new();
 
@@ -32,6 +36,7 @@ fn initialize_logger() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[cfg(not(feature = "defmt"))]
 fn generate_custom_vec(data: &mut Vec<u8>) {
     let size = data.capacity();
     data.clear();
@@ -39,8 +44,10 @@ fn generate_custom_vec(data: &mut Vec<u8>) {
         let num = random::<u8>();
         let _ = data.push_within_capacity(num);
     }
+    log_capacity!(debug, data, "vec");
 }
 
+#[cfg(not(feature = "defmt"))]
 fn main() {
     if let Err(e) = &initialize_logger() {
         error!("logger initialization failed: {e}");
@@ -50,13 +57,25 @@ fn main() {
     let mut vec = Vec::with_capacity(size);
 
     generate_custom_vec(&mut vec);
+    log_hash!(trace, vec);
     trace!("{vec:#?}");
     generate_custom_vec(&mut vec);
+    log_hash!(debug, vec);
     debug!("{vec:#?}");
     generate_custom_vec(&mut vec);
+    log_hash!(info, vec);
     info!("{vec:#?}");
     generate_custom_vec(&mut vec);
+    log_hash!(warn, vec);
     warn!("{vec:#?}");
     generate_custom_vec(&mut vec);
+    log_hash!(error, vec);
     error!("{vec:#?}");
 }
+
+// The `defmt` backend formats at compile time and has no `{:#?}`, so this example only runs
+// with the `log` backend.
+#[cfg(feature = "defmt")]
+fn main() {
+    eprintln!("vec_log logs through `log`; run it without the `defmt` feature");
+}
@@ -0,0 +1,179 @@
+//! # Tacky Borders Logger Macros
+//!
+//! Procedural macros for `tacky-borders-logger`. These are re-exported from the main crate and
+//! should be used through it rather than depended on directly.
+
+use proc_macro::TokenStream;
+use proc_macro_crate::{FoundCrate, crate_name};
+use proc_macro2::Span;
+use quote::quote;
+use syn::{FnArg, Ident, ItemFn, LitStr, Pat, ReturnType, Type, meta, parse_macro_input};
+
+/// Attribute macro to log entry into and exit from a function.
+///
+/// On entry, the arguments are logged with their `Debug` representation; on exit, the return
+/// value is logged, also with `Debug`. The function name is taken from the annotated item itself, so it is exact
+/// even where `function_name!()` would have to guess.
+///
+/// Both lines are logged at trace level unless another level is given with `level = "..."`.
+/// Arguments that do not implement `Debug`, or should not be logged, can be left out with
+/// `skip(...)`. Likewise, the return type must implement `Debug` unless `skip_ret` is given,
+/// in which case the exit line is just `exit`.
+///
+/// The generated code refers to `tacky-borders-logger` by the name the calling crate gives it
+/// in its `Cargo.toml`, so the attribute also works when the dependency is renamed.
+///
+/// # Example
+/// ```rust,ignore
+/// #[logged(level = "debug", skip(password))]
+/// fn login(user: &str, password: &str) -> bool {
+///     // Logs: "enter user=\"admin\" [fn login]"
+///     true
+///     // Logs: "exit -> true [fn login]"
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn logged(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut level = Ident::new("Trace", Span::call_site());
+    let mut skip = Vec::<Ident>::new();
+    let mut skip_ret = false;
+
+    let parser = meta::parser(|meta| {
+        if meta.path.is_ident("level") {
+            let value: LitStr = meta.value()?.parse()?;
+            level = match value.value().as_str() {
+                "trace" => Ident::new("Trace", value.span()),
+                "debug" => Ident::new("Debug", value.span()),
+                "info" => Ident::new("Info", value.span()),
+                "warn" => Ident::new("Warn", value.span()),
+                "error" => Ident::new("Error", value.span()),
+                _ => {
+                    return Err(meta.error(
+                        "expected one of \"trace\", \"debug\", \"info\", \"warn\" or \"error\"",
+                    ));
+                }
+            };
+            Ok(())
+        } else if meta.path.is_ident("skip_ret") {
+            skip_ret = true;
+            Ok(())
+        } else if meta.path.is_ident("skip") {
+            meta.parse_nested_meta(|meta| {
+                skip.push(meta.path.require_ident()?.clone());
+                Ok(())
+            })
+        } else {
+            Err(meta.error("unsupported `logged` argument"))
+        }
+    });
+    parse_macro_input!(attr with parser);
+
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = parse_macro_input!(item as ItemFn);
+    let fn_name = sig.ident.to_string();
+
+    let args: Vec<&Ident> = sig
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Typed(pat_type) => match &*pat_type.pat {
+                Pat::Ident(pat) if !skip.contains(&pat.ident) => Some(&pat.ident),
+                _ => None,
+            },
+            FnArg::Receiver(_) => None,
+        })
+        .collect();
+    let enter_format = args
+        .iter()
+        .map(|arg| format!(" {arg}={{:?}}"))
+        .collect::<Vec<_>>()
+        .join(",");
+    let enter_format = format!("enter{enter_format}");
+
+    let call = if sig.asyncness.is_some() {
+        quote! { async move #block.await }
+    } else {
+        match &sig.output {
+            ReturnType::Type(_, ty) if !contains_impl_trait(ty) => {
+                quote! { (move || -> #ty #block)() }
+            }
+            _ => quote! { (move || #block)() },
+        }
+    };
+
+    let private = crate_path();
+    let private = quote! { #private::__private_api };
+    let emit = |args: proc_macro2::TokenStream| {
+        quote! {
+            if #private::enabled(#private::log::Level::#level) {
+                #private::emit(
+                    #private::log::Level::#level,
+                    #args,
+                    #fn_name,
+                    &(module_path!(), module_path!(), file!(), line!()),
+                );
+            }
+        }
+    };
+    let enter = emit(quote! { format_args!(#enter_format, #(&#args),*) });
+    let exit = if skip_ret {
+        emit(quote! { format_args!("exit") })
+    } else {
+        emit(quote! { format_args!("exit -> {:?}", &__logged_ret) })
+    };
+
+    quote! {
+        #(#attrs)*
+        #vis #sig {
+            #enter
+            #[allow(clippy::redundant_closure_call)]
+            let __logged_ret = #call;
+            #exit
+            __logged_ret
+        }
+    }
+    .into()
+}
+
+/// Returns the path of the main crate as the calling crate names it, which may differ from
+/// `tacky_borders_logger` if the dependency is renamed in its `Cargo.toml`.
+fn crate_path() -> proc_macro2::TokenStream {
+    match crate_name("tacky-borders-logger") {
+        Ok(FoundCrate::Name(name)) => {
+            let name = Ident::new(&name, Span::call_site());
+            quote! { ::#name }
+        }
+        // `Itself` is also reported for the crate's own examples and tests, which reach it by its
+        // name like any other dependent.
+        Ok(FoundCrate::Itself) | Err(_) => quote! { ::tacky_borders_logger },
+    }
+}
+
+/// Closures cannot name `impl Trait` in their return type, so those are left to inference.
+fn contains_impl_trait(ty: &Type) -> bool {
+    match ty {
+        Type::ImplTrait(_) => true,
+        Type::Group(group) => contains_impl_trait(&group.elem),
+        Type::Paren(paren) => contains_impl_trait(&paren.elem),
+        Type::Reference(reference) => contains_impl_trait(&reference.elem),
+        Type::Slice(slice) => contains_impl_trait(&slice.elem),
+        Type::Array(array) => contains_impl_trait(&array.elem),
+        Type::Tuple(tuple) => tuple.elems.iter().any(contains_impl_trait),
+        Type::Path(path) => path
+            .path
+            .segments
+            .iter()
+            .any(|segment| match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) => args.args.iter().any(|arg| match arg {
+                    syn::GenericArgument::Type(ty) => contains_impl_trait(ty),
+                    _ => false,
+                }),
+                _ => false,
+            }),
+        _ => false,
+    }
+}
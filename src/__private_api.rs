@@ -0,0 +1,641 @@
+//! Runtime support for the exported macros. Not part of the public API.
+
+use std::fmt::{self, Write};
+use std::panic;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use log::{Level, Record};
+
+use crate::config::{self, FnSuffixStyle, NewlineStyle, SuffixFormat};
+use crate::context::{self, ContextText};
+use crate::display::FmtDuration;
+use crate::timestamp::Timestamp;
+use crate::traceparent::TraceText;
+use crate::{buffered, capture, crash, dedup, label, secrets, socket, span, stats, timing};
+
+pub use inventory;
+pub use log;
+
+#[cfg(feature = "counting-allocator")]
+pub use crate::alloc::allocated;
+pub use crate::artifact::Artifact;
+pub use crate::audit::{audit, audit_enabled};
+pub use crate::backtrace::FilteredBacktrace;
+pub use crate::banner::Banner;
+pub use crate::baseline::log_baseline;
+pub use crate::capture::Capture;
+pub use crate::clock::{elapsed_since, now};
+pub use crate::context::{AttemptScope, LogGroup, RequestScope, SampledScope};
+#[cfg(feature = "demangle")]
+pub use crate::demangle::Demangled;
+pub use crate::destructor::drop_log;
+pub use crate::display::{Counted, Elapsed, Throughput};
+#[cfg(feature = "windows")]
+pub use crate::dump::fatal_dump;
+pub use crate::env::{EnvVar, config_snapshot, runtime_info};
+pub use crate::errors::{Errno, Expect, Fingerprint, expect_failed, severity_of};
+pub use crate::hash_chain::{
+    ChainState, HashChainText, hash_chain_reset, hash_chain_state, hash_chain_update,
+};
+#[cfg(feature = "windows")]
+pub use crate::hresult::{HResult, LastError};
+pub use crate::http::status_level;
+pub use crate::inspect::{
+    BytesDiff, DebugVars, DepthLimited, HexDiff, MapDelta, SliceDiff, SmartDebug, SortedMap,
+    collection_text, hash_of, render_with,
+};
+pub use crate::label::ModuleLabel;
+pub use crate::lock::{Lockable, lock_contended};
+#[cfg(feature = "metrics")]
+pub use crate::metric::record_op;
+pub use crate::once::first_seen;
+pub use crate::peak::{PeakSite, peak_of};
+pub use crate::rate::{
+    AdaptiveSampler, Cooldown, CounterRate, Interval, JitteredInterval, RateText, SampledText,
+    TokenBucket, message_ready, nth_due, percent,
+};
+pub use crate::recursion::RecursionGuard;
+pub use crate::retry::BackoffPlan;
+pub use crate::samples::{SampleStats, add_sample, samples_key, take_due_samples};
+#[cfg(feature = "serde")]
+pub use crate::serialized::{ConfigChange, Serialized, config_diff};
+pub use crate::span::SpanPath;
+pub use crate::stats::{Stats, take_due_stats};
+pub use crate::steps::{StepOutcome, StepsGuard, TimedStep, TimedStepsGuard, record_step};
+pub use crate::summary::{Summary, summary_add};
+pub use crate::table::table_row;
+pub use crate::tenant::tenant_target;
+#[cfg(feature = "tokio")]
+pub use crate::timeout::log_timeout;
+pub use crate::timing::{mark_start, process_cpu_time, since_start};
+#[cfg(feature = "rayon")]
+pub use crate::worker::Worker;
+
+/// Call-site metadata captured by the macros: `(target, module_path, file, line)`.
+pub type Location<'a> = (&'a str, &'static str, &'static str, u32);
+
+/// Returns `true` if logging is enabled at `level` and a record at `level` would pass `log`'s
+/// static and runtime filters, or be captured by `capture_logs!`.
+#[inline]
+pub fn enabled(level: Level) -> bool {
+    if level > log::STATIC_MAX_LEVEL || !config::logging_enabled() {
+        return false;
+    }
+    match context::sampled() {
+        Some(selected) => selected,
+        None => config::level_enabled(level) && (level <= log::max_level() || capture::active()),
+    }
+}
+
+/// Returns the name of the function enclosing the marker function `_marker`, for
+/// `function_name!()`.
+///
+/// Only this shim is instantiated per call site; it passes the marker's type name to
+/// [`fn_name_from`], which is shared by all of them.
+#[inline(always)]
+pub fn fn_name_of<F>(_marker: F) -> String {
+    fn_name_from(std::any::type_name::<F>())
+}
+
+/// Extracts the function name from the type name of a marker function, e.g. `retry` from
+/// `my_app::net::retry::f`.
+///
+/// Markers in closures and `async` bodies are named after them, as in
+/// `my_app::fetch::{{closure}}::f`, so segments such as `{{closure}}` are skipped to reach the
+/// function that encloses them.
+#[cfg_attr(feature = "inline-fn-name", inline)]
+#[cfg_attr(not(feature = "inline-fn-name"), inline(never))]
+pub fn fn_name_from(marker_type_name: &'static str) -> String {
+    let name = marker_type_name
+        .strip_suffix("::f")
+        .unwrap_or(marker_type_name);
+    name.rsplit("::")
+        .find(|segment| !(segment.starts_with("{{") && segment.ends_with("}}")))
+        .unwrap_or(name)
+        .to_owned()
+}
+
+/// Sleeps for `duration` if the `debug-sleep` feature is enabled, for `log_sleep!`.
+///
+/// The feature has to be checked here rather than in the macro, where `cfg` would test the
+/// calling crate's features.
+#[inline]
+pub fn debug_sleep(duration: std::time::Duration) {
+    #[cfg(feature = "debug-sleep")]
+    std::thread::sleep(duration);
+    #[cfg(not(feature = "debug-sleep"))]
+    let _ = duration;
+}
+
+/// A named value attached to a record, as a key-value pair with the `kv` feature or as
+/// ` key=value` text otherwise.
+pub type Field<'a> = (&'a str, &'a dyn fmt::Display);
+
+/// Formats `args`, applies the runtime configuration and hands the record to the logger.
+pub fn emit(level: Level, args: fmt::Arguments, fn_name: &str, loc: &Location) {
+    emit_fields(level, args, fn_name, loc, &[]);
+}
+
+/// Like [`emit`], for the timing macros. With the `kv` feature, `elapsed` is also attached to
+/// the record as a `duration_ms` field, so metrics can be read without parsing the message.
+pub fn emit_timed(
+    level: Level,
+    args: fmt::Arguments,
+    fn_name: &str,
+    loc: &Location,
+    elapsed: std::time::Duration,
+) {
+    #[cfg(feature = "kv")]
+    emit_fields(
+        level,
+        args,
+        fn_name,
+        loc,
+        &[("duration_ms", &(elapsed.as_secs_f64() * 1e3))],
+    );
+    #[cfg(not(feature = "kv"))]
+    {
+        let _ = elapsed;
+        emit(level, args, fn_name, loc);
+    }
+}
+
+/// Like [`emit`], with additional fields attached to the record.
+pub fn emit_fields(
+    level: Level,
+    args: fmt::Arguments,
+    fn_name: &str,
+    loc: &Location,
+    fields: &[Field],
+) {
+    let &(target, module_path, file, line) = loc;
+
+    // In the target, the name survives bridges such as `tracing-log`, which drop the message's
+    // structure; it is then left out of the message.
+    let fn_target;
+    let target = if config::fn_name_in_target() {
+        fn_target = format!("{target}::{fn_name}");
+        fn_target.as_str()
+    } else {
+        target
+    };
+
+    let suffix = if config::fn_name_in_target()
+        || !config::suffix_level_enabled(level)
+        || config::is_fn_suffix_denied(fn_name)
+    {
+        Suffix::None
+    } else {
+        let module = match config::fn_suffix_style() {
+            FnSuffixStyle::Name => None,
+            FnSuffixStyle::ModuleAndName => module_path.rsplit("::").next(),
+            FnSuffixStyle::Path => Some(module_path),
+        };
+        Suffix::Fn {
+            module,
+            fn_name,
+            format: config::fn_suffix_format(),
+        }
+    };
+    let callsite = Callsite {
+        target,
+        module_path: Some(module_path),
+        module_label: label::module_label(module_path),
+        file,
+        line,
+    };
+    dispatch(level, args, suffix, &callsite, fields);
+}
+
+/// Where a record was logged from.
+pub(crate) struct Callsite<'a> {
+    pub(crate) target: &'a str,
+    pub(crate) module_path: Option<&'static str>,
+    /// The label registered for the module with `module_label!`, if any.
+    pub(crate) module_label: Option<&'static str>,
+    pub(crate) file: &'static str,
+    pub(crate) line: u32,
+}
+
+/// Context appended to every message.
+pub(crate) enum Suffix<'a> {
+    /// Nothing, e.g. when the function name is denylisted or the level has no suffix.
+    None,
+    /// ` [fn name]`, with the name optionally qualified by a module, unless a custom template
+    /// is set.
+    Fn {
+        module: Option<&'a str>,
+        fn_name: &'a str,
+        format: Option<Arc<SuffixFormat>>,
+    },
+    /// ` [at file:line]`, for callers that only know their source location.
+    Caller(&'static panic::Location<'static>),
+}
+
+impl fmt::Display for Suffix<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Suffix::None => Ok(()),
+            Suffix::Fn {
+                module,
+                fn_name,
+                format,
+            } => {
+                let (before, after) = match format {
+                    Some(format) => (format.before.as_str(), format.after.as_str()),
+                    None => (" [fn ", "]"),
+                };
+                match module {
+                    Some(module) => write!(f, "{before}{module}::{fn_name}{after}"),
+                    None => write!(f, "{before}{fn_name}{after}"),
+                }
+            }
+            Suffix::Caller(location) => {
+                write!(f, " [at {}:{}]", location.file(), location.line())
+            }
+        }
+    }
+}
+
+/// Applies the message options shared by every entry point and logs the record.
+pub(crate) fn dispatch(
+    level: Level,
+    args: fmt::Arguments,
+    suffix: Suffix,
+    callsite: &Callsite,
+    fields: &[Field],
+) {
+    // Only render the message into a `String` when it has to be inspected.
+    let redacted;
+    let message: &dyn fmt::Display = match config::secret_detection()
+        .then(|| secrets::redact(&args.to_string()))
+        .flatten()
+    {
+        Some(text) => {
+            if secrets::first_at(callsite.file, callsite.line) && enabled(Level::Warn) {
+                dispatch(
+                    Level::Warn,
+                    format_args!(
+                        "potential secret redacted from a record logged at {}:{}",
+                        callsite.file, callsite.line
+                    ),
+                    Suffix::None,
+                    callsite,
+                    &[],
+                );
+            }
+            redacted = text;
+            &redacted
+        }
+        None => &args,
+    };
+    let truncated;
+    let message: &dyn fmt::Display = match config::max_message_len() {
+        Some(max) => {
+            truncated = truncate(message, max);
+            &truncated
+        }
+        None => message,
+    };
+
+    let build_id = config::build_id();
+    let with_build_id: Vec<Field>;
+    let fields = match &build_id {
+        Some(build_id) => {
+            with_build_id = fields
+                .iter()
+                .copied()
+                .chain([("build", build_id as &dyn fmt::Display)])
+                .collect();
+            &with_build_id
+        }
+        None => fields,
+    };
+
+    // With `kv`, fields travel as key-value pairs and are left out of the record's message, and
+    // so does the function name, as a `function` field next to the `module` path.
+    #[cfg(feature = "kv")]
+    let (with_fn, no_suffix): (Vec<Field>, _);
+    #[cfg(feature = "kv")]
+    let (kv_fields, record_suffix) = match &suffix {
+        Suffix::Fn { fn_name, .. } => {
+            with_fn = fields
+                .iter()
+                .copied()
+                .chain([("function", fn_name as &dyn fmt::Display)])
+                .chain(
+                    (callsite.module_path.as_ref())
+                        .map(|module_path| ("module", module_path as &dyn fmt::Display)),
+                )
+                .collect();
+            no_suffix = Suffix::None;
+            (KvFields(&with_fn), &no_suffix)
+        }
+        _ => (KvFields(fields), &suffix),
+    };
+    let text_fields = TextFields(fields);
+
+    let module_label = ModuleLabelText(callsite.module_label);
+
+    if config::dedup_consecutive() {
+        let key = if config::dedup_ignores_suffix() {
+            format!("{message}{text_fields}{module_label}{ContextText}")
+        } else {
+            format!("{message}{text_fields}{module_label}{ContextText}{suffix}")
+        };
+        if dedup::is_repeat(key, level, callsite) {
+            return;
+        }
+    }
+
+    if config::span_stats() {
+        span::count_record();
+    }
+    stats::count(level);
+
+    let thread = ThreadText;
+    let seq = Seq(config::include_seq().then(next_seq));
+    let span_elapsed = SpanElapsed(
+        config::include_span_elapsed()
+            .then(span::innermost_elapsed)
+            .flatten(),
+    );
+    let delta = Delta(config::include_delta().then(timing::since_last_record));
+    let timestamp = TimestampText(config::include_timestamp().then(|| Timestamp {
+        time: crate::clock::system_now(),
+        timezone: config::timestamp_timezone(),
+        millis: true,
+    }));
+
+    let indent = if config::indent_with_depth() {
+        span::depth() * 2
+    } else {
+        0
+    };
+
+    let body = format_args!("{:indent$}{message}", "");
+    let context = format_args!("{module_label}{ContextText}");
+    let tail =
+        format_args!("{ProfileText}{TraceText}{thread}{seq}{span_elapsed}{delta}{timestamp}");
+    let line = format_args!("{body}{text_fields}{context}{suffix}{tail}");
+    let normalized = NewlineText(&line, config::newline_normalization());
+    let line = format_args!("{normalized}");
+    if buffered::buffer(level, &line, callsite) {
+        return;
+    }
+
+    let mut builder = Record::builder();
+    builder
+        .level(level)
+        .target(callsite.target)
+        .module_path_static(callsite.module_path)
+        .file_static(Some(callsite.file))
+        .line(Some(callsite.line));
+    #[cfg(feature = "kv")]
+    builder.key_values(&kv_fields);
+
+    // Sinks other than the logger only keep text, so they get the fields and the suffix even
+    // when the record carries them as key-value pairs.
+    #[cfg(feature = "kv")]
+    let record_line = format_args!("{body}{context}{record_suffix}{tail}");
+    #[cfg(feature = "kv")]
+    let record_normalized = NewlineText(&record_line, config::newline_normalization());
+    #[cfg(feature = "kv")]
+    let record_line = format_args!("{record_normalized}");
+    #[cfg(not(feature = "kv"))]
+    let record_line = line;
+
+    output(level, &line, &builder.args(record_line).build());
+}
+
+/// Passes a rendered line to the crash report, then to a capture, or else to the socket sink,
+/// and `record` on to the logger.
+///
+/// Records held back by a `LogBuffer` and the repeat counts of deduplication are output here
+/// too, so every sink sees every record.
+pub(crate) fn output(level: Level, line: &dyn fmt::Display, record: &Record) {
+    crash::record(level, line);
+    if capture::capture(level, line) {
+        return;
+    }
+    socket::write(level, line);
+    log::logger().log(record);
+    if config::flushes_on(level) {
+        log::logger().flush();
+    }
+}
+
+/// Renders fields as ` key=value` text.
+struct TextFields<'a>(&'a [Field<'a>]);
+
+impl fmt::Display for TextFields<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0
+            .iter()
+            .try_for_each(|(key, value)| write!(f, " {key}={value}"))
+    }
+}
+
+/// Renders a module label as ` [label]`.
+struct ModuleLabelText(Option<&'static str>);
+
+impl fmt::Display for ModuleLabelText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(label) => write!(f, " [{label}]"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Renders a line with its line endings rewritten to the given style, or as it is for `None`.
+struct NewlineText<'a>(&'a dyn fmt::Display, Option<NewlineStyle>);
+
+impl fmt::Display for NewlineText<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(style) = self.1 else {
+            return self.0.fmt(f);
+        };
+        let mut writer = NewlineWriter {
+            inner: f,
+            newline: style.as_str(),
+            after_cr: false,
+        };
+        write!(writer, "{}", self.0)
+    }
+}
+
+/// Writes through to `inner`, replacing `\r\n`, `\r` and `\n` with `newline`. A `\r` ending
+/// one chunk is remembered, so a `\n` starting the next is not counted twice.
+struct NewlineWriter<'a, 'b> {
+    inner: &'a mut fmt::Formatter<'b>,
+    newline: &'static str,
+    after_cr: bool,
+}
+
+impl fmt::Write for NewlineWriter<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut rest = s;
+        if self.after_cr {
+            rest = rest.strip_prefix('\n').unwrap_or(rest);
+        }
+        self.after_cr = false;
+        while let Some(end) = rest.find(['\r', '\n']) {
+            self.inner.write_str(&rest[..end])?;
+            self.inner.write_str(self.newline)?;
+            rest = match rest[end..].strip_prefix("\r\n") {
+                Some(rest) => rest,
+                None => {
+                    self.after_cr = rest[end..].starts_with('\r') && end + 1 == rest.len();
+                    &rest[end + 1..]
+                }
+            };
+        }
+        self.inner.write_str(rest)
+    }
+}
+
+/// Renders the build profile as ` profile=debug` or ` profile=release`, if enabled.
+struct ProfileText;
+
+impl fmt::Display for ProfileText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !config::include_profile() {
+            return Ok(());
+        }
+        // A compile-time constant, so enabling this costs no more than the check above.
+        f.write_str(if cfg!(debug_assertions) {
+            " profile=debug"
+        } else {
+            " profile=release"
+        })
+    }
+}
+
+/// Renders the current thread as ` thread=<name>` and the current `tokio` task as ` task=<id>`,
+/// each only if enabled.
+struct ThreadText;
+
+impl fmt::Display for ThreadText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if config::include_thread() {
+            let thread = std::thread::current();
+            match thread.name() {
+                Some(name) => write!(f, " thread={name}")?,
+                None => write!(f, " thread={:?}", thread.id())?,
+            }
+        }
+        #[cfg(feature = "tokio")]
+        if config::include_task_id()
+            && let Some(id) = tokio::task::try_id()
+        {
+            write!(f, " task={id}")?;
+        }
+        Ok(())
+    }
+}
+
+static SEQ: AtomicU64 = AtomicU64::new(1);
+
+fn next_seq() -> u64 {
+    SEQ.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Renders a sequence number as ` #n`, or nothing for `None`.
+struct Seq(Option<u64>);
+
+impl fmt::Display for Seq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(seq) => write!(f, " #{seq}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Renders the time since the previous record as ` +12.30ms`, or ` +0ms` for the first one
+/// on a thread. `None` renders nothing.
+struct Delta(Option<Option<std::time::Duration>>);
+
+impl fmt::Display for Delta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(Some(delta)) => write!(f, " +{}", FmtDuration(delta)),
+            Some(None) => f.write_str(" +0ms"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Renders the time since the innermost span was entered as ` +12.30ms`, or nothing for
+/// `None`.
+struct SpanElapsed(Option<std::time::Duration>);
+
+impl fmt::Display for SpanElapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(elapsed) => write!(f, " +{}", FmtDuration(elapsed)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Renders a timestamp as ` @2025-01-31T08:15:00.123Z`, or nothing for `None`.
+struct TimestampText(Option<Timestamp>);
+
+impl fmt::Display for TimestampText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(timestamp) => write!(f, " @{timestamp}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Exposes fields to `log` as key-value pairs.
+#[cfg(feature = "kv")]
+struct KvFields<'a>(&'a [Field<'a>]);
+
+#[cfg(feature = "kv")]
+impl log::kv::Source for KvFields<'_> {
+    fn visit<'kvs>(
+        &'kvs self,
+        visitor: &mut dyn log::kv::VisitSource<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        for &(key, value) in self.0 {
+            visitor.visit_pair(
+                log::kv::Key::from_str(key),
+                log::kv::Value::from_dyn_display(value),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn truncate(message: &dyn fmt::Display, max: usize) -> String {
+    let mut message = message.to_string();
+    let total = message.len();
+    if total <= max {
+        return message;
+    }
+
+    let mut end = max;
+    while !message.is_char_boundary(end) {
+        end -= 1;
+    }
+    message.truncate(end);
+    let _ = write!(message, "… (truncated, {total} bytes total)");
+    message
+}
+
+/// Lets a closure writing a `defmt` message be used as a `{}` argument of another `defmt` call.
+#[cfg(feature = "defmt")]
+pub struct DefmtMessage<F: Fn(defmt::Formatter<'_>)>(pub F);
+
+#[cfg(feature = "defmt")]
+impl<F: Fn(defmt::Formatter<'_>)> defmt::Format for DefmtMessage<F> {
+    fn format(&self, fmt: defmt::Formatter) {
+        (self.0)(fmt)
+    }
+}
@@ -0,0 +1,113 @@
+//! Counting the heap allocations made by a block of code.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    /// The number of allocations made on this thread, and their total size in bytes.
+    static ALLOCATED: Cell<(u64, u64)> = const { Cell::new((0, 0)) };
+}
+
+/// A global allocator that counts the allocations made on each thread, for
+/// [`log_allocs!`](crate::log_allocs).
+///
+/// It forwards every request to the allocator it wraps, [`System`] by default, and only adds
+/// two thread-local counters on top.
+///
+/// # Example
+/// ```rust
+/// use tacky_borders_logger::CountingAllocator;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator(std::alloc::System);
+/// ```
+#[derive(Debug, Default)]
+pub struct CountingAllocator<A = System>(pub A);
+
+impl<A> CountingAllocator<A> {
+    fn count(size: usize) {
+        // `try_with` fails, rather than panicking, while the thread is being torn down.
+        let _ = ALLOCATED.try_with(|allocated| {
+            let (count, bytes) = allocated.get();
+            allocated.set((count + 1, bytes + size as u64));
+        });
+    }
+}
+
+// SAFETY: every method forwards to the wrapped allocator, which upholds the contract; counting
+// does not allocate.
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        Self::count(layout.size());
+        // SAFETY: the caller upholds `alloc`'s contract.
+        unsafe { self.0.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        Self::count(layout.size());
+        // SAFETY: the caller upholds `alloc_zeroed`'s contract.
+        unsafe { self.0.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        Self::count(new_size);
+        // SAFETY: the caller upholds `realloc`'s contract.
+        unsafe { self.0.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: the caller upholds `dealloc`'s contract.
+        unsafe { self.0.dealloc(ptr, layout) }
+    }
+}
+
+/// Returns the number of allocations made on this thread so far, and their total size.
+#[doc(hidden)]
+pub fn allocated() -> (u64, u64) {
+    ALLOCATED.with(Cell::get)
+}
+
+/// Macro to log how many heap allocations a block of code made.
+///
+/// This macro runs the block, then logs `<label>: <n> allocations, <size>` with the name
+/// of the function where it is invoked, where the size is the total requested, e.g.
+/// `4.0 KiB`. Each `realloc` counts as an allocation of its new size. The macro evaluates
+/// to the value of the block.
+///
+/// The counts come from [`CountingAllocator`](crate::CountingAllocator), which must be
+/// installed as the global allocator; without it, every block reports 0 allocations.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{CountingAllocator, log_allocs};
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator(std::alloc::System);
+///
+/// fn parse(input: &str) -> Vec<String> {
+///     log_allocs!(debug, "parse", {
+///         input.split(',').map(str::to_owned).collect()
+///     })
+///     // Logs: "parse: 4 allocations, 99 B [fn parse]"
+/// }
+/// # parse("a,b,c");
+/// ```
+/// # Notes
+/// - Available with the `counting-allocator` feature.
+/// - Allocations are counted per thread, so only those made by the block's own thread are
+///   reported, including any made by logging inside the block.
+#[macro_export]
+macro_rules! log_allocs {
+    ($level:ident, $label:expr, $body:block) => {{
+        let (count, bytes) = $crate::__private_api::allocated();
+        let value = $body;
+        let (count_after, bytes_after) = $crate::__private_api::allocated();
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}: {} allocations, {}",
+            $label,
+            count_after - count,
+            $crate::FmtBytes(bytes_after - bytes)
+        );
+        value
+    }};
+}
@@ -0,0 +1,59 @@
+//! Macros for linking log lines to the files a program produces.
+
+use std::fmt;
+use std::path::Path;
+
+/// Renders a path with [`Path::display`], followed by ` (missing)` if nothing exists there;
+/// for [`log_artifact!`](crate::log_artifact).
+#[doc(hidden)]
+pub struct Artifact<'a>(pub &'a Path);
+
+impl fmt::Display for Artifact<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.display())?;
+        if !self.0.exists() {
+            f.write_str(" (missing)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Macro to log a message referencing a file the program produced.
+///
+/// This macro logs the message followed by `artifact=<path>`, with the name of the function
+/// where it is invoked, so post-mortem tooling can find dumps, screenshots and similar
+/// files from the log. The path can be anything implementing `AsRef<Path>`, and is rendered
+/// with [`Path::display`](std::path::Path::display). If nothing exists at the path when the
+/// line is logged, ` (missing)` is added after it.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_artifact;
+/// fn dump_state(path: &std::path::Path) {
+///     log_artifact!(info, "state dump written", path);
+///     // Logs: "state dump written artifact=/tmp/state.json [fn dump_state]"
+/// }
+/// ```
+/// # Notes
+/// - With the `kv` feature, the path is attached as an `artifact` key-value pair instead.
+#[macro_export]
+macro_rules! log_artifact {
+    ($level:ident, $message:expr, $path:expr $(,)?) => {{
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) {
+            let fn_name = $crate::function_name!();
+            $crate::__private_api::emit_fields(
+                level,
+                format_args!("{}", $message),
+                &fn_name,
+                &(module_path!(), module_path!(), file!(), line!()),
+                &[(
+                    "artifact",
+                    &$crate::__private_api::Artifact(
+                        ::std::convert::AsRef::<::std::path::Path>::as_ref(&$path),
+                    ),
+                )],
+            );
+        }
+    }};
+}
@@ -0,0 +1,111 @@
+//! Logging of the current value of atomics.
+
+use std::fmt;
+use std::sync::atomic::{
+    AtomicBool, AtomicI8, AtomicI16, AtomicI32, AtomicIsize, AtomicPtr, AtomicU8, AtomicU16,
+    AtomicU32, AtomicUsize, Ordering,
+};
+#[cfg(target_has_atomic = "64")]
+use std::sync::atomic::{AtomicI64, AtomicU64};
+
+/// Atomics whose current value can be loaded and logged.
+///
+/// Used by [`log_atomic!`](crate::log_atomic) to log any of the standard atomic types with an
+/// ordering chosen at the call site.
+pub trait AtomicLoad {
+    /// The type of the value held.
+    type Value: fmt::Debug;
+
+    /// Loads the current value with the given ordering, like the atomic's own `load`.
+    fn load_value(&self, order: Ordering) -> Self::Value;
+}
+
+impl<T: AtomicLoad + ?Sized> AtomicLoad for &T {
+    type Value = T::Value;
+
+    fn load_value(&self, order: Ordering) -> Self::Value {
+        T::load_value(self, order)
+    }
+}
+
+impl<T: AtomicLoad + ?Sized> AtomicLoad for std::sync::Arc<T> {
+    type Value = T::Value;
+
+    fn load_value(&self, order: Ordering) -> Self::Value {
+        T::load_value(self, order)
+    }
+}
+
+macro_rules! impl_atomic_load {
+    ($($(#[$attr:meta])* $atomic:ty => $value:ty),+ $(,)?) => {
+        $(
+            $(#[$attr])*
+            impl AtomicLoad for $atomic {
+                type Value = $value;
+
+                fn load_value(&self, order: Ordering) -> $value {
+                    self.load(order)
+                }
+            }
+        )+
+    };
+}
+
+impl_atomic_load! {
+    AtomicBool => bool,
+    AtomicI8 => i8,
+    AtomicI16 => i16,
+    AtomicI32 => i32,
+    #[cfg(target_has_atomic = "64")]
+    AtomicI64 => i64,
+    AtomicIsize => isize,
+    AtomicU8 => u8,
+    AtomicU16 => u16,
+    AtomicU32 => u32,
+    #[cfg(target_has_atomic = "64")]
+    AtomicU64 => u64,
+    AtomicUsize => usize,
+}
+
+impl<T> AtomicLoad for AtomicPtr<T> {
+    type Value = *mut T;
+
+    fn load_value(&self, order: Ordering) -> *mut T {
+        self.load(order)
+    }
+}
+
+/// Macro to log the current value of an atomic.
+///
+/// This macro loads the atomic with the given ordering and logs `<label>=<value>` with the
+/// name of the function where it is invoked. Any of the standard atomic types can be logged,
+/// or anything else implementing [`AtomicLoad`](crate::AtomicLoad). Naming the ordering at the
+/// call site keeps the memory-ordering choice as visible as in the code around it.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_atomic;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static CONNECTIONS: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn accept() {
+///     CONNECTIONS.fetch_add(1, Ordering::Relaxed);
+///     log_atomic!(debug, &CONNECTIONS, Ordering::Relaxed, "connection count");
+///     // Logs: "connection count=1 [fn accept]"
+/// }
+/// # accept();
+/// ```
+/// # Notes
+/// - The atomic is only loaded if the record is going to be logged.
+#[macro_export]
+macro_rules! log_atomic {
+    ($level:ident, $atomic:expr, $order:expr, $($arg:tt)+) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}={:?}",
+            format_args!($($arg)+),
+            $crate::AtomicLoad::load_value($atomic, $order)
+        )
+    };
+}
@@ -0,0 +1,167 @@
+//! An audit trail of structured events, written as JSON lines to a file of its own.
+
+use std::fmt::{self, Write as _};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write as _};
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::Level;
+
+use crate::__private_api::{self, Field, Location};
+use crate::timestamp::{Timestamp, Timezone};
+use crate::{clock, config};
+
+static AUDIT_FILE: Mutex<Option<File>> = Mutex::new(None);
+/// Whether an audit file is open, so `audit!` can skip its work otherwise.
+static AUDIT_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Opens the file [`audit!`](crate::audit) writes to, creating it if needed and appending
+/// to it otherwise.
+///
+/// The audit trail is separate from the `log` sink: events go to this file whatever logger
+/// is installed and whatever its level filter. Calling this again switches to another file.
+/// If the file cannot be opened, the error is logged at error level, as well as returned,
+/// and events are not recorded until a file is set successfully.
+///
+/// # Example
+/// ```rust
+/// # let dir = std::env::temp_dir();
+/// tacky_borders_logger::set_audit_file(dir.join("audit.jsonl")).ok();
+/// ```
+#[track_caller]
+pub fn set_audit_file(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    let mut audit_file = AUDIT_FILE.lock().unwrap_or_else(|e| e.into_inner());
+    match OpenOptions::new().create(true).append(true).open(path) {
+        Ok(file) => {
+            *audit_file = Some(file);
+            AUDIT_ENABLED.store(true, Ordering::Relaxed);
+            Ok(())
+        }
+        Err(err) => {
+            *audit_file = None;
+            AUDIT_ENABLED.store(false, Ordering::Relaxed);
+            crate::log_caller(
+                Level::Error,
+                format_args!("cannot open audit file {}: {err}", path.display()),
+            );
+            Err(err)
+        }
+    }
+}
+
+#[doc(hidden)]
+pub fn audit_enabled() -> bool {
+    AUDIT_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Writes an event to the audit file as one JSON object on its own line.
+#[doc(hidden)]
+pub fn audit(event: &str, fields: &[Field], fn_name: &str, loc: &Location) {
+    let timestamp = Timestamp {
+        time: clock::system_now(),
+        timezone: Timezone::Utc,
+        millis: true,
+    };
+    let mut standard: [(&str, &dyn fmt::Display); 4] = [
+        ("ts", &timestamp),
+        ("event", &event),
+        ("fn", &fn_name),
+        ("module", &loc.1),
+    ];
+    // The sort is stable, so fields left out of the order keep their default one.
+    standard.sort_by_key(|&(name, _)| config::field_position(name).unwrap_or(usize::MAX));
+
+    let mut line = String::from("{");
+    for (name, value) in standard {
+        let _ = write!(line, "{}:{},", Json(&name), Json(value));
+    }
+    line.push_str(r#""fields":{"#);
+    for (i, (key, value)) in fields.iter().enumerate() {
+        let separator = if i == 0 { "" } else { "," };
+        let _ = write!(line, "{separator}{}:{}", Json(key), Json(value));
+    }
+    line.push_str("}}\n");
+
+    let mut audit_file = AUDIT_FILE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(file) = audit_file.as_mut()
+        && let Err(err) = file.write_all(line.as_bytes())
+    {
+        // The lock is not held while logging, in case the logger writes audit events itself.
+        drop(audit_file);
+        __private_api::emit(
+            Level::Error,
+            format_args!("cannot write audit event {event}: {err}"),
+            fn_name,
+            loc,
+        );
+    }
+}
+
+/// Renders a value's `Display` output as a JSON string.
+pub(crate) struct Json<'a>(pub(crate) &'a dyn fmt::Display);
+
+impl fmt::Display for Json<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        JsonEscape(f).write_fmt(format_args!("{}", self.0))?;
+        f.write_char('"')
+    }
+}
+
+/// Escapes text written through it for use inside a JSON string.
+struct JsonEscape<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+impl fmt::Write for JsonEscape<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            match c {
+                '"' => self.0.write_str("\\\"")?,
+                '\\' => self.0.write_str("\\\\")?,
+                '\n' => self.0.write_str("\\n")?,
+                '\r' => self.0.write_str("\\r")?,
+                '\t' => self.0.write_str("\\t")?,
+                c if c.is_control() => write!(self.0, "\\u{:04x}", c as u32)?,
+                c => self.0.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Macro to record an event in the audit trail.
+///
+/// This macro appends one JSON object to the file set with
+/// [`set_audit_file`](crate::set_audit_file), holding the time in UTC, the event name, the
+/// name of the function where it is invoked, its module and the given fields, e.g.
+/// `{"ts":"2025-01-31T08:15:00.123Z","event":"login","fn":"login","module":"app::auth",
+/// "fields":{"user":"admin","ok":"true"}}`. Events do not go through the `log` sink.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::audit;
+/// fn login(user: &str, ok: bool) {
+///     audit!("login", user = user, ok = ok);
+/// }
+/// ```
+/// # Notes
+/// - Field values can be any `Display` type and are recorded as JSON strings.
+/// - Without an audit file, the macro does nothing, and its fields are not evaluated.
+/// - Each event is written with a single `write` call on a file opened in append mode, and
+///   not buffered, so events are on disk in order even if the process crashes right after.
+#[macro_export]
+macro_rules! audit {
+    ($event:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        if $crate::__private_api::audit_enabled() {
+            let fn_name = $crate::function_name!();
+            $crate::__private_api::audit(
+                ::std::convert::AsRef::<str>::as_ref(&$event),
+                &[$((stringify!($key), &$value)),*],
+                &fn_name,
+                &(module_path!(), module_path!(), file!(), line!()),
+            );
+        }
+    };
+}
@@ -0,0 +1,159 @@
+//! Backtraces logged with only the frames relevant to the application.
+
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::fmt;
+use std::sync::RwLock;
+
+type FrameFilter = Box<dyn Fn(&str) -> bool + Send + Sync>;
+
+static FRAME_FILTER: RwLock<Option<FrameFilter>> = RwLock::new(None);
+
+/// Registers which frames [`error_bt!`](crate::error_bt) keeps in the backtraces it logs.
+///
+/// The filter receives the symbol name of each frame, such as `my_app::config::load`, and
+/// returns whether to show it. Until one is set, frames of the standard library, the Rust
+/// runtime and the C runtime are left out, which keeps the application's own frames and
+/// those of its other dependencies.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_backtrace_filter(|frame| frame.starts_with("tacky_borders::"));
+/// ```
+pub fn set_backtrace_filter<F>(filter: F)
+where
+    F: Fn(&str) -> bool + Send + Sync + 'static,
+{
+    *FRAME_FILTER.write().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(filter));
+}
+
+/// Whether the default filter keeps a frame: everything but the standard library and the
+/// runtime entry points below `main`.
+fn is_application_frame(name: &str) -> bool {
+    const RUNTIME: [&str; 5] = [
+        "main",
+        "_start",
+        "__libc_start_main",
+        "<unknown>",
+        "__scrt_common_main_seh",
+    ];
+    const STD_CRATES: [&str; 4] = ["std::", "core::", "alloc::", "fn("];
+
+    // Trait impls are written `<Type as Trait>::method`, and closures `&dyn Fn...`.
+    let path = name.trim_start_matches(['<', '&']);
+    let path = path.strip_prefix("dyn ").unwrap_or(path);
+    !(RUNTIME.contains(&name) || STD_CRATES.iter().any(|prefix| path.starts_with(prefix)))
+}
+
+/// A backtrace of the current thread, reduced to the frames the filter keeps; renders as
+/// one `<frame> at <file>:<line>:<column>` line per frame.
+#[doc(hidden)]
+pub struct FilteredBacktrace {
+    /// Symbol names and source locations, innermost first; `None` if the backtrace could
+    /// not be captured.
+    frames: Option<Vec<(String, Option<String>)>>,
+}
+
+impl FilteredBacktrace {
+    #[inline(never)]
+    pub fn capture() -> Self {
+        let backtrace = Backtrace::force_capture();
+        if backtrace.status() != BacktraceStatus::Captured {
+            return Self { frames: None };
+        }
+
+        // `Backtrace` has no stable way to walk its frames, so its rendering is parsed:
+        // a `<index>: <symbol>` line per frame, each followed by `at <location>` lines.
+        let mut frames = Vec::<(String, Option<String>)>::new();
+        for line in backtrace.to_string().lines() {
+            let line = line.trim();
+            if let Some(location) = line.strip_prefix("at ") {
+                if let Some((_, frame_location @ None)) = frames.last_mut() {
+                    *frame_location = Some(location.to_owned());
+                }
+            } else if let Some((index, name)) = line.split_once(": ")
+                && index.bytes().all(|b| b.is_ascii_digit())
+            {
+                frames.push((name.to_owned(), None));
+            }
+        }
+
+        // The frames capturing the backtrace are never of interest.
+        let internal = |name: &str| {
+            name.starts_with("std::backtrace")
+                || name.starts_with("tacky_borders_logger::")
+                || name.starts_with("<tacky_borders_logger::")
+        };
+        let start = frames
+            .iter()
+            .position(|(name, _)| !internal(name))
+            .unwrap_or(frames.len());
+        frames.drain(..start);
+
+        let filter = FRAME_FILTER.read().unwrap_or_else(|e| e.into_inner());
+        frames.retain(|(name, _)| match &*filter {
+            Some(filter) => filter(name),
+            None => is_application_frame(name),
+        });
+        Self {
+            frames: Some(frames),
+        }
+    }
+}
+
+impl fmt::Display for FilteredBacktrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(frames) = &self.frames else {
+            return f.write_str("\n  <backtrace unavailable>");
+        };
+        if frames.is_empty() {
+            return f.write_str("\n  <all frames filtered out>");
+        }
+        for (name, location) in frames {
+            match location {
+                Some(location) => write!(f, "\n  {name} at {location}")?,
+                None => write!(f, "\n  {name}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Macro to log an error with a backtrace of the application's frames.
+///
+/// This macro logs the message at error level with the name of the function where it is
+/// invoked, followed by a backtrace of the current thread, one frame per line, innermost
+/// first. Only the frames kept by the filter set with
+/// [`set_backtrace_filter`](crate::set_backtrace_filter) are shown; by default, those of the
+/// standard library and the runtime are left out, so the stack is short enough to read.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::error_bt;
+/// fn load_config() {
+///     error_bt!("config is corrupt");
+///     // Logs:
+///     // config is corrupt
+///     //   my_app::load_config at ./src/config.rs:12:5
+///     //   my_app::main at ./src/main.rs:4:5 [fn load_config]
+/// }
+/// # load_config();
+/// ```
+/// # Notes
+/// - The backtrace is captured whatever `RUST_BACKTRACE` is set to, and only if the record is
+///   going to be logged. Capturing and symbolizing it is slow, which suits errors but not
+///   hot paths.
+/// - Frames only have file and line information if the binary has debug info.
+#[macro_export]
+macro_rules! error_bt {
+    ($($arg:tt)+) => {
+        if $crate::__private_api::enabled($crate::__private_api::log::Level::Error) {
+            let backtrace = $crate::__private_api::FilteredBacktrace::capture();
+            $crate::__log!(
+                $crate::__private_api::log::Level::Error,
+                "{}{}",
+                format_args!($($arg)+),
+                backtrace
+            );
+        }
+    };
+}
@@ -0,0 +1,94 @@
+//! A marker line delimiting runs of a program in a shared log.
+
+use std::fmt;
+
+use crate::__private_api::Field;
+use crate::timestamp::Timestamp;
+
+/// Renders `=== RUN START pid=... start=... [version=...] host=... [key=value...] ===`; used by
+/// [`log_banner!`](crate::log_banner).
+#[doc(hidden)]
+pub struct Banner<'a> {
+    pub version: Option<&'a dyn fmt::Display>,
+    pub fields: &'a [Field<'a>],
+}
+
+impl fmt::Display for Banner<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "=== RUN START pid={} start=", std::process::id())?;
+        write!(f, "{}", Timestamp::utc_now())?;
+        if let Some(version) = self.version {
+            write!(f, " version={version}")?;
+        }
+        match hostname() {
+            Some(host) => write!(f, " host={host}")?,
+            None => f.write_str(" host=unknown")?,
+        }
+        for (key, value) in self.fields {
+            write!(f, " {key}={value}")?;
+        }
+        f.write_str(" ===")
+    }
+}
+
+/// Returns the name of this machine, if it can be determined.
+fn hostname() -> Option<String> {
+    #[cfg(unix)]
+    {
+        let mut buffer = [0u8; 256];
+        // SAFETY: the pointer and length describe `buffer`, which is writable.
+        let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+        if result == 0 {
+            let len = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+            return Some(String::from_utf8_lossy(&buffer[..len]).into_owned());
+        }
+        None
+    }
+    #[cfg(windows)]
+    {
+        std::env::var("COMPUTERNAME").ok()
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        std::env::var("HOSTNAME").ok()
+    }
+}
+
+/// Macro to log a marker line for the start of a run.
+///
+/// This macro logs a single line at info level, meant to be called once at the top of
+/// `main`, that log-splitting tools can match on to tell runs sharing a log file apart:
+/// `=== RUN START pid=<pid> start=<UTC timestamp> host=<hostname> ===`. The version of the
+/// program is included when given with `version:`, and further `key = value` fields,
+/// rendered with `Display`, are appended in order.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_banner;
+/// fn main() {
+///     log_banner!(version: env!("CARGO_PKG_VERSION"), config = "default.yaml");
+///     // Logs: "=== RUN START pid=4242 start=2025-01-31T08:15:00Z version=1.2.0
+///     //        host=workstation config=default.yaml === [fn main]"
+/// }
+/// ```
+/// # Notes
+/// - The timestamp is taken when the line is logged, not when the process started.
+#[macro_export]
+macro_rules! log_banner {
+    (version: $version:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        $crate::log_banner!(@ ::std::option::Option::Some(&$version as &dyn ::std::fmt::Display) $(, $key = $value)*)
+    };
+    ($($key:ident = $value:expr),* $(,)?) => {
+        $crate::log_banner!(@ ::std::option::Option::None $(, $key = $value)*)
+    };
+    (@ $version:expr $(, $key:ident = $value:expr)*) => {
+        $crate::__log!(
+            $crate::__private_api::log::Level::Info,
+            "{}",
+            $crate::__private_api::Banner {
+                version: $version,
+                fields: &[$((stringify!($key), &$value as &dyn ::std::fmt::Display)),*],
+            }
+        )
+    };
+}
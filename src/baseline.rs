@@ -0,0 +1,167 @@
+//! Comparing values against baselines recorded in a file by earlier runs.
+
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use log::Level;
+
+use crate::__private_api::{self, Location};
+
+/// Held while a baseline file is read and appended to, so two threads recording the same
+/// new baseline do not both append it.
+static BASELINE_FILE: Mutex<()> = Mutex::new(());
+
+/// Returns the baseline recorded for `name` in the file at `path`, a `name=value` per line,
+/// or, if there is none yet, records `value` as the baseline and returns `None`. A missing
+/// file is created.
+fn read_or_record(path: &Path, name: &str, value: f64) -> io::Result<Option<f64>> {
+    let _lock = BASELINE_FILE.lock().unwrap_or_else(|e| e.into_inner());
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let recorded = contents.lines().find_map(|line| {
+        let (key, recorded) = line.split_once('=')?;
+        (key.trim() == name).then(|| recorded.trim().parse::<f64>().ok())?
+    });
+    if recorded.is_none() {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let separator = if contents.is_empty() || contents.ends_with('\n') {
+            ""
+        } else {
+            "\n"
+        };
+        file.write_all(format!("{separator}{name}={value}\n").as_bytes())?;
+    }
+    Ok(recorded)
+}
+
+/// Renders a value's change from its baseline as `(baseline 100, +20%)`, or without the
+/// percentage for a baseline of zero.
+struct Change {
+    value: f64,
+    baseline: f64,
+}
+
+impl Change {
+    fn percent(&self) -> Option<f64> {
+        (self.baseline != 0.0).then(|| (self.value - self.baseline) / self.baseline.abs() * 100.0)
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.percent() {
+            Some(percent) => write!(f, "(baseline {}, {percent:+.0}%)", self.baseline),
+            None => write!(f, "(baseline {})", self.baseline),
+        }
+    }
+}
+
+/// Compares a value against its baseline and logs the result, for
+/// [`log_baseline!`](crate::log_baseline).
+#[doc(hidden)]
+pub fn log_baseline(
+    level: Level,
+    name: &str,
+    value: f64,
+    path: &Path,
+    threshold: f64,
+    fn_name: &str,
+    loc: &Location,
+) {
+    if !__private_api::enabled(level) && !__private_api::enabled(Level::Warn) {
+        return;
+    }
+    match read_or_record(path, name, value) {
+        Ok(Some(baseline)) => {
+            let change = Change { value, baseline };
+            if change.percent().is_some_and(|percent| percent > threshold) {
+                if __private_api::enabled(Level::Warn) {
+                    __private_api::emit(
+                        Level::Warn,
+                        format_args!("regression: {name}={value} {change}"),
+                        fn_name,
+                        loc,
+                    );
+                }
+            } else if __private_api::enabled(level) {
+                __private_api::emit(level, format_args!("{name}={value} {change}"), fn_name, loc);
+            }
+        }
+        Ok(None) => {
+            if __private_api::enabled(level) {
+                __private_api::emit(
+                    level,
+                    format_args!("{name}={value} (new baseline)"),
+                    fn_name,
+                    loc,
+                );
+            }
+        }
+        Err(e) => {
+            if __private_api::enabled(Level::Warn) {
+                __private_api::emit(
+                    Level::Warn,
+                    format_args!("cannot check {name} against {}: {e}", path.display()),
+                    fn_name,
+                    loc,
+                );
+            }
+        }
+    }
+}
+
+/// Macro to log a value compared against the baseline recorded for it by an earlier run.
+///
+/// This macro looks up the baseline for `name` in the given file, which holds one
+/// `name=value` per line, and logs `<name>=<value> (baseline <b>, <change>%)` at the given
+/// level with the name of the function where it is invoked. If the value exceeds the
+/// baseline by more than the threshold, 10% unless given with `threshold:` in percent, it
+/// logs `regression: <name>=<value> (baseline <b>, <change>%)` at warn level instead. If the
+/// file or the name is missing, the value is recorded as the new baseline and
+/// `<name>=<value> (new baseline)` is logged.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_baseline;
+/// # let baseline_file = std::env::temp_dir().join("log_baseline_doc.txt");
+/// # std::fs::write(&baseline_file, "parse_time_ms=100\n").unwrap();
+/// fn parse(parse_time_ms: u64, baseline_file: &std::path::Path) {
+///     log_baseline!(info, "parse_time_ms", parse_time_ms, baseline_file);
+///     // Logs at warn level: "regression: parse_time_ms=120 (baseline 100, +20%) [fn parse]"
+///     log_baseline!(info, "parse_time_ms", parse_time_ms, baseline_file, threshold: 25.0);
+///     // Logs: "parse_time_ms=120 (baseline 100, +20%) [fn parse]"
+/// }
+/// # parse(120, &baseline_file);
+/// ```
+/// # Notes
+/// - The value can be any primitive number, and is converted to `f64` with `as`; the
+///   threshold is an `f64`.
+/// - Higher values count as worse, as with times and sizes. Improvements are logged at the
+///   given level, and never update the recorded baseline: delete its line to reset it.
+/// - The path can be anything implementing `AsRef<Path>`. If the file cannot be read or
+///   written, `cannot check <name> against <path>: <error>` is logged at warn level.
+/// - Regressions are logged even if the given level is disabled, as long as warn level is
+///   not.
+#[macro_export]
+macro_rules! log_baseline {
+    ($level:ident, $name:expr, $value:expr, $file:expr $(,)?) => {
+        $crate::log_baseline!($level, $name, $value, $file, threshold: 10.0)
+    };
+    ($level:ident, $name:expr, $value:expr, $file:expr, threshold: $threshold:expr $(,)?) => {
+        $crate::__private_api::log_baseline(
+            $crate::__level!($level),
+            $name,
+            $value as f64,
+            ::std::convert::AsRef::<::std::path::Path>::as_ref(&$file),
+            $threshold,
+            &$crate::function_name!(),
+            &(module_path!(), module_path!(), file!(), line!()),
+        )
+    };
+}
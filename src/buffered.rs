@@ -0,0 +1,168 @@
+//! Scoped buffering of this crate's output, passed on to the logger when the scope ends.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::marker::PhantomData;
+
+use log::{Level, Record};
+
+use crate::__private_api::{self, Callsite};
+
+thread_local! {
+    /// One buffer per active scope on this thread, innermost last.
+    static BUFFERS: RefCell<Vec<Vec<Buffered>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A record held back by a [`LogBuffer`], with what is needed to log it later.
+struct Buffered {
+    level: Level,
+    line: String,
+    target: String,
+    module_path: Option<&'static str>,
+    file: &'static str,
+    line_number: u32,
+}
+
+/// Appends `line` to the innermost buffer on this thread and returns `true`, or returns
+/// `false` if there is none and the line should be logged now.
+pub(crate) fn buffer(level: Level, line: &dyn fmt::Display, callsite: &Callsite) -> bool {
+    if BUFFERS.with_borrow(Vec::is_empty) {
+        return false;
+    }
+    // Rendered before borrowing, in case formatting the message logs itself.
+    let record = Buffered {
+        level,
+        line: line.to_string(),
+        target: callsite.target.to_owned(),
+        module_path: callsite.module_path,
+        file: callsite.file,
+        line_number: callsite.line,
+    };
+    BUFFERS.with_borrow_mut(|buffers| {
+        if let Some(buffer) = buffers.last_mut() {
+            buffer.push(record);
+        }
+    });
+    true
+}
+
+/// Passes a buffered record on to the enclosing buffer, if any, or else on to the sinks and
+/// the logger, as it would have been when it was logged.
+fn deliver(record: Buffered, outer: usize) {
+    let delivered = BUFFERS.with_borrow_mut(|buffers| match outer {
+        0 => Err(record),
+        outer => {
+            buffers[outer - 1].push(record);
+            Ok(())
+        }
+    });
+    let Err(record) = delivered else {
+        return;
+    };
+
+    __private_api::output(
+        record.level,
+        &record.line,
+        &Record::builder()
+            .level(record.level)
+            .target(&record.target)
+            .module_path(record.module_path)
+            .file_static(Some(record.file))
+            .line(Some(record.line_number))
+            .args(format_args!("{}", record.line))
+            .build(),
+    );
+}
+
+/// A scope started with [`buffer_logs!`](crate::buffer_logs), which holds back what this
+/// crate logs on the current thread until it is flushed or dropped.
+///
+/// Dropping the guard, whether the scope ends normally, returns early or unwinds from a
+/// panic, logs every line still held back, so none are lost. Buffers are kept per thread,
+/// so a guard cannot be sent to another thread.
+#[must_use = "the buffer is flushed as soon as the guard is dropped"]
+pub struct LogBuffer {
+    depth: usize,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl LogBuffer {
+    #[doc(hidden)]
+    pub fn __start() -> Self {
+        let depth = BUFFERS.with_borrow_mut(|buffers| {
+            buffers.push(Vec::new());
+            buffers.len()
+        });
+        Self {
+            depth,
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Logs every line held back so far, in order, and keeps buffering.
+    pub fn flush(&self) {
+        let lines = BUFFERS.with_borrow_mut(|buffers| {
+            buffers
+                .get_mut(self.depth - 1)
+                .map(std::mem::take)
+                .unwrap_or_default()
+        });
+        for record in lines {
+            deliver(record, self.depth - 1);
+        }
+    }
+}
+
+impl Drop for LogBuffer {
+    fn drop(&mut self) {
+        let lines = BUFFERS.with_borrow_mut(|buffers| {
+            // Scopes end innermost first, unless a guard was leaked; flush any left above this
+            // one into it first.
+            let mut lines = Vec::new();
+            while buffers.len() >= self.depth {
+                let mut buffer = buffers.pop().unwrap_or_default();
+                buffer.append(&mut lines);
+                lines = buffer;
+            }
+            lines
+        });
+        for record in lines {
+            deliver(record, self.depth - 1);
+        }
+    }
+}
+
+/// Macro to hold back what this crate logs on the current thread until the returned guard is
+/// flushed or dropped.
+///
+/// This macro starts a buffered scope and returns a [`LogBuffer`](crate::LogBuffer). Records
+/// logged through this crate's macros on the thread are kept in memory instead of being
+/// passed to the logger, until [`LogBuffer::flush`](crate::LogBuffer::flush) logs those held
+/// so far, or the guard is dropped, which logs the rest. Each record keeps its own message,
+/// function name suffix and call site. Dropping the guard flushes even on an early `return`,
+/// `?` or panic, so buffering never loses lines.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{buffer_logs, debug};
+/// fn parse(input: &str) -> Option<u32> {
+///     let _buffer = buffer_logs!();
+///     debug!("parsing {:?}", input);
+///     let value = input.parse().ok()?;
+///     // Both lines are logged here, or when `?` returns early.
+///     debug!("parsed {}", value);
+///     Some(value)
+/// }
+/// # parse("42");
+/// ```
+/// # Notes
+/// - Bind the guard to a named variable such as `_buffer`; `let _ = buffer_logs!()` drops it,
+///   and ends the scope, immediately.
+/// - Buffered scopes nest: an inner scope flushes into the enclosing one.
+/// - With the `kv` feature, key-value pairs are not kept for buffered records.
+#[macro_export]
+macro_rules! buffer_logs {
+    () => {
+        $crate::LogBuffer::__start()
+    };
+}
@@ -0,0 +1,53 @@
+//! Logging from user-written wrappers, attributed to the wrapper's caller.
+
+use std::fmt;
+use std::panic::Location;
+
+use log::Level;
+
+use crate::__private_api::{self, Callsite, Suffix};
+
+/// Logs a message attributed to the caller of a `#[track_caller]` function.
+///
+/// The logging macros capture the function they are written in, so when they are wrapped in a
+/// helper, every line reports the helper. This function instead uses
+/// [`Location::caller`] to find the real call site: when it is called from a function marked
+/// `#[track_caller]`, the record's file and line point at whoever called that function, and the
+/// message ends with `[at file:line]`.
+///
+/// # Example
+/// ```rust
+/// use log::Level;
+///
+/// #[track_caller]
+/// fn log_failure(what: &str) {
+///     tacky_borders_logger::log_caller(Level::Error, format_args!("{what} failed"));
+/// }
+///
+/// fn apply_borders() {
+///     log_failure("DwmSetWindowAttribute");
+///     // Logs: "DwmSetWindowAttribute failed [at src/main.rs:8]"
+/// }
+/// ```
+/// # Notes
+/// - `Location` only provides the file, line and column, not the function name, so this
+///   complements `function_name!()` rather than replacing it.
+/// - Every function between the wrapper and this call must be `#[track_caller]` as well, or the
+///   reported location stops at the first one that is not.
+/// - As the caller's module is unknown, the record's target is its file path.
+#[track_caller]
+pub fn log_caller(level: Level, args: fmt::Arguments) {
+    if !__private_api::enabled(level) {
+        return;
+    }
+
+    let caller = Location::caller();
+    let callsite = Callsite {
+        target: caller.file(),
+        module_path: None,
+        module_label: None,
+        file: caller.file(),
+        line: caller.line(),
+    };
+    __private_api::dispatch(level, args, Suffix::Caller(caller), &callsite, &[]);
+}
@@ -0,0 +1,118 @@
+//! Scoped capture of this crate's output into memory.
+
+use std::cell::RefCell;
+use std::fmt;
+
+use log::Level;
+
+thread_local! {
+    /// One buffer per active capture on this thread, innermost last.
+    static BUFFERS: RefCell<Vec<Vec<(Level, String)>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Returns `true` if a capture is active on this thread.
+pub(crate) fn active() -> bool {
+    BUFFERS.with_borrow(|buffers| !buffers.is_empty())
+}
+
+/// Appends `line` to every active capture on this thread and returns `true`, or returns
+/// `false` if there is none and the line should go to the logger.
+pub(crate) fn capture(level: Level, line: &dyn fmt::Display) -> bool {
+    if !active() {
+        return false;
+    }
+    // Rendered before borrowing, in case formatting the message logs itself.
+    let line = line.to_string();
+    BUFFERS.with_borrow_mut(|buffers| {
+        for buffer in buffers.iter_mut() {
+            buffer.push((level, line.clone()));
+        }
+    });
+    true
+}
+
+/// An active capture, started by [`capture_logs!`](crate::capture_logs). Dropping it, e.g.
+/// when the block panics, ends the capture and discards its lines.
+#[doc(hidden)]
+pub struct Capture {
+    depth: usize,
+}
+
+impl Capture {
+    #[doc(hidden)]
+    pub fn __start() -> Self {
+        let depth = BUFFERS.with_borrow_mut(|buffers| {
+            buffers.push(Vec::new());
+            buffers.len()
+        });
+        Self { depth }
+    }
+
+    /// Ends the capture and returns its lines.
+    #[doc(hidden)]
+    pub fn __finish(self) -> Vec<String> {
+        let lines = self.__finish_with_levels();
+        lines.into_iter().map(|(_, line)| line).collect()
+    }
+
+    /// Ends the capture and returns its lines along with their levels.
+    #[doc(hidden)]
+    pub fn __finish_with_levels(self) -> Vec<(Level, String)> {
+        let lines = self.end();
+        std::mem::forget(self);
+        lines
+    }
+
+    fn end(&self) -> Vec<(Level, String)> {
+        BUFFERS.with_borrow_mut(|buffers| {
+            // Captures end innermost first, unless one was leaked; drop any left above this one.
+            buffers.truncate(self.depth);
+            buffers.pop().unwrap_or_default()
+        })
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        self.end();
+    }
+}
+
+/// Macro to capture what this crate logs on the current thread while a block runs.
+///
+/// This macro runs the block and returns the messages logged through this crate's macros
+/// during it, in order, as a `Vec<String>`. Each string is the full message, suffix
+/// included, as the logger would have received it. Captured records are not passed to the
+/// logger, though they are still kept for the crash report, and once the block ends, logging
+/// goes back to it.
+///
+/// Captures nest: a record logged inside an inner capture is also captured by every outer
+/// one, so the outer buffer always holds everything logged within its block.
+///
+/// # Example
+#[cfg_attr(feature = "defmt", doc = "```ignore")]
+#[cfg_attr(not(feature = "defmt"), doc = "```rust")]
+/// # use tacky_borders_logger::{capture_logs, warn};
+/// fn apply_theme() {
+///     warn!("theme not found, using default");
+/// }
+///
+/// let lines = capture_logs!({
+///     apply_theme();
+/// });
+/// assert_eq!(lines, ["theme not found, using default [fn apply_theme]"]);
+/// ```
+/// # Notes
+/// - While a capture is active, every level that passes `log`'s compile-time filter is
+///   captured, whatever the runtime level filter, so this works without a logger installed.
+///   [`set_logging_enabled`](crate::set_logging_enabled) still applies.
+/// - Only the current thread is captured; records logged by threads the block spawns go to the
+///   logger as usual.
+#[macro_export]
+macro_rules! capture_logs {
+    ($block:expr $(,)?) => {{
+        let capture = $crate::__private_api::Capture::__start();
+        let _ = $block;
+        capture.__finish()
+    }};
+}
@@ -0,0 +1,209 @@
+//! Span timing written as Chrome trace events, for viewing in `chrome://tracing` or Perfetto.
+
+use std::cell::Cell;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write as _};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use log::Level;
+
+use crate::audit::Json;
+use crate::clock;
+use crate::span::SpanGuard;
+
+static TRACE_FILE: Mutex<Option<BufWriter<File>>> = Mutex::new(None);
+/// Whether a trace file is open, so spans can skip writing events otherwise.
+static TRACE_ENABLED: AtomicBool = AtomicBool::new(false);
+static NEXT_TID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    /// This thread's id in the trace, assigned when it first writes an event.
+    static TID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Creates the file [`chrome_span!`](crate::chrome_span) writes trace events to, replacing
+/// any existing one.
+///
+/// The file uses the JSON array format of Chrome's trace events: it starts with `[`, and
+/// every span adds a `B` event on entry and an `E` event on exit, one per line, each with a
+/// timestamp in microseconds, the process and thread ids, and the name of the function the
+/// span was entered in. The closing `]` is left out, which `chrome://tracing` and Perfetto
+/// accept, so the file stays loadable however the process ends.
+///
+/// Events are buffered in memory and only written in batches. Call
+/// [`flush_chrome_trace`](crate::flush_chrome_trace) before the process exits, and before
+/// loading a trace that is still being written, or the latest events are missing. Calling
+/// this again flushes the previous file and switches to another one. If the file cannot be
+/// created, the error is logged at error level, as well as returned, and no events are
+/// written until a file is set successfully.
+///
+/// # Example
+/// ```rust
+/// # let dir = std::env::temp_dir();
+/// tacky_borders_logger::set_chrome_trace_file(dir.join("trace.json")).ok();
+/// ```
+#[track_caller]
+pub fn set_chrome_trace_file(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    let mut trace_file = TRACE_FILE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(mut previous) = trace_file.take() {
+        let _ = previous.flush();
+    }
+    TRACE_ENABLED.store(false, Ordering::Relaxed);
+
+    let opened = File::create(path).and_then(|file| {
+        let mut file = BufWriter::new(file);
+        file.write_all(b"[\n")?;
+        Ok(file)
+    });
+    match opened {
+        Ok(file) => {
+            *trace_file = Some(file);
+            TRACE_ENABLED.store(true, Ordering::Relaxed);
+            Ok(())
+        }
+        Err(err) => {
+            drop(trace_file);
+            crate::log_caller(
+                Level::Error,
+                format_args!("cannot create chrome trace file {}: {err}", path.display()),
+            );
+            Err(err)
+        }
+    }
+}
+
+/// Writes the trace events buffered so far to the file set with
+/// [`set_chrome_trace_file`](crate::set_chrome_trace_file).
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::flush_chrome_trace();
+/// ```
+pub fn flush_chrome_trace() {
+    let mut trace_file = TRACE_FILE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(file) = trace_file.as_mut() {
+        let _ = file.flush();
+    }
+}
+
+/// Returns the microseconds since the first trace event, which Chrome's viewers take as the
+/// start of the trace.
+fn timestamp_us(now: Instant) -> u128 {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    now.saturating_duration_since(*EPOCH.get_or_init(|| now))
+        .as_micros()
+}
+
+fn thread_id() -> u64 {
+    TID.with(|tid| {
+        if tid.get() == 0 {
+            tid.set(NEXT_TID.fetch_add(1, Ordering::Relaxed));
+        }
+        tid.get()
+    })
+}
+
+/// Writes one trace event, of phase `B` or `E`, as a line of the trace file.
+fn write_event(phase: char, name: &str, fn_name: &str) {
+    if !TRACE_ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut event = String::new();
+    let _ = writeln!(
+        event,
+        r#"{{"name":{},"cat":"span","ph":"{phase}","ts":{},"pid":{},"tid":{},"args":{{"fn":{}}}}},"#,
+        Json(&name),
+        timestamp_us(clock::now()),
+        std::process::id(),
+        thread_id(),
+        Json(&fn_name),
+    );
+
+    let mut trace_file = TRACE_FILE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(file) = trace_file.as_mut()
+        && let Err(err) = file.write_all(event.as_bytes())
+    {
+        // Stop writing to a broken file rather than logging an error for every event.
+        *trace_file = None;
+        TRACE_ENABLED.store(false, Ordering::Relaxed);
+        drop(trace_file);
+        crate::log_caller(
+            Level::Error,
+            format_args!("cannot write chrome trace event: {err}"),
+        );
+    }
+}
+
+/// A scope entered with [`chrome_span!`](crate::chrome_span), which logs its exit and
+/// writes its end event when dropped.
+///
+/// Spans are tracked per thread, so a guard cannot be sent to another thread.
+#[must_use = "the span is exited as soon as the guard is dropped"]
+pub struct ChromeSpan {
+    name: String,
+    fn_name: String,
+    _span: SpanGuard,
+}
+
+impl ChromeSpan {
+    #[doc(hidden)]
+    pub fn __enter(
+        level: Level,
+        name: String,
+        fn_name: String,
+        loc: crate::__private_api::Location<'static>,
+    ) -> Self {
+        write_event('B', &name, &fn_name);
+        Self {
+            _span: SpanGuard::__enter(level, name.clone(), fn_name.clone(), loc),
+            name,
+            fn_name,
+        }
+    }
+}
+
+impl Drop for ChromeSpan {
+    fn drop(&mut self) {
+        write_event('E', &self.name, &self.fn_name);
+    }
+}
+
+/// Macro to enter a span that is also recorded as Chrome trace events.
+///
+/// This macro works like [`log_span!`](crate::log_span), logging `enter <name>` now and
+/// `exit <name> (<elapsed>)` when the returned [`ChromeSpan`](crate::ChromeSpan) is dropped,
+/// and in addition writes a `B` event on entry and an `E` event on exit to the file set with
+/// [`set_chrome_trace_file`](crate::set_chrome_trace_file). Loaded in `chrome://tracing` or
+/// Perfetto, nested spans then show up as a flame graph per thread. Events are written
+/// whatever the level, as long as a trace file is set.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::chrome_span;
+/// fn render_frame() {
+///     let _span = chrome_span!(trace, "render frame");
+///     // Writes: {"name":"render frame","cat":"span","ph":"B","ts":1200,...,"args":{"fn":"render_frame"}},
+/// }   // Writes the matching "ph":"E" event.
+/// # render_frame();
+/// ```
+/// # Notes
+/// - Bind the guard to a named variable such as `_span`; `let _ = chrome_span!(...)` drops
+///   it, and exits the span, immediately.
+/// - Events are buffered; see [`set_chrome_trace_file`](crate::set_chrome_trace_file) for
+///   when to flush them.
+#[macro_export]
+macro_rules! chrome_span {
+    ($level:ident, $($arg:tt)+) => {
+        $crate::ChromeSpan::__enter(
+            $crate::__level!($level),
+            ::std::format!($($arg)+),
+            $crate::function_name!(),
+            (module_path!(), module_path!(), file!(), line!()),
+        )
+    };
+}
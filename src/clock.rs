@@ -0,0 +1,108 @@
+//! The clocks read by the timing macros, which tests can replace with fake ones.
+
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+type Clock<T> = Box<dyn Fn() -> T + Send + Sync>;
+
+static CLOCK: RwLock<Option<Clock<Instant>>> = RwLock::new(None);
+static SYSTEM_CLOCK: RwLock<Option<Clock<SystemTime>>> = RwLock::new(None);
+/// Whether either clock was replaced, so the real clocks can be read without taking a lock.
+static REPLACED: AtomicBool = AtomicBool::new(false);
+
+/// Replaces the monotonic clock read by the timing macros.
+///
+/// Durations logged by [`log_duration!`](crate::log_duration),
+/// [`log_span!`](crate::log_span) or [`set_include_delta`](crate::set_include_delta), and
+/// the intervals of the rate-limiting macros such as [`heartbeat!`](crate::heartbeat), are
+/// measured with `clock` instead of [`Instant::now`] from then on. Installing a fake clock in
+/// tests, one the test advances by hand, makes their output deterministic.
+///
+/// # Example
+/// ```rust
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use std::time::{Duration, Instant};
+///
+/// static FAKE_MILLIS: AtomicU64 = AtomicU64::new(0);
+///
+/// let base = Instant::now();
+/// tacky_borders_logger::set_clock(move || {
+///     base + Duration::from_millis(FAKE_MILLIS.load(Ordering::Relaxed))
+/// });
+/// // Every `log_duration!` block now takes exactly as long as the test advances the clock.
+/// FAKE_MILLIS.fetch_add(250, Ordering::Relaxed);
+/// ```
+/// # Notes
+/// - The clock is process-wide; tests replacing it should not run in parallel with tests
+///   that rely on real time.
+/// - Durations are clamped to zero if the clock goes backwards.
+pub fn set_clock<F>(clock: F)
+where
+    F: Fn() -> Instant + Send + Sync + 'static,
+{
+    *CLOCK.write().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(clock));
+    REPLACED.store(true, Ordering::Release);
+}
+
+/// Replaces the wall clock read for the timestamps this crate renders itself.
+///
+/// The timestamps appended with [`set_include_timestamp`](crate::set_include_timestamp) and
+/// the start time in [`log_banner!`](crate::log_banner) are read from `clock` instead of
+/// [`SystemTime::now`] from then on, so tests can assert on them exactly.
+///
+/// # Example
+/// ```rust
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// tacky_borders_logger::set_system_clock(|| UNIX_EPOCH + Duration::from_secs(1_738_311_300));
+/// // Timestamps now read `2025-01-31T08:15:00.000Z`.
+/// ```
+pub fn set_system_clock<F>(clock: F)
+where
+    F: Fn() -> SystemTime + Send + Sync + 'static,
+{
+    *SYSTEM_CLOCK.write().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(clock));
+    REPLACED.store(true, Ordering::Release);
+}
+
+/// Restores the real clocks after [`set_clock`] or [`set_system_clock`].
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::reset_clock();
+/// ```
+pub fn reset_clock() {
+    *CLOCK.write().unwrap_or_else(|e| e.into_inner()) = None;
+    *SYSTEM_CLOCK.write().unwrap_or_else(|e| e.into_inner()) = None;
+    REPLACED.store(false, Ordering::Release);
+}
+
+/// Returns the current time of the monotonic clock.
+#[doc(hidden)]
+pub fn now() -> Instant {
+    if !REPLACED.load(Ordering::Acquire) {
+        return Instant::now();
+    }
+    match &*CLOCK.read().unwrap_or_else(|e| e.into_inner()) {
+        Some(clock) => clock(),
+        None => Instant::now(),
+    }
+}
+
+/// Returns the time elapsed since `start` on the monotonic clock, or zero if it went backwards.
+#[doc(hidden)]
+pub fn elapsed_since(start: Instant) -> Duration {
+    now().saturating_duration_since(start)
+}
+
+/// Returns the current time of the wall clock.
+pub(crate) fn system_now() -> SystemTime {
+    if !REPLACED.load(Ordering::Acquire) {
+        return SystemTime::now();
+    }
+    match &*SYSTEM_CLOCK.read().unwrap_or_else(|e| e.into_inner()) {
+        Some(clock) => clock(),
+        None => SystemTime::now(),
+    }
+}
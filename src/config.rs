@@ -0,0 +1,851 @@
+//! Runtime configuration for the logging macros.
+//!
+//! Every option defaults to the crate's original behaviour and can be changed at any point
+//! while the program is running; the macros read the current value on every call.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+use log::Level;
+
+use crate::timestamp::Timezone;
+
+static LOGGING_ENABLED: AtomicBool = AtomicBool::new(true);
+static ENABLED_LEVELS: AtomicU8 = AtomicU8::new(0b1_1111);
+static FLUSH_ON: AtomicUsize = AtomicUsize::new(0);
+static MAX_MESSAGE_LEN: AtomicUsize = AtomicUsize::new(usize::MAX);
+static DEDUP_CONSECUTIVE: AtomicBool = AtomicBool::new(false);
+static DEDUP_IGNORES_SUFFIX: AtomicBool = AtomicBool::new(false);
+static INCLUDE_DELTA: AtomicBool = AtomicBool::new(false);
+static INCLUDE_SPAN_ELAPSED: AtomicBool = AtomicBool::new(false);
+static INCLUDE_SEQ: AtomicBool = AtomicBool::new(false);
+static INCLUDE_THREAD: AtomicBool = AtomicBool::new(false);
+static INCLUDE_PROFILE: AtomicBool = AtomicBool::new(false);
+static INCLUDE_TRACEPARENT: AtomicBool = AtomicBool::new(false);
+#[cfg(feature = "tokio")]
+static INCLUDE_TASK_ID: AtomicBool = AtomicBool::new(false);
+static FN_NAME_IN_TARGET: AtomicBool = AtomicBool::new(false);
+static INCLUDE_TIMESTAMP: AtomicBool = AtomicBool::new(false);
+static TIMESTAMP_TIMEZONE: RwLock<Timezone> = RwLock::new(Timezone::Utc);
+static SPAN_STATS: AtomicBool = AtomicBool::new(false);
+static INDENT_WITH_DEPTH: AtomicBool = AtomicBool::new(false);
+static FN_SUFFIX_STYLE: AtomicU8 = AtomicU8::new(FnSuffixStyle::Name as u8);
+static NEWLINE_NORMALIZATION: AtomicBool = AtomicBool::new(false);
+static SECRET_DETECTION: AtomicBool = AtomicBool::new(false);
+static NEWLINE_STYLE: AtomicU8 = AtomicU8::new(NewlineStyle::Lf as u8);
+static FN_SUFFIX_FORMAT: RwLock<Option<Arc<SuffixFormat>>> = RwLock::new(None);
+static ONCE_PER_CAPACITY: AtomicUsize = AtomicUsize::new(usize::MAX);
+static EVERY_BY_MSG_CAPACITY: AtomicUsize = AtomicUsize::new(usize::MAX);
+static LOG_SMART_THRESHOLD: AtomicUsize = AtomicUsize::new(1024);
+static ADAPTIVE_RATE: AtomicU32 = AtomicU32::new(10);
+static SUFFIX_LEVELS: AtomicU8 = AtomicU8::new(0b1_1111);
+static FN_SUFFIX_DENYLIST: RwLock<Vec<String>> = RwLock::new(Vec::new());
+static FIELD_ORDER: RwLock<Vec<String>> = RwLock::new(Vec::new());
+static BUILD_ID: RwLock<Option<Arc<str>>> = RwLock::new(None);
+
+/// Enables or disables all logging done through this crate.
+///
+/// While disabled, every macro returns before doing any work, including capturing the function
+/// name, regardless of `log`'s level filter. This is cheaper and more absolute than adjusting
+/// the level filter, and leaves logging done directly through `log` untouched. Logging is
+/// enabled by default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_logging_enabled(false);
+/// // ... sensitive operation ...
+/// tacky_borders_logger::set_logging_enabled(true);
+/// ```
+pub fn set_logging_enabled(enabled: bool) {
+    LOGGING_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns `true` unless logging was disabled with [`set_logging_enabled`].
+pub fn logging_enabled() -> bool {
+    LOGGING_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Enables or disables logging at a single level.
+///
+/// Unlike `log`'s level filter, which is a threshold, this allows any combination of levels,
+/// such as trace and error without the debug, info and warn lines in between. A record is only
+/// logged if both its level is enabled here and it passes `log`'s filter. Every level is
+/// enabled by default.
+///
+/// # Example
+/// ```rust
+/// use log::Level;
+///
+/// for level in [Level::Debug, Level::Info, Level::Warn] {
+///     tacky_borders_logger::set_level_enabled(level, false);
+/// }
+/// ```
+pub fn set_level_enabled(level: Level, enabled: bool) {
+    let bit = level_bit(level);
+    if enabled {
+        ENABLED_LEVELS.fetch_or(bit, Ordering::Relaxed);
+    } else {
+        ENABLED_LEVELS.fetch_and(!bit, Ordering::Relaxed);
+    }
+}
+
+/// Returns `true` unless `level` was disabled with [`set_level_enabled`].
+pub fn level_enabled(level: Level) -> bool {
+    ENABLED_LEVELS.load(Ordering::Relaxed) & level_bit(level) != 0
+}
+
+fn level_bit(level: Level) -> u8 {
+    1 << (level as usize - 1)
+}
+
+/// Sets the maximum length, in bytes, of a formatted message.
+///
+/// Messages longer than `max` are cut at the nearest char boundary at or below the limit and
+/// followed by `… (truncated, N bytes total)`, where `N` is the length of the original message.
+/// The function-name suffix is appended after truncation, so it is always preserved.
+///
+/// Passing `None` disables truncation, which is the default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_max_message_len(Some(4096));
+/// ```
+/// # Notes
+/// - This is a safety valve for accidental giant dumps, such as `{:#?}` on a large structure.
+pub fn set_max_message_len(max: Option<usize>) {
+    MAX_MESSAGE_LEN.store(max.unwrap_or(usize::MAX), Ordering::Relaxed);
+}
+
+pub(crate) fn max_message_len() -> Option<usize> {
+    match MAX_MESSAGE_LEN.load(Ordering::Relaxed) {
+        usize::MAX => None,
+        max => Some(max),
+    }
+}
+
+/// Sets the level at or above which the logger is flushed after each record.
+///
+/// After logging a record at `level` or a more severe one, the macros call
+/// `log::logger().flush()`, so critical messages reach a buffered sink promptly. Passing
+/// `None` disables this, which is the default.
+///
+/// # Example
+/// ```rust
+/// # use log::Level;
+/// tacky_borders_logger::set_flush_on(Level::Error);
+/// ```
+/// # Notes
+/// - A flush typically means a write system call, or waiting for a background writer, on the
+///   logging thread. Keep the level high enough that flushing stays rare; flushing on every
+///   line defeats the purpose of a buffered sink.
+pub fn set_flush_on(level: impl Into<Option<Level>>) {
+    let level = level.into().map_or(0, |level| level as usize);
+    FLUSH_ON.store(level, Ordering::Relaxed);
+}
+
+pub(crate) fn flushes_on(level: Level) -> bool {
+    level as usize <= FLUSH_ON.load(Ordering::Relaxed)
+}
+
+/// Enables or disables suppression of consecutive identical messages.
+///
+/// While enabled, each thread remembers the last message it logged. A message identical to it,
+/// including its fields and suffix, is not logged but counted, and once a different message is
+/// logged on that thread, or [`flush_dedup`](crate::flush_dedup) is called, the count is
+/// reported as `last message repeated N times`. Disabled by default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_dedup_consecutive(true);
+/// ```
+pub fn set_dedup_consecutive(enabled: bool) {
+    DEDUP_CONSECUTIVE.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn dedup_consecutive() -> bool {
+    DEDUP_CONSECUTIVE.load(Ordering::Relaxed)
+}
+
+/// Sets whether [`set_dedup_consecutive`] ignores the function-name suffix when comparing
+/// messages, so the same line logged from different functions also counts as a repeat.
+/// Defaults to `false`.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_dedup_ignores_suffix(true);
+/// ```
+pub fn set_dedup_ignores_suffix(ignore: bool) {
+    DEDUP_IGNORES_SUFFIX.store(ignore, Ordering::Relaxed);
+}
+
+pub(crate) fn dedup_ignores_suffix() -> bool {
+    DEDUP_IGNORES_SUFFIX.load(Ordering::Relaxed)
+}
+
+/// Caps the number of keys remembered by [`log_once_per!`](crate::log_once_per) and the
+/// `*_once_per!` macros.
+///
+/// Once the cap is reached, the least recently seen key is forgotten for each new one, so a
+/// forgotten key is logged again when it next shows up. Passing `None` remembers every key,
+/// which is the default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_once_per_capacity(Some(10_000));
+/// ```
+pub fn set_once_per_capacity(capacity: Option<usize>) {
+    ONCE_PER_CAPACITY.store(capacity.unwrap_or(usize::MAX), Ordering::Relaxed);
+}
+
+pub(crate) fn once_per_capacity() -> Option<usize> {
+    match ONCE_PER_CAPACITY.load(Ordering::Relaxed) {
+        usize::MAX => None,
+        capacity => Some(capacity),
+    }
+}
+
+/// Caps the number of distinct messages remembered by
+/// [`log_every_by_msg!`](crate::log_every_by_msg) and `warn_every_by_msg!`.
+///
+/// Once the cap is reached, the message logged longest ago is forgotten for each new one, so
+/// a forgotten message is logged again, even within its interval, when it next shows up.
+/// Passing `None` remembers every message, which is the default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_every_by_msg_capacity(Some(1_000));
+/// ```
+pub fn set_every_by_msg_capacity(capacity: Option<usize>) {
+    EVERY_BY_MSG_CAPACITY.store(capacity.unwrap_or(usize::MAX), Ordering::Relaxed);
+}
+
+pub(crate) fn every_by_msg_capacity() -> Option<usize> {
+    match EVERY_BY_MSG_CAPACITY.load(Ordering::Relaxed) {
+        usize::MAX => None,
+        capacity => Some(capacity),
+    }
+}
+
+/// Sets the number of lines per second each call site of
+/// [`log_adaptive!`](crate::log_adaptive) aims to log at most. Defaults to 10.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_adaptive_rate(50);
+/// ```
+pub fn set_adaptive_rate(per_second: u32) {
+    ADAPTIVE_RATE.store(per_second, Ordering::Relaxed);
+}
+
+pub(crate) fn adaptive_rate() -> u32 {
+    ADAPTIVE_RATE.load(Ordering::Relaxed)
+}
+
+/// Sets the size, in bytes of `{:?}` output, above which [`log_smart!`](crate::log_smart)
+/// summarizes a value instead of logging it in full. The summary shows this many bytes of the
+/// value. Defaults to 1024.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_log_smart_threshold(256);
+/// ```
+pub fn set_log_smart_threshold(threshold: usize) {
+    LOG_SMART_THRESHOLD.store(threshold, Ordering::Relaxed);
+}
+
+pub(crate) fn log_smart_threshold() -> usize {
+    LOG_SMART_THRESHOLD.load(Ordering::Relaxed)
+}
+
+/// Enables or disables appending the time since the previous record on the same thread.
+///
+/// While enabled, every message ends with `+<elapsed>`, e.g. `+12.30ms`, after the
+/// function-name suffix, which turns an ordinary trace into a rough timing profile. The first
+/// record on a thread shows `+0ms`. Disabled by default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_include_delta(true);
+/// ```
+pub fn set_include_delta(enabled: bool) {
+    INCLUDE_DELTA.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn include_delta() -> bool {
+    INCLUDE_DELTA.load(Ordering::Relaxed)
+}
+
+/// Enables or disables appending the time since the innermost span on the thread was entered.
+///
+/// While enabled, every message logged inside a [`log_span!`](crate::log_span) scope ends with
+/// `+<elapsed>`, e.g. `+12.30ms`, measured from when the innermost span was entered, after the
+/// function-name suffix. Messages logged outside any span are left as they are. This times
+/// each line relative to the operation it belongs to. With
+/// [`set_include_delta`](crate::set_include_delta) also enabled, this comes first. Disabled by
+/// default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_include_span_elapsed(true);
+/// ```
+pub fn set_include_span_elapsed(enabled: bool) {
+    INCLUDE_SPAN_ELAPSED.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn include_span_elapsed() -> bool {
+    INCLUDE_SPAN_ELAPSED.load(Ordering::Relaxed)
+}
+
+/// Enables or disables appending a process-wide sequence number to every message.
+///
+/// While enabled, each record ends with `#<n>`, after the function-name suffix, where `n`
+/// counts up from 1 across all threads. This gives records a total order even when their
+/// timestamps tie, e.g. when reconstructing what concurrent producers did. Disabled by default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_include_seq(true);
+/// ```
+pub fn set_include_seq(enabled: bool) {
+    INCLUDE_SEQ.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn include_seq() -> bool {
+    INCLUDE_SEQ.load(Ordering::Relaxed)
+}
+
+/// Enables or disables appending the name of the logging thread to every message.
+///
+/// While enabled, each record carries `thread=<name>` after the function-name suffix, or the
+/// thread's id, such as `thread=ThreadId(3)`, for unnamed threads. This tells apart the lines
+/// that concurrent threads interleave. Disabled by default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_include_thread(true);
+/// ```
+pub fn set_include_thread(enabled: bool) {
+    INCLUDE_THREAD.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn include_thread() -> bool {
+    INCLUDE_THREAD.load(Ordering::Relaxed)
+}
+
+/// Enables or disables appending the build profile to every message.
+///
+/// While enabled, each record carries `profile=debug` or `profile=release` right after the
+/// function-name suffix, chosen at compile time by whether debug assertions are on. Operators
+/// reading aggregated logs can then tell records from optimized builds apart, which matters
+/// when reading timings. Disabled by default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_include_profile(true);
+/// ```
+/// # Notes
+/// - The profile is the one this crate was compiled with, which is the application's unless
+///   its profile overrides settings for this crate alone.
+pub fn set_include_profile(enabled: bool) {
+    INCLUDE_PROFILE.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn include_profile() -> bool {
+    INCLUDE_PROFILE.load(Ordering::Relaxed)
+}
+
+/// Enables or disables appending the W3C Trace Context ids set with
+/// [`set_traceparent`](crate::set_traceparent) to every message.
+///
+/// While enabled, each record logged on a thread with a trace context carries
+/// `trace=<trace-id> span=<span-id>` after the function-name suffix, so log lines can be
+/// joined with the distributed trace of the request, without an OpenTelemetry dependency.
+/// Records on threads without one are left as they are. Disabled by default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_include_traceparent(true);
+/// ```
+pub fn set_include_traceparent(enabled: bool) {
+    INCLUDE_TRACEPARENT.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn include_traceparent() -> bool {
+    INCLUDE_TRACEPARENT.load(Ordering::Relaxed)
+}
+
+/// Enables or disables appending the id of the current `tokio` task to every message.
+///
+/// While enabled, records logged from inside a task carry `task=<id>`, after the thread name
+/// if [`set_include_thread`] is enabled too; outside a task, nothing is added. Many tasks share
+/// a few runtime threads, so the thread name alone cannot tell their lines apart. Disabled by
+/// default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_include_thread(true);
+/// tacky_borders_logger::set_include_task_id(true);
+/// // Inside a task: "polling [fn poll] thread=tokio-runtime-worker task=12"
+/// ```
+#[cfg(feature = "tokio")]
+pub fn set_include_task_id(enabled: bool) {
+    INCLUDE_TASK_ID.store(enabled, Ordering::Relaxed);
+}
+
+#[cfg(feature = "tokio")]
+pub(crate) fn include_task_id() -> bool {
+    INCLUDE_TASK_ID.load(Ordering::Relaxed)
+}
+
+/// Sets the build identifier, such as a git commit hash, attached to every record.
+///
+/// While set, each record carries a `build=<id>` field, rendered before the function-name
+/// suffix, or attached as a `build` key-value pair with the `kv` feature. Logs can then be
+/// matched to the exact source they came from. The crate cannot read the application's git
+/// information itself; a build script typically exports it for `env!`. Passing `None`, the
+/// default, attaches nothing.
+///
+/// # Example
+/// ```rust
+/// // build.rs: println!("cargo:rustc-env=GIT_SHA={}", sha);
+/// # const GIT_SHA: &str = "3f9c2a1";
+/// tacky_borders_logger::set_build_id(GIT_SHA); // env!("GIT_SHA")
+/// ```
+pub fn set_build_id<'a>(id: impl Into<Option<&'a str>>) {
+    *BUILD_ID.write().unwrap_or_else(|e| e.into_inner()) = id.into().map(Arc::from);
+}
+
+pub(crate) fn build_id() -> Option<Arc<str>> {
+    BUILD_ID.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Enables or disables appending the time of the record to every message.
+///
+/// While enabled, each record ends with `@<timestamp>`, after the other suffixes, where the
+/// timestamp is in RFC 3339 form with milliseconds, e.g. `@2025-01-31T08:15:00.123Z`. It is
+/// rendered in UTC unless another zone is set with [`set_timestamp_timezone`]. This is for
+/// sinks that do not timestamp records themselves, or whose clock cannot be trusted. Disabled
+/// by default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_include_timestamp(true);
+/// ```
+pub fn set_include_timestamp(enabled: bool) {
+    INCLUDE_TIMESTAMP.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn include_timestamp() -> bool {
+    INCLUDE_TIMESTAMP.load(Ordering::Relaxed)
+}
+
+/// Sets the time zone of the timestamps appended by [`set_include_timestamp`].
+///
+/// Timestamps are in UTC by default, so logs aggregated from hosts in different regions line
+/// up without ambiguity. [`Timezone::Local`] uses the host's zone, and, with the `chrono-tz`
+/// feature, [`Timezone::Named`] any zone from the IANA database. Timestamps outside UTC end
+/// with their offset, e.g. `+01:00`.
+///
+/// # Example
+/// ```rust
+/// use tacky_borders_logger::Timezone;
+///
+/// tacky_borders_logger::set_timestamp_timezone(Timezone::Local);
+/// ```
+pub fn set_timestamp_timezone(timezone: Timezone) {
+    *TIMESTAMP_TIMEZONE
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = timezone;
+}
+
+pub(crate) fn timestamp_timezone() -> Timezone {
+    *TIMESTAMP_TIMEZONE.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Enables or disables indenting messages by how deeply nested they are in
+/// [`log_span!`](crate::log_span) spans.
+///
+/// While enabled, each message is preceded by two spaces per span entered on the current
+/// thread, so nested operations read as a tree. A span's own enter and exit lines are indented
+/// at the level of the code around it. Disabled by default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_indent_with_depth(true);
+/// ```
+pub fn set_indent_with_depth(enabled: bool) {
+    INDENT_WITH_DEPTH.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn indent_with_depth() -> bool {
+    INDENT_WITH_DEPTH.load(Ordering::Relaxed)
+}
+
+/// Enables or disables collecting statistics for [`log_span!`](crate::log_span) spans.
+///
+/// While enabled, each span counts the records logged while it is entered, including those of
+/// nested spans, and the deepest nesting reached, and the line logged when it closes becomes
+/// `span <name> closed duration=<elapsed> logs=<n> peak_depth=<n>`; with the `kv` feature,
+/// the statistics are key-value pairs. Counting adds work to every record logged inside a
+/// span, so this is disabled by default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_span_stats(true);
+/// ```
+pub fn set_span_stats(enabled: bool) {
+    SPAN_STATS.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn span_stats() -> bool {
+    SPAN_STATS.load(Ordering::Relaxed)
+}
+
+/// Sets the function names whose `[fn name]` suffix is left out.
+///
+/// This is meant for thin wrappers, such as a generic `log_helper`, whose name adds noise rather
+/// than context. Names are matched exactly against the name extracted by `function_name!()`, and
+/// the list replaces any previously configured one. The list is empty by default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_fn_suffix_denylist(vec!["log_helper", "dispatch"]);
+/// ```
+pub fn set_fn_suffix_denylist<I, S>(names: I)
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let names = names.into_iter().map(Into::into).collect();
+    *FN_SUFFIX_DENYLIST
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = names;
+}
+
+pub(crate) fn is_fn_suffix_denied(fn_name: &str) -> bool {
+    let denylist = FN_SUFFIX_DENYLIST.read().unwrap_or_else(|e| e.into_inner());
+    denylist.iter().any(|name| name == fn_name)
+}
+
+/// Sets the order of the standard fields in the JSON lines of the audit trail.
+///
+/// The standard fields of an event written by [`audit!`](crate::audit) are `ts`, `event`,
+/// `fn` and `module`, in that order by default. The listed ones are written first, in the
+/// given order, followed by the others in their default order, and the event's own fields
+/// always come last, in the order they were passed. Names that are not standard fields are
+/// ignored, and the list replaces any previously configured one. A fixed order keeps the
+/// lines easy to read by eye as well as to load into columnar storage.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_field_order(&["ts", "fn", "event"]);
+/// // Writes: {"ts":"2025-01-31T08:15:00.123Z","fn":"login","event":"login",
+/// //          "module":"app::auth","fields":{"user":"admin"}}
+/// ```
+pub fn set_field_order(names: &[&str]) {
+    let names = names.iter().map(|&name| name.to_owned()).collect();
+    *FIELD_ORDER.write().unwrap_or_else(|e| e.into_inner()) = names;
+}
+
+/// Returns where a standard field goes in the order set with [`set_field_order`], or `None`
+/// if it keeps its default place after the listed ones.
+pub(crate) fn field_position(name: &str) -> Option<usize> {
+    let order = FIELD_ORDER.read().unwrap_or_else(|e| e.into_inner());
+    order.iter().position(|listed| listed == name)
+}
+
+/// Sets the levels whose records get the function-name suffix; records at other levels are
+/// logged with the bare message.
+///
+/// This suits logs where info lines are user-facing but warnings and errors should say where
+/// they came from. Every level gets the suffix by default.
+///
+/// # Example
+/// ```rust
+/// use log::Level;
+///
+/// tacky_borders_logger::set_suffix_levels(&[Level::Warn, Level::Error]);
+/// ```
+pub fn set_suffix_levels(levels: &[Level]) {
+    let mask = levels
+        .iter()
+        .fold(0, |mask, &level| mask | level_bit(level));
+    SUFFIX_LEVELS.store(mask, Ordering::Relaxed);
+}
+
+pub(crate) fn suffix_level_enabled(level: Level) -> bool {
+    SUFFIX_LEVELS.load(Ordering::Relaxed) & level_bit(level) != 0
+}
+
+/// Moves the function name from the message suffix into the record's target.
+///
+/// While enabled, a record logged from `connect` in `my_app::net` has the target
+/// `my_app::net::connect` and no `[fn connect]` suffix. This is meant for programs that log
+/// through `log` but run under a `tracing` subscriber via `tracing-log`: the bridge keeps the
+/// target as event metadata, while the suffix would only be part of the message text. Target
+/// filters by module prefix, such as `my_app::net=debug`, keep matching. Disabled by default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_fn_name_in_target(true);
+/// ```
+/// # Notes
+/// - Whether a `tracing` subscriber is in use cannot be detected through `log`, so this has to
+///   be enabled explicitly.
+pub fn set_fn_name_in_target(enabled: bool) {
+    FN_NAME_IN_TARGET.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn fn_name_in_target() -> bool {
+    FN_NAME_IN_TARGET.load(Ordering::Relaxed)
+}
+
+/// Enables or disables scanning every message for likely secrets and redacting them.
+///
+/// While enabled, each rendered message is scanned for tokens that look like secrets: JSON Web
+/// Tokens, API keys with a well-known prefix such as `sk-`, `ghp_` or `AKIA`, and long tokens
+/// that mix upper case, lower case and digits about as randomly as generated keys do. Each
+/// one is replaced by `***`, and the first time it happens at a call site, a warning that a
+/// potential secret was redacted there is logged, so the call can be fixed. Disabled by
+/// default.
+///
+/// Detection is heuristic and works both ways: a secret in an unusual shape, such as a short
+/// password or a lower-case hex key, is logged as is, and a harmless identifier that happens
+/// to look random, such as a base64-encoded hash, is redacted. Lower-case hex values like commit
+/// hashes and UUIDs are never flagged as random tokens. Scanning renders every message into a
+/// `String` first, which costs an allocation per record.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_secret_detection(true);
+/// ```
+pub fn set_secret_detection(enabled: bool) {
+    SECRET_DETECTION.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn secret_detection() -> bool {
+    SECRET_DETECTION.load(Ordering::Relaxed)
+}
+
+/// Enables or disables rewriting the line endings in every message to one style.
+///
+/// While enabled, every `\r\n`, lone `\r` and `\n` in the rendered line, including the
+/// output of `{:#?}` and any fields, is written as the style set with [`set_newline_style`],
+/// `\n` unless set otherwise, before the record reaches the logger. Multi-line messages then
+/// parse the same wherever the logs are processed. Disabled by default.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_newline_normalization(true);
+/// ```
+pub fn set_newline_normalization(enabled: bool) {
+    NEWLINE_NORMALIZATION.store(enabled, Ordering::Relaxed);
+}
+
+/// The line ending messages are rewritten to with [`set_newline_normalization`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum NewlineStyle {
+    /// `\n`, as on Unix.
+    #[default]
+    Lf,
+    /// `\r\n`, as on Windows.
+    CrLf,
+}
+
+impl NewlineStyle {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            NewlineStyle::Lf => "\n",
+            NewlineStyle::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Sets the line ending used by [`set_newline_normalization`]. Defaults to
+/// [`NewlineStyle::Lf`].
+///
+/// # Example
+/// ```rust
+/// use tacky_borders_logger::NewlineStyle;
+///
+/// tacky_borders_logger::set_newline_style(NewlineStyle::CrLf);
+/// tacky_borders_logger::set_newline_normalization(true);
+/// ```
+pub fn set_newline_style(style: NewlineStyle) {
+    NEWLINE_STYLE.store(style as u8, Ordering::Relaxed);
+}
+
+/// Returns the line ending to normalize messages to, or `None` if they are left as they are.
+pub(crate) fn newline_normalization() -> Option<NewlineStyle> {
+    NEWLINE_NORMALIZATION.load(Ordering::Relaxed).then(|| {
+        match NEWLINE_STYLE.load(Ordering::Relaxed) {
+            style if style == NewlineStyle::CrLf as u8 => NewlineStyle::CrLf,
+            _ => NewlineStyle::Lf,
+        }
+    })
+}
+
+/// How the function is named in the `[fn ...]` suffix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum FnSuffixStyle {
+    /// Only the function name, as returned by `function_name!()`: `[fn retry]`.
+    #[default]
+    Name,
+    /// The function name qualified with the innermost module, as returned by
+    /// `module_and_fn!()`: `[fn net::retry]`.
+    ModuleAndName,
+    /// The function name qualified with the full module path, as returned by
+    /// `function_path!()`: `[fn my_app::net::retry]`.
+    Path,
+}
+
+/// Sets how the function is named in the `[fn ...]` suffix. Defaults to [`FnSuffixStyle::Name`].
+///
+/// # Example
+/// ```rust
+/// use tacky_borders_logger::FnSuffixStyle;
+///
+/// tacky_borders_logger::set_fn_suffix_style(FnSuffixStyle::ModuleAndName);
+/// ```
+pub fn set_fn_suffix_style(style: FnSuffixStyle) {
+    FN_SUFFIX_STYLE.store(style as u8, Ordering::Relaxed);
+}
+
+pub(crate) fn fn_suffix_style() -> FnSuffixStyle {
+    match FN_SUFFIX_STYLE.load(Ordering::Relaxed) {
+        style if style == FnSuffixStyle::ModuleAndName as u8 => FnSuffixStyle::ModuleAndName,
+        style if style == FnSuffixStyle::Path as u8 => FnSuffixStyle::Path,
+        _ => FnSuffixStyle::Name,
+    }
+}
+
+/// Sets a custom template for the function-name suffix.
+///
+/// The template is appended to every message as-is, with its placeholder, `{}` or `{fn}`,
+/// replaced by the function name; literal braces are written as `{{` and `}}`. For example,
+/// `" ({fn})"` turns `[fn load_config]` into `(load_config)`. The name follows
+/// [`set_fn_suffix_style`], and the same template is used at every level.
+///
+/// Passing `None` restores the default ` [fn name]` suffix.
+///
+/// # Errors
+/// Fails, leaving the current template in place, if the template does not contain exactly one
+/// placeholder or has unmatched braces.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_fn_suffix_format(Some(" <- {fn}")).unwrap();
+/// assert!(tacky_borders_logger::set_fn_suffix_format(Some(" [fn]")).is_err());
+/// ```
+pub fn set_fn_suffix_format(template: Option<&str>) -> Result<(), SuffixFormatError> {
+    let format = template.map(SuffixFormat::parse).transpose()?.map(Arc::new);
+    *FN_SUFFIX_FORMAT.write().unwrap_or_else(|e| e.into_inner()) = format;
+    Ok(())
+}
+
+pub(crate) fn fn_suffix_format() -> Option<Arc<SuffixFormat>> {
+    FN_SUFFIX_FORMAT
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// Applies configuration from environment variables.
+///
+/// Currently this reads `TACKY_LOG_SUFFIX`, which, when set, is installed as the suffix
+/// template with [`set_fn_suffix_format`]. This lets operators change the suffix per deployment
+/// without recompiling. Call it once at startup, after any programmatic configuration it should
+/// override.
+///
+/// # Errors
+/// If the variable holds an invalid template, the default suffix is restored and the parse error
+/// is returned, so it can be reported once a logger is installed.
+///
+/// # Example
+/// ```rust
+/// if let Err(e) = tacky_borders_logger::init_from_env() {
+///     eprintln!("ignoring TACKY_LOG_SUFFIX: {e}");
+/// }
+/// ```
+pub fn init_from_env() -> Result<(), SuffixFormatError> {
+    let Ok(template) = std::env::var("TACKY_LOG_SUFFIX") else {
+        return Ok(());
+    };
+    set_fn_suffix_format(Some(&template)).inspect_err(|_| {
+        *FN_SUFFIX_FORMAT.write().unwrap_or_else(|e| e.into_inner()) = None;
+    })
+}
+
+/// A parsed suffix template: the text around the function-name placeholder.
+#[derive(Debug)]
+pub(crate) struct SuffixFormat {
+    pub(crate) before: String,
+    pub(crate) after: String,
+}
+
+impl SuffixFormat {
+    fn parse(template: &str) -> Result<Self, SuffixFormatError> {
+        let error = |reason| SuffixFormatError {
+            template: template.to_owned(),
+            reason,
+        };
+
+        let mut before = String::new();
+        let mut after = None::<String>;
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            let literal = match c {
+                '{' if chars.next_if_eq(&'{').is_some() => '{',
+                '}' if chars.next_if_eq(&'}').is_some() => '}',
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(error("unclosed `{`")),
+                        }
+                    }
+                    if !name.is_empty() && name != "fn" {
+                        return Err(error("unknown placeholder, expected `{}` or `{fn}`"));
+                    }
+                    if after.replace(String::new()).is_some() {
+                        return Err(error("more than one placeholder"));
+                    }
+                    continue;
+                }
+                '}' => return Err(error("unmatched `}`")),
+                c => c,
+            };
+            after.as_mut().unwrap_or(&mut before).push(literal);
+        }
+
+        match after {
+            Some(after) => Ok(Self { before, after }),
+            None => Err(error("missing `{}` or `{fn}` placeholder")),
+        }
+    }
+}
+
+/// The error returned when a suffix template cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuffixFormatError {
+    template: String,
+    reason: &'static str,
+}
+
+impl fmt::Display for SuffixFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid suffix template {:?}: {}",
+            self.template, self.reason
+        )
+    }
+}
+
+impl Error for SuffixFormatError {}
@@ -0,0 +1,436 @@
+//! Tags attached to every record logged on a thread while a scope is active.
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::hash::{BuildHasher, RandomState};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+use log::Level;
+
+use crate::__private_api::{self, Elapsed, Location};
+use crate::{clock, rate};
+
+thread_local! {
+    /// The tags of the scopes entered on this thread, outermost first.
+    static CONTEXT: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    /// The decision of the outermost sampled scope this thread is in, if any.
+    static SAMPLED: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+/// Pushes a tag onto this thread's context stack, appended to every record as ` [tag]`.
+pub(crate) fn push(tag: String) {
+    CONTEXT.with_borrow_mut(|context| context.push(tag));
+}
+
+/// Pops the innermost tag from this thread's context stack.
+pub(crate) fn pop() {
+    CONTEXT.with_borrow_mut(Vec::pop);
+}
+
+/// Returns whether the sampled scope this thread is in was selected, or `None` outside one.
+pub(crate) fn sampled() -> Option<bool> {
+    SAMPLED.get()
+}
+
+/// Renders this thread's context stack as ` [outer] [inner]`.
+pub(crate) struct ContextText;
+
+impl fmt::Display for ContextText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        CONTEXT.with_borrow(|context| context.iter().try_for_each(|tag| write!(f, " [{tag}]")))
+    }
+}
+
+/// Spawns a thread that starts out with the calling thread's context tags.
+///
+/// This works like [`std::thread::spawn`], but first copies the tags of the scopes the
+/// calling thread is in, such as `[req=<id>]` from [`request_scope!`](crate::request_scope),
+/// and installs them on the new thread before running `f`, so the records it logs are
+/// tagged like the parent's. The decision of a [`sampled_scope!`](crate::sampled_scope) the
+/// calling thread is in is copied too. The tags are a copy: scopes entered or left on either thread
+/// afterwards do not affect the other.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{info, request_scope, spawn_with_context};
+/// fn handle(id: u64) {
+///     request_scope!(id, {
+///         spawn_with_context(|| {
+///             info!("uploading");
+///             // Logs: "uploading [req=42] [fn handle]"
+///         })
+///         .join()
+///         .unwrap();
+///     })
+/// }
+/// # handle(42);
+/// ```
+pub fn spawn_with_context<F, T>(f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let context = CONTEXT.with_borrow(Vec::clone);
+    let sampled = SAMPLED.get();
+    thread::spawn(move || {
+        CONTEXT.set(context);
+        SAMPLED.set(sampled);
+        f()
+    })
+}
+
+/// Returns a short random id, such as `a1b2c3d4`, for [`auto_scope!`](crate::auto_scope).
+fn random_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    // `RandomState` is seeded randomly, so hashing a counter through it gives ids that differ
+    // between scopes and between runs.
+    let random = RandomState::new().hash_one(COUNTER.fetch_add(1, Ordering::Relaxed));
+    format!("{:08x}", random as u32)
+}
+
+/// The scope of a request, entered by [`request_scope!`](crate::request_scope), or of an
+/// operation entered by [`auto_scope!`](crate::auto_scope).
+#[doc(hidden)]
+pub struct RequestScope {
+    /// What the start and end lines call the scope, such as `request`.
+    kind: &'static str,
+    id: String,
+    fn_name: String,
+    loc: Location<'static>,
+    start: Instant,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl RequestScope {
+    pub fn __enter(id: String, fn_name: String, loc: Location<'static>) -> Self {
+        Self::enter("request", "req", id, fn_name, loc)
+    }
+
+    pub fn __enter_auto(fn_name: String, loc: Location<'static>) -> Self {
+        Self::enter("scope", "id", random_id(), fn_name, loc)
+    }
+
+    fn enter(
+        kind: &'static str,
+        key: &str,
+        id: String,
+        fn_name: String,
+        loc: Location<'static>,
+    ) -> Self {
+        if __private_api::enabled(Level::Info) {
+            __private_api::emit(
+                Level::Info,
+                format_args!("{kind} {id} start"),
+                &fn_name,
+                &loc,
+            );
+        }
+        push(format!("{key}={id}"));
+        Self {
+            kind,
+            id,
+            fn_name,
+            loc,
+            start: clock::now(),
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl Drop for RequestScope {
+    fn drop(&mut self) {
+        pop();
+        if __private_api::enabled(Level::Info) {
+            __private_api::emit(
+                Level::Info,
+                format_args!(
+                    "{} {} end ({})",
+                    self.kind,
+                    self.id,
+                    Elapsed(clock::elapsed_since(self.start))
+                ),
+                &self.fn_name,
+                &self.loc,
+            );
+        }
+    }
+}
+
+/// Macro to run a block as the handling of one request, correlating its log lines by id.
+///
+/// This macro logs `request <id> start` at info level with the name of the function where
+/// it is invoked, runs the block, then logs `request <id> end (<elapsed>)`. Every record
+/// logged on the thread while the block runs, including from functions it calls, is
+/// tagged with `[req=<id>]`. The macro evaluates to the value of the block.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{info, request_scope};
+/// fn handle(id: u64) -> u16 {
+///     request_scope!(id, {
+///         // Logs: "request 42 start [fn handle]"
+///         info!("loading user");
+///         // Logs: "loading user [req=42] [fn handle]"
+///         200
+///     })
+///     // Logs: "request 42 end (1.2ms) [fn handle]"
+/// }
+/// # handle(42);
+/// ```
+/// # Notes
+/// - The id can be any `Display` value.
+/// - The end line is also logged if the block returns early, with `return` or `?`, or panics.
+/// - Tags are kept per thread: the block must not hold the scope across an `.await` in a future
+///   that may move between threads. Threads spawned with
+///   [`spawn_with_context`](crate::spawn_with_context) start out with a copy of them.
+#[macro_export]
+macro_rules! request_scope {
+    ($id:expr, $body:block) => {{
+        let _scope = $crate::__private_api::RequestScope::__enter(
+            ::std::string::ToString::to_string(&$id),
+            $crate::function_name!(),
+            (module_path!(), module_path!(), file!(), line!()),
+        );
+        $body
+    }};
+}
+
+/// Macro to run a block in a scope tagged with a randomly generated id.
+///
+/// This macro works like [`request_scope!`](crate::request_scope) for code that has no
+/// natural id to correlate its records by: it generates a random id of 8 hex digits, logs
+/// `scope <id> start` at info level with the name of the function where it is invoked, runs
+/// the block, then logs `scope <id> end (<elapsed>)`. Every record logged on the thread while
+/// the block runs is tagged with `[id=<id>]`. The macro evaluates to the value of the block.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{auto_scope, info};
+/// fn rebuild_borders() {
+///     auto_scope!({
+///         // Logs: "scope a1b2c3d4 start [fn rebuild_borders]"
+///         info!("rebuilding");
+///         // Logs: "rebuilding [id=a1b2c3d4] [fn rebuild_borders]"
+///     })
+///     // Logs: "scope a1b2c3d4 end (1.2ms) [fn rebuild_borders]"
+/// }
+/// # rebuild_borders();
+/// ```
+/// # Notes
+/// - A new id is generated every time the scope is entered.
+/// - The end line is also logged if the block returns early, with `return` or `?`, or panics.
+/// - Like [`request_scope!`](crate::request_scope), tags are kept per thread.
+#[macro_export]
+macro_rules! auto_scope {
+    ($body:block) => {{
+        let _scope = $crate::__private_api::RequestScope::__enter_auto(
+            $crate::function_name!(),
+            (module_path!(), module_path!(), file!(), line!()),
+        );
+        $body
+    }};
+}
+
+/// The scope of one attempt of a retry loop, entered by
+/// [`attempt_scope!`](crate::attempt_scope).
+#[doc(hidden)]
+pub struct AttemptScope {
+    _not_send: PhantomData<*const ()>,
+}
+
+impl AttemptScope {
+    pub fn __enter(attempt: &dyn fmt::Display) -> Self {
+        push(format!("attempt={attempt}"));
+        Self {
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl Drop for AttemptScope {
+    fn drop(&mut self) {
+        pop();
+    }
+}
+
+/// Macro to run a block as one attempt of a retry loop, tagging its log lines with the
+/// attempt number.
+///
+/// Every record logged on the thread while the block runs, including from functions it
+/// calls, is tagged with `[attempt=<n>]`. Unlike [`request_scope!`](crate::request_scope),
+/// no start or end line is logged. The macro evaluates to the value of the block.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{attempt_scope, warn};
+/// fn connect() {
+///     for attempt in 1..=3 {
+///         attempt_scope!(attempt, {
+///             warn!("connection refused");
+///             // Logs: "connection refused [attempt=1] [fn connect]"
+///         });
+///     }
+/// }
+/// # connect();
+/// ```
+/// # Notes
+/// - The attempt can be any `Display` value.
+/// - [`log_retry!`](crate::log_retry) runs a block in one of these scopes per attempt.
+/// - Like [`request_scope!`](crate::request_scope), tags are kept per thread.
+#[macro_export]
+macro_rules! attempt_scope {
+    ($attempt:expr, $body:block) => {{
+        let _scope = $crate::__private_api::AttemptScope::__enter(&$attempt);
+        $body
+    }};
+}
+
+/// A group of related records, entered by [`log_group!`](crate::log_group).
+#[doc(hidden)]
+pub struct LogGroup {
+    level: Level,
+    name: String,
+    fn_name: String,
+    loc: Location<'static>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl LogGroup {
+    pub fn __enter(level: Level, name: String, fn_name: String, loc: Location<'static>) -> Self {
+        if __private_api::enabled(level) {
+            __private_api::emit(level, format_args!("--- begin {name} ---"), &fn_name, &loc);
+        }
+        push(name.clone());
+        Self {
+            level,
+            name,
+            fn_name,
+            loc,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl Drop for LogGroup {
+    fn drop(&mut self) {
+        pop();
+        if __private_api::enabled(self.level) {
+            __private_api::emit(
+                self.level,
+                format_args!("--- end {} ---", self.name),
+                &self.fn_name,
+                &self.loc,
+            );
+        }
+    }
+}
+
+/// Macro to run a block with its log lines bracketed and tagged as one group.
+///
+/// This macro logs `--- begin <name> ---` at the given level with the name of the function
+/// where it is invoked, runs the block, then logs `--- end <name> ---`. Every record logged
+/// on the thread while the block runs, including from functions it calls, is tagged with
+/// `[<name>]`, so a burst of related lines is easy to find in a busy log. The macro
+/// evaluates to the value of the block.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{info, log_group};
+/// fn import(batch: u32, rows: &[&str]) {
+///     log_group!(info, format!("batch-{batch}"), {
+///         // Logs: "--- begin batch-42 --- [fn import]"
+///         for row in rows {
+///             info!("imported {}", row);
+///             // Logs: "imported alice [batch-42] [fn import]"
+///         }
+///     });
+///     // Logs: "--- end batch-42 --- [fn import]"
+/// }
+/// # import(42, &["alice"]);
+/// ```
+/// # Notes
+/// - The name can be any `Display` value.
+/// - The end line is also logged if the block returns early, with `return` or `?`, or panics.
+/// - Like [`request_scope!`](crate::request_scope), tags are kept per thread.
+#[macro_export]
+macro_rules! log_group {
+    ($level:ident, $name:expr, $body:block) => {{
+        let _group = $crate::__private_api::LogGroup::__enter(
+            $crate::__level!($level),
+            ::std::string::ToString::to_string(&$name),
+            $crate::function_name!(),
+            (module_path!(), module_path!(), file!(), line!()),
+        );
+        $body
+    }};
+}
+
+/// A scope whose records are all logged or all suppressed, entered by
+/// [`sampled_scope!`](crate::sampled_scope).
+#[doc(hidden)]
+pub struct SampledScope {
+    previous: Option<bool>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl SampledScope {
+    pub fn __enter(probability: f64) -> Self {
+        let previous = SAMPLED.get();
+        if previous.is_none() {
+            SAMPLED.set(Some(rate::random_unit() < probability));
+        }
+        Self {
+            previous,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl Drop for SampledScope {
+    fn drop(&mut self) {
+        SAMPLED.set(self.previous);
+    }
+}
+
+/// Macro to run a block with all or none of its log lines, as decided once on entry.
+///
+/// On entry, the block is selected with the given probability. If it is, every record logged
+/// on the thread while it runs, including from functions it calls, is logged in full, at
+/// every level, even those turned off with [`set_level_enabled`](crate::set_level_enabled) or
+/// above `log::max_level()`. If it is not, this crate's records are suppressed for the whole
+/// block. Sampling a fraction of requests this way gives complete, coherent traces for them,
+/// rather than unrelated lines sampled from all of them. The macro evaluates to the value of
+/// the block.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{debug, sampled_scope, trace};
+/// fn handle(id: u64) {
+///     sampled_scope!(0.01, {
+///         debug!("handling request {}", id);
+///         trace!("parsed headers");
+///         // For 1% of requests, logs both lines, even if trace is filtered out; for the
+///         // rest, logs neither.
+///     })
+/// }
+/// # handle(42);
+/// ```
+/// # Notes
+/// - The probability is an `f64`; 0.0 or less suppresses every block and 1.0 or more logs
+///   every block in full.
+/// - A nested sampled scope keeps the decision of the outermost one, so a request is traced
+///   coherently all the way down.
+/// - [`set_logging_enabled(false)`](crate::set_logging_enabled) and the `STATIC_MAX_LEVEL`
+///   set with `log`'s features still apply, as does any filtering the installed logger does.
+/// - The decision is kept per thread; threads spawned with
+///   [`spawn_with_context`](crate::spawn_with_context) inherit it.
+#[macro_export]
+macro_rules! sampled_scope {
+    ($probability:expr, $body:block) => {{
+        let _scope = $crate::__private_api::SampledScope::__enter($probability);
+        $body
+    }};
+}
@@ -0,0 +1,101 @@
+//! A ring buffer of recent records, for attaching to crash reports or serving from a debug
+//! endpoint.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use log::Level;
+
+static CAPACITY: AtomicUsize = AtomicUsize::new(0);
+static LINES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Keeps the last `capacity` records logged through this crate in memory, for
+/// [`crash_report`]. Passing 0 turns the buffer off and clears it, which is the default.
+///
+/// Each record is kept as `<LEVEL> <message>`, with the message as the logger receives it.
+/// Records are kept in addition to being logged, so this works alongside any logger.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_crash_buffer(200);
+///
+/// let default_hook = std::panic::take_hook();
+/// std::panic::set_hook(Box::new(move |info| {
+///     eprintln!("recent log lines:\n{}", tacky_borders_logger::crash_report!());
+///     default_hook(info);
+/// }));
+/// ```
+pub fn set_crash_buffer(capacity: usize) {
+    CAPACITY.store(capacity, Ordering::Relaxed);
+    let mut lines = LINES.lock().unwrap_or_else(|e| e.into_inner());
+    let excess = lines.len().saturating_sub(capacity);
+    lines.drain(..excess);
+    lines.shrink_to(capacity);
+}
+
+/// Returns the records kept by [`set_crash_buffer`], oldest first, one per line.
+///
+/// This is safe to call from a panic hook: the buffer's lock is never held while formatting
+/// or logging, so a panic elsewhere cannot leave it locked.
+pub fn crash_report() -> String {
+    let lines = LINES.lock().unwrap_or_else(|e| e.into_inner());
+    let mut report = String::with_capacity(lines.iter().map(|line| line.len() + 1).sum());
+    for line in lines.iter() {
+        report.push_str(line);
+        report.push('\n');
+    }
+    report
+}
+
+/// Returns up to the `n` most recent records kept by [`set_crash_buffer`], oldest first.
+///
+/// Each record is `<LEVEL> <message>`, as in [`crash_report`]. Unlike the report, this can be
+/// called at any time to read what was logged lately, e.g. from an admin or debug endpoint
+/// showing live logs. Fewer than `n` records are returned if fewer are kept, and none while
+/// the buffer is off.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_crash_buffer(1_000);
+/// // ... later, in a `/debug/logs` handler:
+/// let body = tacky_borders_logger::recent_logs(100).join("\n");
+/// ```
+pub fn recent_logs(n: usize) -> Vec<String> {
+    let lines = LINES.lock().unwrap_or_else(|e| e.into_inner());
+    let skip = lines.len().saturating_sub(n);
+    lines.iter().skip(skip).cloned().collect()
+}
+
+/// Keeps `line` if the crash buffer is on.
+pub(crate) fn record(level: Level, line: &dyn fmt::Display) {
+    let capacity = CAPACITY.load(Ordering::Relaxed);
+    if capacity == 0 {
+        return;
+    }
+    // Formatted before locking, so user code never runs under the lock.
+    let line = format!("{level:<5} {line}");
+    let mut lines = LINES.lock().unwrap_or_else(|e| e.into_inner());
+    while lines.len() >= capacity {
+        lines.pop_front();
+    }
+    lines.push_back(line);
+}
+
+/// Macro to get the recent records kept by [`set_crash_buffer`](crate::set_crash_buffer) as a
+/// `String`, one record per line; shorthand for [`crash_report`](crate::crash_report).
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::crash_report;
+/// std::panic::set_hook(Box::new(|info| {
+///     eprintln!("{info}\nrecent log lines:\n{}", crash_report!());
+/// }));
+/// ```
+#[macro_export]
+macro_rules! crash_report {
+    () => {
+        $crate::crash_report()
+    };
+}
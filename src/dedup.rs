@@ -0,0 +1,94 @@
+//! Suppression of consecutive identical messages.
+
+use std::cell::RefCell;
+
+use log::{Level, Record};
+
+use crate::__private_api::{self, Callsite};
+
+/// The last message logged on a thread and how many times it has been suppressed since.
+struct Last {
+    key: String,
+    repeats: usize,
+    level: Level,
+    target: String,
+    module_path: Option<&'static str>,
+    file: &'static str,
+    line: u32,
+}
+
+thread_local! {
+    static LAST: RefCell<Option<Last>> = const { RefCell::new(None) };
+}
+
+/// Returns `true` if `key` repeats the previous message on this thread, which should then be
+/// suppressed. Otherwise, reports the repeats of the previous message, if any, and remembers
+/// this one.
+pub(crate) fn is_repeat(key: String, level: Level, callsite: &Callsite) -> bool {
+    let mut repeat = false;
+    let previous = LAST.with_borrow_mut(|last| match last {
+        Some(last) if last.key == key => {
+            last.repeats += 1;
+            repeat = true;
+            None
+        }
+        _ => last.replace(Last {
+            key,
+            repeats: 0,
+            level,
+            target: callsite.target.to_owned(),
+            module_path: callsite.module_path,
+            file: callsite.file,
+            line: callsite.line,
+        }),
+    });
+
+    // Logged outside the borrow, in case the logger logs through this crate itself.
+    if let Some(previous) = previous {
+        report(previous);
+    }
+    repeat
+}
+
+/// Logs how often the last message on the calling thread was suppressed by
+/// [`set_dedup_consecutive`](crate::set_dedup_consecutive), if it was, and forgets it, so the
+/// next message is logged even if it is the same.
+///
+/// Pending repeats are otherwise only reported once a different message is logged on the same
+/// thread, so call this before the thread exits or the logger is flushed.
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_dedup_consecutive(true);
+/// // ... a loop logging the same line ...
+/// tacky_borders_logger::flush_dedup();
+/// // Logs: "last message repeated 41 times"
+/// ```
+pub fn flush_dedup() {
+    if let Some(last) = LAST.take() {
+        report(last);
+    }
+}
+
+fn report(last: Last) {
+    if last.repeats == 0 {
+        return;
+    }
+    let line = format_args!(
+        "last message repeated {} {}",
+        last.repeats,
+        if last.repeats == 1 { "time" } else { "times" }
+    );
+    __private_api::output(
+        last.level,
+        &line,
+        &Record::builder()
+            .level(last.level)
+            .target(&last.target)
+            .module_path_static(last.module_path)
+            .file_static(Some(last.file))
+            .line(Some(last.line))
+            .args(line)
+            .build(),
+    );
+}
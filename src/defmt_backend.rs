@@ -0,0 +1,75 @@
+//! `defmt` versions of the level macros, enabled by the `defmt` feature.
+//!
+//! `defmt` formats at compile time, so these macros take a literal format string using `defmt`'s
+//! syntax, and the function name is passed to `defmt` as a `{=str}` argument next to the message.
+
+/// Macro to log a message through `defmt` at a level chosen at runtime, with the current
+/// function name.
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $fmt:literal $(, $arg:expr)* $(,)?) => ({
+        match $level {
+            $crate::__private_api::log::Level::Trace => {
+                $crate::__defmt_log!(trace, $fmt $(, $arg)*)
+            }
+            $crate::__private_api::log::Level::Debug => {
+                $crate::__defmt_log!(debug, $fmt $(, $arg)*)
+            }
+            $crate::__private_api::log::Level::Info => {
+                $crate::__defmt_log!(info, $fmt $(, $arg)*)
+            }
+            $crate::__private_api::log::Level::Warn => {
+                $crate::__defmt_log!(warn, $fmt $(, $arg)*)
+            }
+            $crate::__private_api::log::Level::Error => {
+                $crate::__defmt_log!(error, $fmt $(, $arg)*)
+            }
+        }
+    });
+}
+
+/// Logs a message through the `defmt` macro named by `$macro`, with the `[fn name]` suffix.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __defmt_log {
+    ($macro:ident, $fmt:literal $(, $arg:expr)*) => ({
+        if $crate::logging_enabled() {
+            let fn_name = $crate::function_name!();
+            ::defmt::$macro!(
+                "{} [fn {=str}]",
+                $crate::__private_api::DefmtMessage(|f| ::defmt::write!(f, $fmt $(, $arg)*)),
+                fn_name.as_str(),
+            );
+        }
+    });
+}
+
+/// Macro to log trace-level messages through `defmt` with the current function name.
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => ($crate::log!($crate::__private_api::log::Level::Trace, $($arg)+));
+}
+
+/// Macro to log debug-level messages through `defmt` with the current function name.
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => ($crate::log!($crate::__private_api::log::Level::Debug, $($arg)+));
+}
+
+/// Macro to log info-level messages through `defmt` with the current function name.
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => ($crate::log!($crate::__private_api::log::Level::Info, $($arg)+));
+}
+
+/// Macro to log warning-level messages through `defmt` with the current function name.
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => ($crate::log!($crate::__private_api::log::Level::Warn, $($arg)+));
+}
+
+/// Macro to log error-level messages through `defmt` with the current function name.
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => ($crate::log!($crate::__private_api::log::Level::Error, $($arg)+));
+}
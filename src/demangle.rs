@@ -0,0 +1,38 @@
+//! Readable rendering of mangled Rust symbol names.
+
+use std::fmt;
+
+/// A symbol name rendered demangled, without its trailing hash; for
+/// [`demangle!`](crate::demangle).
+#[doc(hidden)]
+pub struct Demangled<'a>(pub &'a str);
+
+impl fmt::Display for Demangled<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#}", rustc_demangle::demangle(self.0))
+    }
+}
+
+/// Macro to format a mangled Rust symbol name readably inside a log message.
+///
+/// Accepts anything implementing `AsRef<str>`, such as a symbol name read from a backtrace
+/// frame or an FFI callback. Both the legacy and the `v0` mangling schemes are understood,
+/// and the trailing hash is left out, so `_ZN4core3fmt5write17h0123456789abcdefE` is rendered
+/// as `core::fmt::write`. Names that are not mangled Rust symbols are rendered unchanged.
+///
+/// # Example
+#[cfg_attr(feature = "defmt", doc = "```ignore")]
+#[cfg_attr(not(feature = "defmt"), doc = "```rust")]
+/// # use tacky_borders_logger::{debug, demangle};
+/// fn on_callback(raw_name: &str) {
+///     debug!("callback from {}", demangle!(raw_name));
+///     // Logs: "callback from core::fmt::write [fn on_callback]"
+/// }
+/// # on_callback("_ZN4core3fmt5write17h0123456789abcdefE");
+/// ```
+#[macro_export]
+macro_rules! demangle {
+    ($symbol:expr $(,)?) => {
+        $crate::__private_api::Demangled(::std::convert::AsRef::<str>::as_ref(&$symbol))
+    };
+}
@@ -0,0 +1,84 @@
+//! Logging from `Drop` implementations.
+
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+use log::Level;
+
+use crate::__private_api::{self, Location};
+
+/// Returns `<Type>::drop` for the type being dropped, with the module paths of the type and
+/// any generic arguments left out, e.g. `Conn<String>::drop`.
+fn drop_fn_name(type_name: &str) -> String {
+    let mut name = String::with_capacity(type_name.len() + 6);
+    let mut path = String::new();
+    fn flush(path: &mut String, name: &mut String) {
+        name.push_str(path.rsplit("::").next().unwrap_or(path));
+        path.clear();
+    }
+
+    for c in type_name.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            path.push(c);
+        } else {
+            flush(&mut path, &mut name);
+            name.push(c);
+        }
+    }
+    flush(&mut path, &mut name);
+    name.push_str("::drop");
+    name
+}
+
+/// Logs a record from a destructor for [`drop_log!`](crate::drop_log), naming the type being
+/// dropped and swallowing any panic raised while logging.
+#[doc(hidden)]
+pub fn drop_log(level: Level, args: fmt::Arguments, type_name: &'static str, loc: &Location) {
+    // A panic escaping a destructor that runs during unwinding aborts the process, so a
+    // failing logger must not be allowed to propagate one from here.
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+        if __private_api::enabled(level) {
+            __private_api::emit(level, args, &drop_fn_name(type_name), loc);
+        }
+    }));
+}
+
+/// Macro to log from a `Drop` implementation, naming the type being dropped.
+///
+/// This macro is meant for use inside `Drop::drop`. It logs the message at the given level
+/// like the level macros, but the suffix names the type being dropped, as
+/// `[fn <Type>::drop]`, rather than the bare `drop` that `function_name!()` would report for
+/// every destructor alike. Generic arguments are kept, without their module paths.
+///
+/// Logging is also panic-safe: a panic raised while formatting the message or by the
+/// logger is caught and discarded. A destructor may run while another panic is unwinding,
+/// and a second panic escaping it then aborts the process.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::drop_log;
+/// struct Connection {
+///     id: u32,
+/// }
+///
+/// impl Drop for Connection {
+///     fn drop(&mut self) {
+///         drop_log!(debug, "cleaning up {}", self.id);
+///         // Logs: "cleaning up 7 [fn Connection::drop]"
+///     }
+/// }
+/// # drop(Connection { id: 7 });
+/// ```
+/// # Notes
+/// - The type is taken from `Self`, so the macro only compiles inside an `impl` block.
+#[macro_export]
+macro_rules! drop_log {
+    ($level:ident, $($arg:tt)+) => {
+        $crate::__private_api::drop_log(
+            $crate::__level!($level),
+            format_args!($($arg)+),
+            ::std::any::type_name::<Self>(),
+            &(module_path!(), module_path!(), file!(), line!()),
+        )
+    };
+}
@@ -0,0 +1,406 @@
+//! `Display` wrappers for values that print awkwardly in log messages.
+
+use std::fmt;
+use std::time::Duration;
+
+/// Renders a [`Duration`] compactly, e.g. `1.23s` or `4.56ms`; created by
+/// [`fmt_dur!`](crate::fmt_dur).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FmtDuration(pub Duration);
+
+impl fmt::Display for FmtDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nanos = self.0.as_nanos();
+        if nanos < 1_000 {
+            return write!(f, "{nanos}ns");
+        }
+        // The unit is picked after rounding to the two decimals shown, so 999.999ms becomes
+        // `1.00s` rather than `1000.00ms`.
+        let below_1000 = |scale: f64| ((nanos as f64 / scale) * 100.0).round() < 100_000.0;
+        if below_1000(1e3) {
+            write!(f, "{:.2}µs", nanos as f64 / 1e3)
+        } else if below_1000(1e6) {
+            write!(f, "{:.2}ms", nanos as f64 / 1e6)
+        } else {
+            write!(f, "{:.2}s", self.0.as_secs_f64())
+        }
+    }
+}
+
+/// Renders a byte count with binary prefixes, e.g. `512 B` or `4.2 MiB`; created by
+/// [`fmt_bytes!`](crate::fmt_bytes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FmtBytes(pub u64);
+
+impl fmt::Display for FmtBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+        if self.0 < 1024 {
+            return write!(f, "{} B", self.0);
+        }
+        let mut value = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+        // Compared as rounded for display, so 1048575 bytes become `1.0 MiB`, not `1024.0 KiB`.
+        while (value * 10.0).round() / 10.0 >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        write!(f, "{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Which prefixes [`FmtSi`] scales a value with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SiPrefixes {
+    /// Powers of 1000, from `p` up to `E`, e.g. `1.5 Mbytes` or `12 ms`.
+    #[default]
+    Decimal,
+    /// Powers of 1024, from `Ki` up to `Ei`, e.g. `1.43 MiB`; values below 1024 are not
+    /// scaled.
+    Binary,
+}
+
+/// Renders a number scaled with an SI prefix and followed by a unit, e.g. `1.5 Mbytes`,
+/// `-2.5 kW` or `0 bytes`; created by [`fmt_si!`](crate::fmt_si).
+///
+/// At most two decimals are shown, without trailing zeros.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FmtSi<'a>(pub f64, pub &'a str, pub SiPrefixes);
+
+impl fmt::Display for FmtSi<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const DECIMAL: [&str; 11] = ["p", "n", "µ", "m", "", "k", "M", "G", "T", "P", "E"];
+        const BINARY: [&str; 7] = ["", "Ki", "Mi", "Gi", "Ti", "Pi", "Ei"];
+        /// The index of the empty prefix in `DECIMAL`.
+        const UNIT: usize = 4;
+
+        let FmtSi(value, unit, prefixes) = *self;
+        let (mut scaled, base, table, mut index) = match prefixes {
+            SiPrefixes::Decimal => (value.abs(), 1000.0, &DECIMAL[..], UNIT),
+            SiPrefixes::Binary => (value.abs(), 1024.0, &BINARY[..], 0),
+        };
+        if scaled.is_finite() && scaled != 0.0 {
+            while scaled < 1.0 && prefixes == SiPrefixes::Decimal && index > 0 {
+                scaled *= base;
+                index -= 1;
+            }
+            // Compared as rounded for display, so 999.999 becomes `1 k` rather than `1000`, and
+            // 0.999999 becomes `1` rather than `1000 m`.
+            while (scaled * 100.0).round() / 100.0 >= base && index < table.len() - 1 {
+                scaled /= base;
+                index += 1;
+            }
+        }
+
+        let number = format!("{scaled:.2}");
+        let number = number.trim_end_matches('0').trim_end_matches('.');
+        let sign = if value < 0.0 && number != "0" {
+            "-"
+        } else {
+            ""
+        };
+        write!(f, "{sign}{number} {}{unit}", table[index])
+    }
+}
+
+/// How [`FmtDurationLong`] spells out a duration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DurationStyle {
+    /// Space-separated units, e.g. `1h 23m 45s`.
+    #[default]
+    Human,
+    /// An ISO-8601 duration, e.g. `PT1H23M45S`.
+    Iso8601,
+}
+
+/// Renders a long [`Duration`] in days, hours, minutes and whole seconds, e.g. `1h 23m 45s`
+/// or `PT1H23M45S`; created by [`fmt_duration_long!`](crate::fmt_duration_long).
+///
+/// Units above the largest non-zero one are left out, and sub-second precision is dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FmtDurationLong(pub Duration, pub DurationStyle);
+
+impl fmt::Display for FmtDurationLong {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.0.as_secs();
+        let (days, hours, minutes, seconds) =
+            (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60, secs % 60);
+
+        match self.1 {
+            DurationStyle::Human => {
+                if days > 0 {
+                    write!(f, "{days}d {hours}h {minutes}m {seconds}s")
+                } else if hours > 0 {
+                    write!(f, "{hours}h {minutes}m {seconds}s")
+                } else if minutes > 0 {
+                    write!(f, "{minutes}m {seconds}s")
+                } else {
+                    write!(f, "{seconds}s")
+                }
+            }
+            DurationStyle::Iso8601 => {
+                f.write_str("P")?;
+                if days > 0 {
+                    write!(f, "{days}D")?;
+                }
+                if days > 0 && hours == 0 && minutes == 0 && seconds == 0 {
+                    return Ok(());
+                }
+                f.write_str("T")?;
+                if hours > 0 {
+                    write!(f, "{hours}H")?;
+                }
+                if minutes > 0 {
+                    write!(f, "{minutes}M")?;
+                }
+                if seconds > 0 || (hours == 0 && minutes == 0) {
+                    write!(f, "{seconds}S")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Renders an elapsed time for the timing macros: as `Debug` while under a minute, and
+/// as `1h 23m 45s` from then on.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed(pub Duration);
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 < Duration::from_secs(60) {
+            write!(f, "{:?}", self.0)
+        } else {
+            FmtDurationLong(self.0, DurationStyle::Human).fmt(f)
+        }
+    }
+}
+
+/// Renders `<bytes> in <elapsed> (<rate>/s)`; used by
+/// [`log_throughput!`](crate::log_throughput).
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Throughput {
+    pub bytes: u64,
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for Throughput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} in {}",
+            FmtBytes(self.bytes),
+            FmtDuration(self.elapsed)
+        )?;
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            write!(f, " ({}/s)", FmtBytes((self.bytes as f64 / secs) as u64))
+        } else {
+            // Too fast to measure; any rate would be made up.
+            f.write_str(" (rate unknown)")
+        }
+    }
+}
+
+/// Renders a count followed by a word, e.g. `3 items`; used by [`plural!`](crate::plural).
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub struct Counted<N>(pub N, pub &'static str);
+
+impl<N: fmt::Display> fmt::Display for Counted<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.0, self.1)
+    }
+}
+
+/// Macro to format a `Duration` compactly inside a log message.
+///
+/// # Example
+#[cfg_attr(feature = "defmt", doc = "```ignore")]
+#[cfg_attr(not(feature = "defmt"), doc = "```rust")]
+/// # use std::time::Duration;
+/// # use tacky_borders_logger::{fmt_dur, info};
+/// fn report(elapsed: Duration) {
+///     info!("took {}", fmt_dur!(elapsed));
+///     // Logs: "took 1.23s [fn report]"
+/// }
+/// # assert_eq!(fmt_dur!(Duration::from_millis(1230)).to_string(), "1.23s");
+/// ```
+#[macro_export]
+macro_rules! fmt_dur {
+    ($duration:expr $(,)?) => {
+        $crate::FmtDuration($duration)
+    };
+}
+
+/// Macro to format a long `Duration`, such as an uptime, in days, hours, minutes and seconds.
+///
+/// The optional second argument selects the style: `human` (the default) for
+/// `1h 23m 45s`, or `iso8601` for `PT1H23M45S`.
+///
+/// # Example
+#[cfg_attr(feature = "defmt", doc = "```ignore")]
+#[cfg_attr(not(feature = "defmt"), doc = "```rust")]
+/// # use std::time::Duration;
+/// # use tacky_borders_logger::{fmt_duration_long, info};
+/// fn report_uptime(uptime: Duration) {
+///     info!("up for {}", fmt_duration_long!(uptime));
+///     // Logs: "up for 1h 23m 45s [fn report_uptime]"
+/// }
+/// # let uptime = Duration::from_secs(5025);
+/// # assert_eq!(fmt_duration_long!(uptime).to_string(), "1h 23m 45s");
+/// # assert_eq!(fmt_duration_long!(uptime, iso8601).to_string(), "PT1H23M45S");
+/// ```
+#[macro_export]
+macro_rules! fmt_duration_long {
+    ($duration:expr $(,)?) => {
+        $crate::fmt_duration_long!($duration, human)
+    };
+    ($duration:expr, human $(,)?) => {
+        $crate::FmtDurationLong($duration, $crate::DurationStyle::Human)
+    };
+    ($duration:expr, iso8601 $(,)?) => {
+        $crate::FmtDurationLong($duration, $crate::DurationStyle::Iso8601)
+    };
+}
+
+/// Macro to format a path with `Path::display()` inside a log message.
+///
+/// Accepts anything implementing `AsRef<Path>`, such as `PathBuf`, `&Path` or `&str`.
+///
+/// # Example
+#[cfg_attr(feature = "defmt", doc = "```ignore")]
+#[cfg_attr(not(feature = "defmt"), doc = "```rust")]
+/// # use std::path::PathBuf;
+/// # use tacky_borders_logger::{fmt_path, info};
+/// fn open_config(path: PathBuf) {
+///     info!("opening {}", fmt_path!(path));
+///     // Logs: "opening C:\Users\me\.config\tacky-borders\config.yaml [fn open_config]"
+/// }
+/// ```
+#[macro_export]
+macro_rules! fmt_path {
+    ($path:expr $(,)?) => {
+        ::std::convert::AsRef::<::std::path::Path>::as_ref(&$path).display()
+    };
+}
+
+/// Macro to format a byte count with binary prefixes inside a log message.
+///
+/// Accepts any integer, which is converted with `as u64`.
+///
+/// # Example
+#[cfg_attr(feature = "defmt", doc = "```ignore")]
+#[cfg_attr(not(feature = "defmt"), doc = "```rust")]
+/// # use tacky_borders_logger::{debug, fmt_bytes};
+/// fn report(buffer: &[u8]) {
+///     debug!("buffer holds {}", fmt_bytes!(buffer.len()));
+///     // Logs: "buffer holds 4.2 MiB [fn report]"
+/// }
+/// # assert_eq!(fmt_bytes!(4_404_019).to_string(), "4.2 MiB");
+/// ```
+#[macro_export]
+macro_rules! fmt_bytes {
+    ($bytes:expr $(,)?) => {
+        $crate::FmtBytes($bytes as u64)
+    };
+}
+
+/// Macro to format a number scaled with an SI prefix inside a log message.
+///
+/// `fmt_si!(value, "unit")` scales by powers of 1000, e.g. `1.5 Mbytes` or `12 ms` for
+/// `0.012` seconds; `fmt_si!(value, "B", binary)` scales by powers of 1024, e.g. `1.43 MiB`.
+/// Accepts any number, which is converted with `as f64`.
+///
+/// # Example
+#[cfg_attr(feature = "defmt", doc = "```ignore")]
+#[cfg_attr(not(feature = "defmt"), doc = "```rust")]
+/// # use tacky_borders_logger::{debug, fmt_si};
+/// fn report(rate: f64) {
+///     debug!("bus rate {}", fmt_si!(rate, "Hz"));
+///     // Logs: "bus rate 2.4 GHz [fn report]"
+/// }
+/// # assert_eq!(fmt_si!(1_500_000, "bytes").to_string(), "1.5 Mbytes");
+/// # assert_eq!(fmt_si!(-2500, "W").to_string(), "-2.5 kW");
+/// # assert_eq!(fmt_si!(0, "bytes").to_string(), "0 bytes");
+/// # assert_eq!(fmt_si!(0.012, "s").to_string(), "12 ms");
+/// # assert_eq!(fmt_si!(1_500_000, "B", binary).to_string(), "1.43 MiB");
+/// # assert_eq!(fmt_si!(999_999, "bytes").to_string(), "1 Mbytes");
+/// ```
+#[macro_export]
+macro_rules! fmt_si {
+    ($value:expr, $unit:expr $(,)?) => {
+        $crate::FmtSi($value as f64, $unit, $crate::SiPrefixes::Decimal)
+    };
+    ($value:expr, $unit:expr, binary $(,)?) => {
+        $crate::FmtSi($value as f64, $unit, $crate::SiPrefixes::Binary)
+    };
+}
+
+/// Macro to log a number scaled with an SI prefix and followed by a unit.
+///
+/// This macro logs the value as [`fmt_si!`](crate::fmt_si) formats it, e.g. `1.5 Mbytes`, at
+/// the given level with the name of the function where it is invoked. With `binary` after
+/// the unit, it scales by powers of 1024 instead of 1000.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_si;
+/// fn report(written: u64) {
+///     log_si!(info, written, "bytes");
+///     // Logs: "1.5 Mbytes [fn report]"
+///     log_si!(info, written, "B", binary);
+///     // Logs: "1.43 MiB [fn report]"
+/// }
+/// # report(1_500_000);
+/// ```
+#[macro_export]
+macro_rules! log_si {
+    ($level:ident, $value:expr, $unit:expr $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}",
+            $crate::fmt_si!($value, $unit)
+        )
+    };
+    ($level:ident, $value:expr, $unit:expr, binary $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}",
+            $crate::fmt_si!($value, $unit, binary)
+        )
+    };
+}
+
+/// Macro to pick the singular or plural form of a word for a count inside a log message.
+///
+/// `plural!(n, "item", "items")` yields the word alone; `plural!(count: n, "item", "items")`
+/// yields the count followed by the word, e.g. `1 item` or `3 items`. Only a count of
+/// exactly one is singular. `n` is evaluated once and may be any integer.
+///
+/// # Example
+#[cfg_attr(feature = "defmt", doc = "```ignore")]
+#[cfg_attr(not(feature = "defmt"), doc = "```rust")]
+/// # use tacky_borders_logger::{info, plural};
+/// fn report(processed: usize) {
+///     info!("processed {}", plural!(count: processed, "item", "items"));
+///     // Logs: "processed 1 item [fn report]"
+/// }
+/// # assert_eq!(plural!(1, "item", "items"), "item");
+/// # assert_eq!(plural!(count: 3, "entry", "entries").to_string(), "3 entries");
+/// ```
+#[macro_export]
+macro_rules! plural {
+    (count: $n:expr, $singular:expr, $plural:expr $(,)?) => {
+        match $n {
+            n => $crate::__private_api::Counted(n, if n == 1 { $singular } else { $plural }),
+        }
+    };
+    ($n:expr, $singular:expr, $plural:expr $(,)?) => {
+        if $n == 1 { $singular } else { $plural }
+    };
+}
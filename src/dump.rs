@@ -0,0 +1,155 @@
+//! Minidumps of the current process, written alongside a fatal error's log record.
+
+use std::fs::File;
+use std::io;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::UNIX_EPOCH;
+
+use log::Level;
+
+use crate::__private_api::{self, Location};
+use crate::clock;
+
+static DUMP_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Sets the directory [`fatal_dump!`](crate::fatal_dump) writes minidumps to.
+///
+/// The directory must already exist. Until this is called, dumps are written to the system's
+/// temporary directory, as returned by [`std::env::temp_dir`].
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::set_dump_dir(std::env::temp_dir().join("dumps"));
+/// ```
+pub fn set_dump_dir(dir: impl Into<PathBuf>) {
+    *DUMP_DIR.write().unwrap_or_else(|e| e.into_inner()) = Some(dir.into());
+}
+
+/// Logs `args` at error level, then writes a minidump and logs where it went, or why it
+/// could not be written.
+#[doc(hidden)]
+#[cold]
+pub fn fatal_dump(args: std::fmt::Arguments, fn_name: &str, loc: &Location) {
+    // The original error is logged, and flushed, before anything can go wrong with the dump.
+    if __private_api::enabled(Level::Error) {
+        __private_api::emit(Level::Error, args, fn_name, loc);
+    }
+    log::logger().flush();
+
+    let result = write_dump();
+    if __private_api::enabled(Level::Error) {
+        match &result {
+            Ok(path) => __private_api::emit(
+                Level::Error,
+                format_args!("minidump written to {}", path.display()),
+                fn_name,
+                loc,
+            ),
+            Err(err) => __private_api::emit(
+                Level::Error,
+                format_args!("cannot write minidump: {err}"),
+                fn_name,
+                loc,
+            ),
+        }
+    }
+    log::logger().flush();
+}
+
+/// Writes a minidump of the current process to `<dir>/<exe>-<pid>-<unix secs>.dmp`.
+fn write_dump() -> io::Result<PathBuf> {
+    let dir = DUMP_DIR.read().unwrap_or_else(|e| e.into_inner()).clone();
+    let exe = std::env::current_exe().ok();
+    let stem = exe
+        .as_deref()
+        .and_then(|exe| exe.file_stem())
+        .map_or("process".into(), |stem| stem.to_string_lossy());
+    let secs = clock::system_now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let path = dir
+        .unwrap_or_else(std::env::temp_dir)
+        .join(format!("{stem}-{}-{secs}.dmp", std::process::id()));
+
+    let file = File::create(&path)?;
+    match write_minidump(&file) {
+        Ok(()) => Ok(path),
+        Err(err) => {
+            drop(file);
+            let _ = std::fs::remove_file(&path);
+            Err(err)
+        }
+    }
+}
+
+#[cfg(windows)]
+fn write_minidump(file: &File) -> io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+
+    use windows_sys::Win32::System::Diagnostics::Debug::{MiniDumpNormal, MiniDumpWriteDump};
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, GetCurrentProcessId};
+
+    // SAFETY: the pseudo-handle of the current process is always valid, the file handle is
+    // open for writing for the duration of the call, and the optional parameters are null.
+    let result = unsafe {
+        MiniDumpWriteDump(
+            GetCurrentProcess(),
+            GetCurrentProcessId(),
+            file.as_raw_handle(),
+            MiniDumpNormal,
+            std::ptr::null(),
+            std::ptr::null(),
+            std::ptr::null(),
+        )
+    };
+    match result {
+        0 => Err(io::Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(not(windows))]
+fn write_minidump(_file: &File) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "minidumps are only supported on Windows",
+    ))
+}
+
+/// Macro to log a fatal error and write a minidump of the process for post-mortem debugging.
+///
+/// This macro logs the message at error level with the name of the function where it is
+/// invoked, then writes a minidump of the current process with `MiniDumpWriteDump` to the
+/// directory set with [`set_dump_dir`](crate::set_dump_dir), and logs
+/// `minidump written to <path>`. The logger is flushed after each line. If the dump cannot
+/// be written, `cannot write minidump: <error>` is logged instead; the original message has
+/// already been logged by then either way.
+///
+/// # Example
+/// ```rust,no_run
+/// # use tacky_borders_logger::fatal_dump;
+/// fn on_device_lost() {
+///     fatal_dump!("unrecoverable: device lost");
+///     // Logs: "unrecoverable: device lost [fn on_device_lost]"
+///     // then: "minidump written to C:\Users\me\AppData\Local\Temp\borders-4242-1738311300.dmp
+///     // [fn on_device_lost]"
+///     std::process::abort();
+/// }
+/// ```
+/// # Notes
+/// - The macro does not end the process; it is up to the caller to abort, exit or carry on.
+/// - The dump is written even if error level logging is disabled.
+/// - Dumps are `MiniDumpNormal`: thread stacks and module lists, without heap memory.
+/// - On other systems, the macro still compiles and logs the message, followed by a note
+///   that dumps are not supported.
+#[macro_export]
+macro_rules! fatal_dump {
+    ($($arg:tt)+) => {
+        $crate::__private_api::fatal_dump(
+            format_args!($($arg)+),
+            &$crate::function_name!(),
+            &(module_path!(), module_path!(), file!(), line!()),
+        )
+    };
+}
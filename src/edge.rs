@@ -0,0 +1,256 @@
+//! Macros that log when something changes, rather than every time they are called.
+
+/// Macro to log when a health check flips between ok and failing.
+///
+/// This macro remembers the previous outcome at each call site, and only logs when it
+/// changes: `<component> recovered` at `ok_level` when it becomes ok, and
+/// `<component> failed` at `err_level` when it starts failing. The name of the function
+/// where it is invoked is appended as usual. Calls that repeat the previous outcome log
+/// nothing. The levels default to `info` and `error`.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_transition;
+/// fn poll_dwm(composition_enabled: bool) {
+///     log_transition!(composition_enabled, "dwm composition", ok_level: info, err_level: warn);
+///     // Logs "dwm composition failed [fn poll_dwm]" once when it goes down, and
+///     // "dwm composition recovered [fn poll_dwm]" once when it comes back.
+/// }
+/// ```
+/// # Notes
+/// - Each call site starts out assuming the component is ok, so a first call that fails is
+///   reported, and a first call that succeeds is not.
+#[macro_export]
+macro_rules! log_transition {
+    ($is_ok:expr, $component:expr $(,)?) => {
+        $crate::log_transition!($is_ok, $component, ok_level: info, err_level: error)
+    };
+    ($is_ok:expr, $component:expr, ok_level: $ok_level:ident, err_level: $err_level:ident $(,)?) => {{
+        static WAS_OK: ::std::sync::atomic::AtomicBool = ::std::sync::atomic::AtomicBool::new(true);
+        let is_ok: bool = $is_ok;
+        if WAS_OK.swap(is_ok, ::std::sync::atomic::Ordering::Relaxed) != is_ok {
+            if is_ok {
+                $crate::__log!($crate::__level!($ok_level), "{} recovered", $component);
+            } else {
+                $crate::__log!($crate::__level!($err_level), "{} failed", $component);
+            }
+        }
+    }};
+}
+
+/// Macro to log when something starts failing after having been healthy.
+///
+/// This macro remembers the previous outcome at each call site, and logs `<message> began
+/// failing` at the given level, with the name of the function where it is invoked, only when
+/// it goes from ok to failing. Further failures log nothing, so a failure storm shows up as
+/// a single line. With `recovery:` and a level before the message, it also logs `<message>
+/// recovered` at that level when it goes back to ok. The message is formatted like a log
+/// message.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_first_failure;
+/// fn poll_service(is_ok: bool) {
+///     log_first_failure!(error, is_ok, "service");
+///     // Logs "service began failing [fn poll_service]" once per outage.
+///     log_first_failure!(error, is_ok, recovery: info, "backup service");
+///     // Also logs "backup service recovered [fn poll_service]" at info level when it
+///     // comes back.
+/// }
+/// # poll_service(false);
+/// ```
+/// # Notes
+/// - Each call site starts out assuming it is ok, so a first call that fails is reported.
+/// - Unlike [`log_transition!`](crate::log_transition), recovery is silent unless asked for.
+#[macro_export]
+macro_rules! log_first_failure {
+    ($level:ident, $is_ok:expr, recovery: $recovery:ident, $($arg:tt)+) => {{
+        static WAS_OK: ::std::sync::atomic::AtomicBool = ::std::sync::atomic::AtomicBool::new(true);
+        let is_ok: bool = $is_ok;
+        if WAS_OK.swap(is_ok, ::std::sync::atomic::Ordering::Relaxed) != is_ok {
+            if is_ok {
+                $crate::__log!($crate::__level!($recovery), "{} recovered", format_args!($($arg)+));
+            } else {
+                $crate::__log!($crate::__level!($level), "{} began failing", format_args!($($arg)+));
+            }
+        }
+    }};
+    ($level:ident, $is_ok:expr, $($arg:tt)+) => {{
+        static WAS_OK: ::std::sync::atomic::AtomicBool = ::std::sync::atomic::AtomicBool::new(true);
+        let is_ok: bool = $is_ok;
+        if WAS_OK.swap(is_ok, ::std::sync::atomic::Ordering::Relaxed) && !is_ok {
+            $crate::__log!($crate::__level!($level), "{} began failing", format_args!($($arg)+));
+        }
+    }};
+}
+
+/// Macro to log when a value crosses a threshold, in either direction.
+///
+/// This macro remembers at each call site whether the previous value was above the threshold,
+/// and only logs when that changes: `<message> crossed above <threshold> (now <value>)` when
+/// the value rises past it, and `<message> crossed below <threshold> (now <value>)` when it
+/// falls back, both at the given level with the name of the function where it is invoked.
+/// Values that stay on the same side log nothing. The message is formatted like a log
+/// message.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_crossing;
+/// fn sample_cpu(load: u32) {
+///     log_crossing!(warn, load, 80, "cpu load");
+///     // Logs "cpu load crossed above 80 (now 85) [fn sample_cpu]" once when it rises past
+///     // 80, and "cpu load crossed below 80 (now 60) [fn sample_cpu]" once when it drops.
+/// }
+/// # sample_cpu(85);
+/// ```
+/// # Notes
+/// - A value is above the threshold only if it is greater than it; a value equal to the
+///   threshold counts as below.
+/// - Each call site starts out assuming the value is below the threshold, so a first value
+///   above it is reported, and a first value below it is not.
+/// - The value and threshold must implement `PartialOrd` and `Display`, and are borrowed,
+///   not moved.
+#[macro_export]
+macro_rules! log_crossing {
+    ($level:ident, $value:expr, $threshold:expr, $($arg:tt)+) => {{
+        static WAS_ABOVE: ::std::sync::atomic::AtomicBool =
+            ::std::sync::atomic::AtomicBool::new(false);
+        let (value, threshold) = (&$value, &$threshold);
+        let is_above = value > threshold;
+        if WAS_ABOVE.swap(is_above, ::std::sync::atomic::Ordering::Relaxed) != is_above {
+            $crate::__log!(
+                $crate::__level!($level),
+                "{} crossed {} {} (now {})",
+                format_args!($($arg)+),
+                if is_above { "above" } else { "below" },
+                threshold,
+                value
+            );
+        }
+    }};
+}
+
+/// Macro to log when a collection's capacity changes, to spot reallocation churn.
+///
+/// This macro remembers the capacity seen at each call site, and logs `<message> cap <old>
+/// -> <new> (<change>), len=<len>` at the given level, with the name of the function where it
+/// is invoked, only when it differs from the previous call. The change is `allocated` from a
+/// capacity of zero, `reallocated` when it grew, and `shrunk` when it shrank. Calls with an
+/// unchanged capacity log nothing. The message is formatted like a log message.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_capacity;
+/// fn fill(buffer: &mut Vec<u32>) {
+///     for i in 0..5 {
+///         buffer.push(i);
+///         log_capacity!(debug, buffer, "buffer");
+///     }
+///     // Logs "buffer cap 0 -> 4 (allocated), len=1 [fn fill]" on the first push and
+///     // "buffer cap 4 -> 8 (reallocated), len=5 [fn fill]" on the fifth.
+/// }
+/// # fill(&mut Vec::new());
+/// ```
+/// # Notes
+/// - The collection is borrowed, not moved, and can be anything with `capacity()` and
+///   `len()` methods returning `usize`, such as a `Vec`, `String`, `VecDeque` or `HashMap`.
+/// - Each call site starts out assuming a capacity of zero, so a first call on a collection
+///   that has allocated is reported.
+/// - The capacity is remembered per call site, not per collection: calling it on several
+///   collections from one place logs whenever consecutive calls see different capacities.
+#[macro_export]
+macro_rules! log_capacity {
+    ($level:ident, $collection:expr, $($arg:tt)+) => {{
+        static CAPACITY: ::std::sync::atomic::AtomicUsize =
+            ::std::sync::atomic::AtomicUsize::new(0);
+        let collection = &$collection;
+        let capacity: usize = collection.capacity();
+        let old = CAPACITY.swap(capacity, ::std::sync::atomic::Ordering::Relaxed);
+        if old != capacity {
+            $crate::__log!(
+                $crate::__level!($level),
+                "{} cap {} -> {} ({}), len={}",
+                format_args!($($arg)+),
+                old,
+                capacity,
+                if old == 0 {
+                    "allocated"
+                } else if capacity > old {
+                    "reallocated"
+                } else {
+                    "shrunk"
+                },
+                collection.len()
+            );
+        }
+    }};
+}
+
+/// Macro to log a state machine's transition from one state to another.
+///
+/// This macro logs `state: <from> -> <to> (<reason>)` at the given level with the name of the
+/// function where it is invoked, using the `Debug` representation of both states. Given
+/// `legal:` followed by the allowed `(from, to)` pairs, it checks the transition against them
+/// first, and logs `illegal state transition: <from> -> <to> (<reason>)` at error level
+/// instead if the pair is not among them.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_state;
+/// #[derive(Debug, PartialEq)]
+/// enum Conn {
+///     Idle,
+///     Connecting,
+///     Open,
+/// }
+///
+/// const LEGAL: &[(Conn, Conn)] = &[
+///     (Conn::Idle, Conn::Connecting),
+///     (Conn::Connecting, Conn::Open),
+///     (Conn::Open, Conn::Idle),
+/// ];
+///
+/// fn on_connected(state: &mut Conn) {
+///     let next = Conn::Open;
+///     log_state!(info, *state, next, "handshake done", legal: LEGAL);
+///     // Logs, from Connecting: "state: Connecting -> Open (handshake done) [fn on_connected]"
+///     // and, from Idle, at error level:
+///     // "illegal state transition: Idle -> Open (handshake done) [fn on_connected]"
+///     *state = next;
+/// }
+/// # on_connected(&mut Conn::Connecting);
+/// ```
+/// # Notes
+/// - The states are borrowed, not moved. With `legal:`, they must implement `PartialEq`,
+///   and the pairs can be given as any slice, array or `Vec` of tuples.
+/// - Illegal transitions are logged even if the given level is disabled, as long as error
+///   level is not.
+#[macro_export]
+macro_rules! log_state {
+    ($level:ident, $from:expr, $to:expr, $reason:expr $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "state: {:?} -> {:?} ({})",
+            &$from,
+            &$to,
+            $reason
+        )
+    };
+    ($level:ident, $from:expr, $to:expr, $reason:expr, legal: $legal:expr $(,)?) => {{
+        let (from, to) = (&$from, &$to);
+        if $legal
+            .iter()
+            .any(|(legal_from, legal_to)| legal_from == from && legal_to == to)
+        {
+            $crate::log_state!($level, *from, *to, $reason);
+        } else {
+            $crate::__log!(
+                $crate::__private_api::log::Level::Error,
+                "illegal state transition: {:?} -> {:?} ({})",
+                from,
+                to,
+                $reason
+            );
+        }
+    }};
+}
@@ -0,0 +1,234 @@
+//! Macros for logging the process environment, its configuration and how the program was
+//! started.
+
+use std::env;
+use std::fmt;
+
+/// Renders an environment variable as `NAME=value`, `NAME=***` for names that look like
+/// secrets, or `NAME=<unset>`; for [`log_env!`](crate::log_env).
+#[doc(hidden)]
+pub struct EnvVar<'a>(pub &'a str);
+
+impl fmt::Display for EnvVar<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.0;
+        match env::var_os(name) {
+            None => write!(f, "{name}=<unset>"),
+            Some(_) if is_secret(name) => write!(f, "{name}=***"),
+            Some(value) => write!(f, "{name}={}", value.to_string_lossy()),
+        }
+    }
+}
+
+/// Returns `true` for names matching `*_TOKEN`, `*_SECRET`, `*_KEY` or `*PASSWORD*`, ignoring
+/// case.
+fn is_secret(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    ["_TOKEN", "_SECRET", "_KEY"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+        || name.contains("PASSWORD")
+}
+
+/// Renders a configuration snapshot between `=== config ===` and `=== end config ===`
+/// lines, with the values of fields that look like secrets replaced by `"***"`; for
+/// [`log_config!`](crate::log_config).
+#[doc(hidden)]
+pub fn config_snapshot(text: &str) -> String {
+    let mut snapshot = String::from("=== config ===\n");
+    // The indentation of a redacted field whose value spans several lines, until it closes.
+    let mut skipping: Option<usize> = None;
+    for line in text.lines() {
+        let indent = line.len() - line.trim_start().len();
+        if let Some(skip_indent) = skipping {
+            if indent > skip_indent {
+                continue;
+            }
+            skipping = None;
+            // The line closing the redacted value only contributes its trailing comma.
+            if line.trim_start().starts_with(['}', ']', ')']) {
+                snapshot.push_str(if line.ends_with(',') { ",\n" } else { "\n" });
+                continue;
+            }
+            snapshot.push('\n');
+        }
+
+        match secret_field(line) {
+            Some((key, value)) => {
+                snapshot.push_str(key);
+                snapshot.push_str(": \"***\"");
+                if value.ends_with(['{', '[', '(']) {
+                    skipping = Some(indent);
+                    continue;
+                }
+                if value.ends_with(',') {
+                    snapshot.push(',');
+                }
+            }
+            None => snapshot.push_str(line),
+        }
+        snapshot.push('\n');
+    }
+    if skipping.is_some() {
+        snapshot.push('\n');
+    }
+    snapshot.push_str("=== end config ===");
+    snapshot
+}
+
+/// Splits a line into the key and value of a field that looks like a secret, for `name: value`
+/// in `{:#?}` output or `"name": value` in pretty-printed JSON.
+fn secret_field(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once(": ")?;
+    let name = key.trim().trim_matches('"');
+    let is_field = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    (is_field && is_secret_field(name)).then_some((key, value))
+}
+
+/// Like [`is_secret`], also matching field names that are just `token`, `secret` or `key`.
+fn is_secret_field(name: &str) -> bool {
+    let name = name.replace('-', "_");
+    is_secret(&name)
+        || ["TOKEN", "SECRET", "KEY"]
+            .iter()
+            .any(|word| name.eq_ignore_ascii_case(word))
+}
+
+/// Macro to log a snapshot of the program's effective configuration, hiding likely secrets.
+///
+/// This macro logs the configuration, any value implementing `Debug`, pretty-printed with
+/// `{:#?}` between `=== config ===` and `=== end config ===` lines, with the name of the
+/// function where it is invoked. It is meant to be called once at startup, so the log
+/// records what configuration the program actually ran with. Fields whose names match
+/// `*_token`, `*_secret`, `*_key` or `*password*`, ignoring case, or are just `token`,
+/// `secret` or `key`, have their values shown as `"***"`.
+///
+/// With the `serde` feature, passing a [`Format`](crate::Format) as a third argument logs the
+/// configuration serialized to it instead, e.g. `Format::JsonPretty`.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_config;
+/// #[derive(Debug)]
+/// struct Config {
+///     border_width: u32,
+///     api_token: String,
+/// }
+///
+/// fn main() {
+///     let config = Config { border_width: 2, api_token: "hunter2".into() };
+///     log_config!(info, &config);
+///     // Logs:
+///     // === config ===
+///     // Config {
+///     //     border_width: 2,
+///     //     api_token: "***",
+///     // }
+///     // === end config === [fn main]
+/// }
+/// ```
+/// # Notes
+/// - Redaction goes by field name alone, and works on the rendered text, so it only applies
+///   to fields shown as `name: value`, or `"name": value` in JSON, one per line.
+/// - The configuration is only rendered if the record is going to be logged.
+#[macro_export]
+macro_rules! log_config {
+    ($level:ident, $config:expr $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}",
+            $crate::__private_api::config_snapshot(&::std::format!("{:#?}", $config))
+        )
+    };
+    ($level:ident, $config:expr, $format:expr $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}",
+            $crate::__private_api::config_snapshot(
+                &$crate::__private_api::Serialized($config, $format).to_string()
+            )
+        )
+    };
+}
+
+/// Macro to log the value of an environment variable, hiding likely secrets.
+///
+/// This macro reads the variable and logs `NAME=<value>` with the name of the function
+/// where it is invoked, or `NAME=<unset>` if it is not set. If the name matches `*_TOKEN`,
+/// `*_SECRET`, `*_KEY` or `*PASSWORD*`, ignoring case, the value is shown as `***`.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_env;
+/// fn load_config() {
+///     log_env!(info, "TACKY_CONFIG_DIR");
+///     // Logs: "TACKY_CONFIG_DIR=/home/me/.config/tacky [fn load_config]"
+///     log_env!(info, "GITHUB_TOKEN");
+///     // Logs: "GITHUB_TOKEN=*** [fn load_config]"
+/// }
+/// ```
+/// # Notes
+/// - The variable is only read if the record is going to be logged.
+/// - Redaction goes by the name alone; a secret stored under an innocuous name is logged as is.
+/// - Values that are not valid Unicode are logged lossily.
+#[macro_export]
+macro_rules! log_env {
+    ($level:ident, $name:expr $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}",
+            $crate::__private_api::EnvVar(::std::convert::AsRef::<str>::as_ref(&$name))
+        )
+    };
+}
+
+/// Returns the lines logged by [`log_runtime_info!`](crate::log_runtime_info): the working
+/// directory, the executable path and the arguments.
+#[doc(hidden)]
+pub fn runtime_info() -> [String; 3] {
+    let path = |path: std::io::Result<std::path::PathBuf>| match path {
+        Ok(path) => path.display().to_string(),
+        Err(_) => "<unavailable>".to_owned(),
+    };
+    let args: Vec<String> = env::args_os()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    [
+        format!("cwd={}", path(env::current_dir())),
+        format!("exe={}", path(env::current_exe())),
+        format!("args={args:?}"),
+    ]
+}
+
+/// Macro to log where and how the program was started.
+///
+/// This macro logs three records, each with the name of the function where it is invoked:
+/// `cwd=<current directory>`, `exe=<executable path>` and `args=[<arguments>]`, including
+/// the program name. A path that cannot be determined is logged as `<unavailable>`.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_runtime_info;
+/// fn main() {
+///     log_runtime_info!(info);
+///     // Logs: "cwd=/home/me/project [fn main]"
+///     //       "exe=/home/me/project/target/debug/app [fn main]"
+///     //       "args=[\"target/debug/app\", \"--verbose\"] [fn main]"
+/// }
+/// ```
+/// # Notes
+/// - Arguments that are not valid Unicode are logged lossily.
+#[macro_export]
+macro_rules! log_runtime_info {
+    ($level:ident $(,)?) => {{
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) {
+            for line in $crate::__private_api::runtime_info() {
+                $crate::__log!(level, "{}", line);
+            }
+        }
+    }};
+}
@@ -0,0 +1,441 @@
+//! Macros for logging errors.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::RwLock;
+
+use log::Level;
+
+use crate::__private_api::{self, Location};
+
+type SeverityMapper = Box<dyn Fn(&(dyn Error + 'static)) -> Level + Send + Sync>;
+
+static SEVERITY_MAPPER: RwLock<Option<SeverityMapper>> = RwLock::new(None);
+
+/// Registers the mapping from errors to log levels used by
+/// [`log_by_severity!`](crate::log_by_severity) when no mapper is passed to it.
+///
+/// The mapper receives the error as a trait object, so it can downcast to the application's
+/// error types to read their severity. Errors are logged at error level until one is set.
+///
+/// # Example
+/// ```rust
+/// # use log::Level;
+/// # use std::io;
+/// tacky_borders_logger::set_severity_mapper(|err| match err.downcast_ref::<io::Error>() {
+///     Some(err) if err.kind() == io::ErrorKind::NotFound => Level::Warn,
+///     _ => Level::Error,
+/// });
+/// ```
+pub fn set_severity_mapper<F>(mapper: F)
+where
+    F: Fn(&(dyn Error + 'static)) -> Level + Send + Sync + 'static,
+{
+    *SEVERITY_MAPPER.write().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(mapper));
+}
+
+#[doc(hidden)]
+pub fn severity_of(err: &(dyn Error + 'static)) -> Level {
+    match &*SEVERITY_MAPPER.read().unwrap_or_else(|e| e.into_inner()) {
+        Some(mapper) => mapper(err),
+        None => Level::Error,
+    }
+}
+
+/// Macro to log an error at a level derived from the error itself.
+///
+/// This macro logs the error's `Display` representation with the name of the function
+/// where it is invoked, at the level returned by `mapper` for that error. Without a
+/// mapper, the one registered with [`set_severity_mapper`](crate::set_severity_mapper)
+/// is used, so the severity policy can live in one place.
+///
+/// # Example
+/// ```rust
+/// # use log::Level;
+/// # use tacky_borders_logger::log_by_severity;
+/// #[derive(Debug)]
+/// struct BorderError { fatal: bool }
+/// # impl std::fmt::Display for BorderError {
+/// #     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { f.write_str("border error") }
+/// # }
+/// # impl std::error::Error for BorderError {}
+///
+/// fn refresh(err: BorderError) {
+///     log_by_severity!(err, |e: &BorderError| if e.fatal { Level::Error } else { Level::Warn });
+///     // Logs at warn level: "border error [fn refresh]"
+/// }
+/// ```
+/// # Notes
+/// - With a mapper, the error only needs to implement `Display`; without one, it must
+///   implement `std::error::Error + 'static`.
+#[macro_export]
+macro_rules! log_by_severity {
+    ($err:expr, $mapper:expr $(,)?) => {{
+        let err = &$err;
+        let level: $crate::__private_api::log::Level = ($mapper)(err);
+        $crate::__log!(level, "{}", err);
+    }};
+    ($err:expr $(,)?) => {{
+        let err = &$err;
+        $crate::__log!($crate::__private_api::severity_of(err), "{}", err);
+    }};
+}
+
+/// Logging methods available on every error type.
+///
+/// Methods cannot see the function they are called from, so these use the caller's source
+/// location instead, like [`log_caller`](crate::log_caller), and end the message with
+/// `[at file:line]`. To get the usual `[fn name]` suffix, use [`log_err!`](crate::log_err),
+/// which captures the function name at the call site and hands it to this trait.
+///
+/// # Example
+/// ```rust
+/// use tacky_borders_logger::LoggableError;
+///
+/// fn load_theme() {
+///     if let Err(err) = std::fs::read_to_string("theme.toml") {
+///         err.log_error_ctx("reading theme");
+///         // Logs: "reading theme: No such file or directory (os error 2) [at src/main.rs:5]"
+///     }
+/// }
+/// ```
+pub trait LoggableError: Error {
+    /// Logs the error's `Display` representation at error level.
+    #[track_caller]
+    fn log_error(&self) {
+        crate::log_caller(Level::Error, format_args!("{self}"));
+    }
+
+    /// Logs the error at error level, prefixed with `ctx: `.
+    #[track_caller]
+    fn log_error_ctx(&self, ctx: &str) {
+        crate::log_caller(Level::Error, format_args!("{ctx}: {self}"));
+    }
+
+    #[doc(hidden)]
+    fn __log_error_in(&self, ctx: Option<&str>, fn_name: &str, loc: &Location) {
+        match ctx {
+            Some(ctx) => {
+                __private_api::emit(Level::Error, format_args!("{ctx}: {self}"), fn_name, loc)
+            }
+            None => __private_api::emit(Level::Error, format_args!("{self}"), fn_name, loc),
+        }
+    }
+}
+
+impl<T: Error + ?Sized> LoggableError for T {}
+
+/// Macro to log an error at error level through [`LoggableError`](crate::LoggableError).
+///
+/// This macro logs the error's `Display` representation, optionally prefixed with a
+/// context string, with the name of the function where it is invoked. It works with any
+/// type implementing `std::error::Error`, including `dyn Error` behind a reference or box.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_err;
+/// fn load_theme() {
+///     if let Err(err) = std::fs::read_to_string("theme.toml") {
+///         log_err!(err, "reading theme");
+///         // Logs: "reading theme: No such file or directory (os error 2) [fn load_theme]"
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! log_err {
+    (@ $err:expr, $ctx:expr) => {
+        if $crate::__private_api::enabled($crate::__private_api::log::Level::Error) {
+            use $crate::LoggableError as _;
+            let fn_name = $crate::function_name!();
+            (&$err).__log_error_in(
+                $ctx,
+                &fn_name,
+                &(module_path!(), module_path!(), file!(), line!()),
+            );
+        }
+    };
+    ($err:expr $(,)?) => {
+        $crate::log_err!(@ $err, ::std::option::Option::None)
+    };
+    ($err:expr, $ctx:expr $(,)?) => {
+        $crate::log_err!(@ $err, ::std::option::Option::Some(::std::convert::AsRef::<str>::as_ref(&$ctx)))
+    };
+}
+
+/// A stable hash identifying where an error was logged and what kind of error it was; created
+/// by [`error_fp!`](crate::error_fp). Renders as 16 hex digits.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fingerprint(pub u64);
+
+impl Fingerprint {
+    /// Hashes the call site, the error's type name and the name of its variant, taken from the
+    /// start of its `Debug` output, with 64-bit FNV-1a.
+    pub fn of<E: fmt::Debug + ?Sized>(err: &E, file: &str, line: u32) -> Self {
+        let type_name = std::any::type_name::<E>().trim_start_matches(['&', ' ']);
+        let type_name = type_name.strip_prefix("mut ").unwrap_or(type_name);
+        let debug = format!("{err:?}");
+        let variant = debug
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .next()
+            .unwrap_or_default();
+
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for part in [file, &line.to_string(), type_name, variant] {
+            // The separator keeps e.g. ("ab", "c") and ("a", "bc") apart.
+            for byte in part.bytes().chain([0]) {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+        Self(hash)
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Macro to log an error at error level with a fingerprint for grouping.
+///
+/// This macro logs the error's `Display` representation with the name of the function
+/// where it is invoked and an `fp=<hex>` field, which is the same every time an error of
+/// the same kind is logged from the same place, whatever data its message contains. Error
+/// trackers and log queries can group recurring errors by it rather than by exact text.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::error_fp;
+/// fn read_theme(path: &str) {
+///     if let Err(err) = std::fs::read_to_string(path) {
+///         error_fp!(err);
+///         // Logs: "No such file or directory (os error 2) fp=5f1c0a29b3e4d871 [fn read_theme]"
+///     }
+/// }
+/// ```
+/// # Notes
+/// - The fingerprint is a 64-bit FNV-1a hash of the call site's file and line, the error's
+///   type name and its variant, read from the start of its `Debug` output (e.g. `NotFound`
+///   for `NotFound { path: ... }`). It is stable across runs and builds as long as the
+///   call site does not move, but changes if, say, a line is inserted above it.
+/// - With the `kv` feature, the fingerprint is attached as an `fp` key-value pair instead.
+#[macro_export]
+macro_rules! error_fp {
+    ($err:expr $(,)?) => {
+        if $crate::__private_api::enabled($crate::__private_api::log::Level::Error) {
+            let err = &$err;
+            let fp = $crate::__private_api::Fingerprint::of(err, file!(), line!());
+            let fn_name = $crate::function_name!();
+            $crate::__private_api::emit_fields(
+                $crate::__private_api::log::Level::Error,
+                format_args!("{}", err),
+                &fn_name,
+                &(module_path!(), module_path!(), file!(), line!()),
+                &[("fp", &fp)],
+            );
+        }
+    };
+}
+
+/// Renders a raw OS error code as `<message> (errno N)`, with the message the operating
+/// system gives for it; for [`log_errno!`](crate::log_errno).
+#[doc(hidden)]
+pub struct Errno(pub i32);
+
+impl fmt::Display for Errno {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // `io::Error` renders as "<message> (os error N)"; keep only the message.
+        let message = std::io::Error::from_raw_os_error(self.0).to_string();
+        let suffix = format!(" (os error {})", self.0);
+        let message = message.strip_suffix(&suffix).unwrap_or(&message);
+        write!(f, "{message} (errno {})", self.0)
+    }
+}
+
+/// Macro to log a raw OS error code with its message.
+///
+/// This macro converts an `i32` error code, such as `errno` on Unix or the result of
+/// `GetLastError` on Windows, to the message the operating system gives for it with
+/// `std::io::Error::from_raw_os_error`, and logs `<message>: <os message> (errno N)` with
+/// the name of the function where it is invoked. The message accepts format arguments.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_errno;
+/// fn open_device(errno: i32) {
+///     log_errno!(error, errno, "open failed");
+///     // Logs, for errno 2 on Linux:
+///     // "open failed: No such file or directory (errno 2) [fn open_device]"
+/// }
+/// # open_device(2);
+/// ```
+/// # Notes
+/// - The code is only converted if the record is going to be logged.
+#[macro_export]
+macro_rules! log_errno {
+    ($level:ident, $errno:expr, $($arg:tt)+) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}: {}",
+            format_args!($($arg)+),
+            $crate::__private_api::Errno($errno)
+        )
+    };
+}
+
+/// Values that [`expect_log!`](crate::expect_log) can unwrap.
+#[doc(hidden)]
+pub trait Expect {
+    type Output;
+
+    /// Returns the inner value, or the error to log, if any.
+    fn __split(self) -> Result<Self::Output, Option<Box<dyn fmt::Display>>>;
+}
+
+impl<T, E: fmt::Display + 'static> Expect for Result<T, E> {
+    type Output = T;
+
+    fn __split(self) -> Result<T, Option<Box<dyn fmt::Display>>> {
+        self.map_err(|err| Some(Box::new(err) as Box<dyn fmt::Display>))
+    }
+}
+
+impl<T> Expect for Option<T> {
+    type Output = T;
+
+    fn __split(self) -> Result<T, Option<Box<dyn fmt::Display>>> {
+        self.ok_or(None)
+    }
+}
+
+/// Logs why [`expect_log!`](crate::expect_log) failed, flushes the logger and panics.
+#[doc(hidden)]
+#[cold]
+#[track_caller]
+pub fn expect_failed(
+    ctx: &str,
+    err: Option<Box<dyn fmt::Display>>,
+    fn_name: &str,
+    loc: &Location,
+) -> ! {
+    let message = match err {
+        Some(err) => format!("{ctx}: {err}"),
+        None => format!("{ctx}: value was None"),
+    };
+    if __private_api::enabled(Level::Error) {
+        __private_api::emit(Level::Error, format_args!("{message}"), fn_name, loc);
+        log::logger().flush();
+    }
+    panic!("{message}");
+}
+
+/// Macro to unwrap a `Result` or `Option`, logging the failure before panicking.
+///
+/// This macro returns the `Ok` or `Some` value. Otherwise, it logs `<context>: <error>` (or
+/// `<context>: value was None`) at error level with the name of the function where it is
+/// invoked, flushes the logger and panics with the same message, like `.expect()`. The
+/// failure thus reaches the log sink even when panic messages only go to stderr.
+///
+/// # Example
+/// ```rust,should_panic
+/// # use tacky_borders_logger::expect_log;
+/// fn load_theme() -> String {
+///     expect_log!(std::fs::read_to_string("theme.toml"), "reading theme")
+///     // Logs, then panics: "reading theme: No such file or directory (os error 2) [fn load_theme]"
+/// }
+/// # load_theme();
+/// ```
+/// # Notes
+/// - The error must implement `Display`.
+#[macro_export]
+macro_rules! expect_log {
+    ($value:expr, $ctx:expr $(,)?) => {
+        match $crate::__private_api::Expect::__split($value) {
+            ::std::result::Result::Ok(value) => value,
+            ::std::result::Result::Err(err) => $crate::__private_api::expect_failed(
+                ::std::convert::AsRef::<str>::as_ref(&$ctx),
+                err,
+                &$crate::function_name!(),
+                &(module_path!(), module_path!(), file!(), line!()),
+            ),
+        }
+    };
+}
+
+/// Macro to unwrap an `Option`, logging a warning when the default is used instead.
+///
+/// This macro returns the `Some` value without logging anything. If the option is `None`,
+/// it evaluates the default, logs the message at warn level with the name of the function
+/// where it is invoked, and returns the default, like `.unwrap_or_else()`. The default is
+/// passed to the format string as its first argument; any further arguments follow it.
+/// Fallbacks that would otherwise hide a misconfiguration thus show up in the log.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::unwrap_or_log;
+/// fn border_width(configured: Option<u32>) -> u32 {
+///     unwrap_or_log!(configured, 2, "border width missing, using default {}")
+///     // Logs, for None: "border width missing, using default 2 [fn border_width]"
+/// }
+/// # assert_eq!(border_width(None), 2);
+/// ```
+/// # Notes
+/// - The default is only evaluated if the option is `None`.
+#[macro_export]
+macro_rules! unwrap_or_log {
+    ($option:expr, $default:expr, $fmt:literal $(, $arg:expr)* $(,)?) => {
+        match $option {
+            ::std::option::Option::Some(value) => value,
+            ::std::option::Option::None => {
+                let default = $default;
+                $crate::__log!(
+                    $crate::__private_api::log::Level::Warn,
+                    $fmt,
+                    default
+                    $(, $arg)*
+                );
+                default
+            }
+        }
+    };
+}
+
+/// Macro to log a value that failed validation, with the path of the field holding it.
+///
+/// This macro logs `validation failed at <path> (value=<value>): <reason>` with the name of
+/// the function where it is invoked. The path is a dotted string supplied by the caller,
+/// such as `config.server.port`, the value is shown with `Debug`, and the reason is
+/// formatted like a log message. Pointing at the exact field makes the message actionable,
+/// unlike a generic "invalid config".
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_validation_error;
+/// fn validate_port(port: u32) -> bool {
+///     if !(1..=65535).contains(&port) {
+///         log_validation_error!(warn, "config.server.port", port, "must be 1-65535");
+///         // Logs: "validation failed at config.server.port (value=70000): must be 1-65535
+///         // [fn validate_port]"
+///         return false;
+///     }
+///     true
+/// }
+/// # assert!(!validate_port(70000));
+/// ```
+/// # Notes
+/// - The value is borrowed, not moved, and only formatted if the record is going to be
+///   logged.
+#[macro_export]
+macro_rules! log_validation_error {
+    ($level:ident, $path:expr, $value:expr, $($arg:tt)+) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "validation failed at {} (value={:?}): {}",
+            $path,
+            &$value,
+            format_args!($($arg)+)
+        )
+    };
+}
@@ -0,0 +1,168 @@
+//! Logging with stable event identifiers and explicit correlation ids.
+
+/// Macro to log a message tagged with a stable event identifier.
+///
+/// This macro logs a formatted message at the given level with the name of the function
+/// where it is invoked, and attaches the event id as an `event` field. With the `kv`
+/// feature the id is a key-value pair on the record; otherwise it is rendered as
+/// `event=<id>` before the function name.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::event;
+/// fn check_disk(free: u64) {
+///     event!(error, "disk_full", "only {} bytes left", free);
+///     // Logs: "only 512 bytes left event=disk_full [fn check_disk]"
+/// }
+/// ```
+/// # Notes
+/// - Alerting rules can key off `event=disk_full` regardless of how the message is worded.
+#[macro_export]
+macro_rules! event {
+    ($level:ident, $event:expr, $($arg:tt)+) => ({
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) {
+            let fn_name = $crate::function_name!();
+            $crate::__private_api::emit_fields(
+                level,
+                format_args!($($arg)+),
+                &fn_name,
+                &(module_path!(), module_path!(), file!(), line!()),
+                &[("event", &$event)],
+            );
+        }
+    });
+}
+
+/// Macro to log a structured event with a schema version, for consumers that parse the fields.
+///
+/// This macro logs the event name as the message at the given level with the name of the
+/// function where it is invoked, and attaches the name as an `event` field, the schema
+/// version as a `schema_version` field, and the given `key = value` pairs as the payload.
+/// With the `kv` feature these are key-value pairs on the record; otherwise they are rendered
+/// as `key=value` before the function name. The version is given as `v = <n>` right after
+/// the name, and is 1 when left out.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::schema_event;
+/// fn place_order(id: u64, total: f64) {
+///     schema_event!(info, "order_placed", v = 2, order_id = id, total = total);
+///     // Logs: "order_placed event=order_placed schema_version=2 order_id=7 total=9.5
+///     // [fn place_order]"
+///     schema_event!(info, "cart_cleared");
+///     // Logs: "cart_cleared event=cart_cleared schema_version=1 [fn place_order]"
+/// }
+/// # place_order(7, 9.5);
+/// ```
+/// # Notes
+/// - The version is a `u32`, and payload values can be any `Display` type.
+/// - Keep the version while only adding fields, and bump it when a field is renamed, removed
+///   or changes meaning or type, so consumers can tell the shapes apart and handle each
+///   version they know.
+#[macro_export]
+macro_rules! schema_event {
+    ($level:ident, $event:expr, v = $version:expr $(, $key:ident = $value:expr)* $(,)?) => ({
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) {
+            let fn_name = $crate::function_name!();
+            let (event, version): (_, u32) = (&$event, $version);
+            let event: &str = ::std::convert::AsRef::as_ref(event);
+            $crate::__private_api::emit_fields(
+                level,
+                format_args!("{}", event),
+                &fn_name,
+                &(module_path!(), module_path!(), file!(), line!()),
+                &[
+                    ("event", &event),
+                    ("schema_version", &version),
+                    $((stringify!($key), &$value)),*
+                ],
+            );
+        }
+    });
+    ($level:ident, $event:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        $crate::schema_event!($level, $event, v = 1 $(, $key = $value)*)
+    };
+}
+
+/// Macro to log a message correlated with a parent operation by an explicitly passed id.
+///
+/// This macro logs a formatted message at the given level with the name of the function
+/// where it is invoked, and attaches the parent id as a `parent` field, rendered as
+/// `parent=<id>` before the function name, or as a key-value pair with the `kv` feature.
+/// Unlike [`request_scope!`](crate::request_scope), which tags records through thread-local
+/// state, the id is passed at each call, so it can follow an operation into spawned tasks
+/// and other threads that the thread-local context does not reach.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_corr;
+/// fn spawn_upload(request_id: u64) {
+///     std::thread::spawn(move || {
+///         log_corr!(info, request_id, "upload started");
+///         // Logs: "upload started parent=42 [fn spawn_upload]"
+///     })
+///     .join()
+///     .unwrap();
+/// }
+/// # spawn_upload(42);
+/// ```
+/// # Notes
+/// - The id can be any `Display` value.
+#[macro_export]
+macro_rules! log_corr {
+    ($level:ident, $parent:expr, $($arg:tt)+) => ({
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) {
+            let fn_name = $crate::function_name!();
+            $crate::__private_api::emit_fields(
+                level,
+                format_args!($($arg)+),
+                &fn_name,
+                &(module_path!(), module_path!(), file!(), line!()),
+                &[("parent", &$parent)],
+            );
+        }
+    });
+}
+
+/// Logs a trace message with a `parent=<id>` correlation field; see [`log_corr!`](crate::log_corr).
+#[macro_export]
+macro_rules! trace_corr {
+    ($parent:expr, $($arg:tt)+) => {
+        $crate::log_corr!(trace, $parent, $($arg)+)
+    };
+}
+
+/// Logs a debug message with a `parent=<id>` correlation field; see [`log_corr!`](crate::log_corr).
+#[macro_export]
+macro_rules! debug_corr {
+    ($parent:expr, $($arg:tt)+) => {
+        $crate::log_corr!(debug, $parent, $($arg)+)
+    };
+}
+
+/// Logs an info message with a `parent=<id>` correlation field; see [`log_corr!`](crate::log_corr).
+#[macro_export]
+macro_rules! info_corr {
+    ($parent:expr, $($arg:tt)+) => {
+        $crate::log_corr!(info, $parent, $($arg)+)
+    };
+}
+
+/// Logs a warning with a `parent=<id>` correlation field; see [`log_corr!`](crate::log_corr).
+#[macro_export]
+macro_rules! warn_corr {
+    ($parent:expr, $($arg:tt)+) => {
+        $crate::log_corr!(warn, $parent, $($arg)+)
+    };
+}
+
+/// Logs an error with a `parent=<id>` correlation field; see [`log_corr!`](crate::log_corr).
+#[macro_export]
+macro_rules! error_corr {
+    ($parent:expr, $($arg:tt)+) => {
+        $crate::log_corr!(error, $parent, $($arg)+)
+    };
+}
@@ -0,0 +1,171 @@
+//! Running hashes of the data passing through named checkpoints, for integrity tracing.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+
+/// The 64-bit FNV-1a offset basis and prime. FNV-1a is used rather than `DefaultHasher`
+/// because its output is fixed, so chains can be compared between builds and machines.
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+static CHAINS: Mutex<BTreeMap<String, ChainState>> = Mutex::new(BTreeMap::new());
+
+/// The state of a hash chain, rendered as `<hex> (<n> updates, <n> bytes)`.
+#[doc(hidden)]
+#[derive(Clone, Copy)]
+pub struct ChainState {
+    hash: u64,
+    updates: u64,
+    bytes: u64,
+}
+
+impl ChainState {
+    const EMPTY: Self = Self {
+        hash: FNV_OFFSET,
+        updates: 0,
+        bytes: 0,
+    };
+}
+
+impl fmt::Display for ChainState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:016x} ({} {}, {} {})",
+            self.hash,
+            self.updates,
+            crate::plural!(self.updates, "update", "updates"),
+            self.bytes,
+            crate::plural!(self.bytes, "byte", "bytes")
+        )
+    }
+}
+
+/// Starts the chain called `name` over, for [`hash_chain!`](crate::hash_chain).
+#[doc(hidden)]
+pub fn hash_chain_reset(name: &str) {
+    let mut chains = CHAINS.lock().unwrap_or_else(|e| e.into_inner());
+    chains.insert(name.to_owned(), ChainState::EMPTY);
+}
+
+/// Feeds `bytes` into the chain called `name`, starting it if needed, for
+/// [`hash_update!`](crate::hash_update).
+#[doc(hidden)]
+pub fn hash_chain_update(name: &str, bytes: &[u8]) {
+    let mut chains = CHAINS.lock().unwrap_or_else(|e| e.into_inner());
+    let state = match chains.get_mut(name) {
+        Some(state) => state,
+        None => chains.entry(name.to_owned()).or_insert(ChainState::EMPTY),
+    };
+    for &byte in bytes {
+        state.hash = (state.hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME);
+    }
+    state.updates += 1;
+    state.bytes += bytes.len() as u64;
+}
+
+/// Returns the state of the chain called `name`, if it was started, for
+/// [`log_hash_chain!`](crate::log_hash_chain).
+#[doc(hidden)]
+pub fn hash_chain_state(name: &str) -> Option<ChainState> {
+    let chains = CHAINS.lock().unwrap_or_else(|e| e.into_inner());
+    chains.get(name).copied()
+}
+
+/// Renders the state of a hash chain, or `<not started>`.
+#[doc(hidden)]
+pub struct HashChainText(pub Option<ChainState>);
+
+impl fmt::Display for HashChainText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(state) => write!(f, "{state}"),
+            None => f.write_str("<not started>"),
+        }
+    }
+}
+
+/// Macro to start, or start over, a named hash chain.
+///
+/// A hash chain is a running 64-bit hash of every byte slice fed into it with
+/// [`hash_update!`](crate::hash_update), kept in a process-wide map by name, and logged with
+/// [`log_hash_chain!`](crate::log_hash_chain). Starting it over resets it to the hash of no
+/// data. Chains are also started by their first update, so this is only needed to reset
+/// one, e.g. at the start of each batch.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::hash_chain;
+/// fn start_batch() {
+///     hash_chain!("pipeline");
+/// }
+/// ```
+#[macro_export]
+macro_rules! hash_chain {
+    ($name:expr $(,)?) => {
+        $crate::__private_api::hash_chain_reset(::std::convert::AsRef::<str>::as_ref(&$name))
+    };
+}
+
+/// Macro to feed bytes into a named hash chain.
+///
+/// The bytes can be anything implementing `AsRef<[u8]>`, such as a slice, `Vec<u8>` or
+/// `String`, and are borrowed. If the chain has not been started with
+/// [`hash_chain!`](crate::hash_chain), it is started first. See
+/// [`log_hash_chain!`](crate::log_hash_chain).
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::hash_update;
+/// fn decode(frame: &[u8]) {
+///     hash_update!("pipeline", frame);
+/// }
+/// ```
+#[macro_export]
+macro_rules! hash_update {
+    ($name:expr, $bytes:expr $(,)?) => {
+        $crate::__private_api::hash_chain_update(
+            ::std::convert::AsRef::<str>::as_ref(&$name),
+            ::std::convert::AsRef::<[u8]>::as_ref(&$bytes),
+        )
+    };
+}
+
+/// Macro to log the current hash of a named hash chain, to find where two runs diverge.
+///
+/// This macro logs `hash chain <name>: <hex> (<n> updates, <n> bytes)` at the given level
+/// with the name of the function where it is invoked, or `hash chain <name>: <not started>`
+/// for a chain that was neither started nor updated. Logging the chain at each checkpoint
+/// of a pipeline that feeds its data through [`hash_update!`](crate::hash_update) and
+/// comparing the lines from two runs shows the first checkpoint where the data differs.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{hash_update, log_hash_chain};
+/// fn checkpoint(frames: &[&[u8]]) {
+///     for frame in frames {
+///         hash_update!("pipeline", frame);
+///     }
+///     log_hash_chain!(debug, "pipeline");
+///     // Logs: "hash chain pipeline: d80bda3fbe244a0a (2 updates, 6 bytes) [fn checkpoint]"
+/// }
+/// # checkpoint(&[b"abc", b"def"]);
+/// ```
+/// # Notes
+/// - The hash is 64-bit FNV-1a over the concatenated bytes, which is stable between builds
+///   and platforms, so runs on different machines can be compared too. It is not
+///   cryptographic, and how the bytes are split between updates does not change it.
+#[macro_export]
+macro_rules! log_hash_chain {
+    ($level:ident, $name:expr $(,)?) => {{
+        let name = &$name;
+        let name: &str = ::std::convert::AsRef::as_ref(name);
+        $crate::__log!(
+            $crate::__level!($level),
+            "hash chain {}: {}",
+            name,
+            $crate::__private_api::HashChainText($crate::__private_api::hash_chain_state(name))
+        )
+    }};
+}
@@ -0,0 +1,168 @@
+//! Logging of Windows `HRESULT` and `GetLastError` codes with the message the system gives
+//! for them.
+
+use std::fmt;
+
+/// Renders an `HRESULT` as `<message> (0x8007000E)`, or just the hex code if the system has
+/// no message for it; for [`log_hresult!`](crate::log_hresult).
+#[doc(hidden)]
+pub struct HResult(pub i32);
+
+impl fmt::Display for HResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match message(self.0) {
+            Some(message) => write!(f, "{message} (0x{:08X})", self.0 as u32),
+            None => write!(f, "0x{:08X}", self.0 as u32),
+        }
+    }
+}
+
+/// Renders a Win32 error code as `<message> (error N)`, or `error N` if the system has no
+/// message for it; for [`log_last_error!`](crate::log_last_error).
+#[doc(hidden)]
+pub struct LastError(pub u32);
+
+impl LastError {
+    /// Reads the calling thread's last error: `GetLastError` on Windows, `errno` elsewhere.
+    pub fn get() -> Self {
+        #[cfg(windows)]
+        {
+            // SAFETY: `GetLastError` only reads a thread-local value.
+            Self(unsafe { windows_sys::Win32::Foundation::GetLastError() })
+        }
+        #[cfg(not(windows))]
+        {
+            Self(std::io::Error::last_os_error().raw_os_error().unwrap_or(0) as u32)
+        }
+    }
+}
+
+impl fmt::Display for LastError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(windows)]
+        let message = message(self.0 as i32);
+        // `io::Error` renders as "<message> (os error N)"; keep only the message.
+        #[cfg(not(windows))]
+        let message = {
+            let message = std::io::Error::from_raw_os_error(self.0 as i32).to_string();
+            let suffix = format!(" (os error {})", self.0);
+            Some(message.strip_suffix(&suffix).unwrap_or(&message).to_owned())
+        };
+        match message {
+            Some(message) => write!(f, "{message} (error {})", self.0),
+            None => write!(f, "error {}", self.0),
+        }
+    }
+}
+
+/// Returns the system message for `hr`, without its trailing line break.
+#[cfg(windows)]
+fn message(hr: i32) -> Option<String> {
+    use windows_sys::Win32::System::Diagnostics::Debug::{
+        FORMAT_MESSAGE_FROM_SYSTEM, FORMAT_MESSAGE_IGNORE_INSERTS, FormatMessageW,
+    };
+
+    let format = |code: u32| {
+        let mut buffer = [0u16; 512];
+        // SAFETY: the buffer is valid for writes of `buffer.len()` UTF-16 units, and no
+        // inserts are read as `FORMAT_MESSAGE_IGNORE_INSERTS` is given.
+        let len = unsafe {
+            FormatMessageW(
+                FORMAT_MESSAGE_FROM_SYSTEM | FORMAT_MESSAGE_IGNORE_INSERTS,
+                std::ptr::null(),
+                code,
+                0,
+                buffer.as_mut_ptr(),
+                buffer.len() as u32,
+                std::ptr::null(),
+            )
+        };
+        let message = String::from_utf16_lossy(&buffer[..len as usize]);
+        let message = message.trim_end();
+        (!message.is_empty()).then(|| message.to_owned())
+    };
+
+    let code = hr as u32;
+    // `HRESULT`s wrapping a Win32 error (`FACILITY_WIN32`) are not always in the system's
+    // message table themselves, but the error they wrap is.
+    match format(code) {
+        None if code & 0xFFFF_0000 == 0x8007_0000 => format(code & 0xFFFF),
+        message => message,
+    }
+}
+
+/// Other systems have no message table, so only the hex code is shown.
+#[cfg(not(windows))]
+fn message(_hr: i32) -> Option<String> {
+    None
+}
+
+/// Macro to log a Windows `HRESULT` with its message.
+///
+/// This macro decodes an `HRESULT`, such as one returned by a Direct2D or DWM call, into the
+/// message the system gives for it with `FormatMessageW`, and logs `<message>: <decoded>
+/// (0x8007000E)` with the name of the function where it is invoked. Codes the system has no
+/// message for are shown as just the hex code. The message accepts format arguments.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_hresult;
+/// fn create_render_target(hr: i32) {
+///     log_hresult!(error, hr, "D2D call failed");
+///     // Logs, for E_OUTOFMEMORY on Windows:
+///     // "D2D call failed: Not enough memory resources are available to complete this
+///     // operation. (0x8007000E) [fn create_render_target]"
+/// }
+/// # create_render_target(0x8007_000E_u32 as i32);
+/// ```
+/// # Notes
+/// - The code is an `i32`, like `HRESULT` in `windows-sys`; with the `windows` crate, pass
+///   `hr.0`.
+/// - The code is only decoded if the record is going to be logged.
+/// - On other systems, the macro still compiles, but always shows just the hex code.
+#[macro_export]
+macro_rules! log_hresult {
+    ($level:ident, $hr:expr, $($arg:tt)+) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}: {}",
+            format_args!($($arg)+),
+            $crate::__private_api::HResult($hr)
+        )
+    };
+}
+
+/// Macro to log the calling thread's last Windows error with its message.
+///
+/// This macro reads `GetLastError` before anything else runs, so that neither formatting
+/// nor logging can overwrite it, decodes it with `FormatMessageW`, and logs
+/// `<message>: <decoded> (error N)` with the name of the function where it is invoked. Codes
+/// the system has no message for are shown as `error N`. The message accepts format
+/// arguments.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_last_error;
+/// fn create_border_window() {
+///     // let hwnd = unsafe { CreateWindowExW(...) };
+///     log_last_error!(error, "CreateWindowEx failed");
+///     // Logs, for ERROR_INVALID_WINDOW_HANDLE on Windows:
+///     // "CreateWindowEx failed: Invalid window handle. (error 1400) [fn create_border_window]"
+/// }
+/// ```
+/// # Notes
+/// - Call it right after the failing API call: any call in between may change the last
+///   error.
+/// - On other systems, the macro still compiles, and reports `errno` instead.
+#[macro_export]
+macro_rules! log_last_error {
+    ($level:ident, $($arg:tt)+) => {{
+        let error = $crate::__private_api::LastError::get();
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}: {}",
+            format_args!($($arg)+),
+            error
+        )
+    }};
+}
@@ -0,0 +1,44 @@
+//! Logging of HTTP responses.
+
+use log::Level;
+
+/// Returns the conventional level for an HTTP status code: info for 1xx to 3xx, warn for 4xx
+/// and error for 5xx. Codes outside 100 to 599 are not valid statuses and log a warning.
+#[doc(hidden)]
+pub fn status_level(status: u16) -> Level {
+    match status {
+        100..=399 => Level::Info,
+        500..=599 => Level::Error,
+        _ => Level::Warn,
+    }
+}
+
+/// Macro to log a request's outcome at a level picked from its HTTP status code.
+///
+/// This macro logs `<message> -> <status>` with the name of the function where it is
+/// invoked, at info level for 1xx, 2xx and 3xx statuses, warn level for 4xx and error
+/// level for 5xx. Anything outside 100 to 599 is logged as a warning. The status may be
+/// a `u16` or any type implementing `Into<u16>`.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_status;
+/// fn get_theme(found: bool) -> u16 {
+///     let status = if found { 200 } else { 404 };
+///     log_status!(status, "GET /theme");
+///     // Logs at warn level: "GET /theme -> 404 [fn get_theme]"
+///     status
+/// }
+/// ```
+#[macro_export]
+macro_rules! log_status {
+    ($status:expr, $($arg:tt)+) => {{
+        let status: u16 = ::std::convert::Into::into($status);
+        $crate::__log!(
+            $crate::__private_api::status_level(status),
+            "{} -> {}",
+            format_args!($($arg)+),
+            status
+        );
+    }};
+}
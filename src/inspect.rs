@@ -0,0 +1,926 @@
+//! Macros for logging values together with the context they came from.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use crate::config;
+
+/// Renders labeled values as `a=<Debug>, b=<Debug>`, for [`log_vars!`](crate::log_vars).
+#[doc(hidden)]
+pub struct DebugVars<'a>(pub &'a [(&'a str, &'a dyn fmt::Debug)]);
+
+impl fmt::Display for DebugVars<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (label, value)) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{label}={value:?}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders a value with `{:#?}`, or, if its `{:?}` form is longer than the configured
+/// threshold, the start of that form and its length; for [`log_smart!`](crate::log_smart).
+#[doc(hidden)]
+pub struct SmartDebug<'a>(pub &'a dyn fmt::Debug);
+
+impl fmt::Display for SmartDebug<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let compact = format!("{:?}", self.0);
+        let threshold = config::log_smart_threshold();
+        if compact.len() <= threshold {
+            return write!(f, "{:#?}", self.0);
+        }
+
+        let mut end = threshold;
+        while !compact.is_char_boundary(end) {
+            end -= 1;
+        }
+        write!(
+            f,
+            "{}… ({} bytes of Debug output)",
+            &compact[..end],
+            compact.len()
+        )
+    }
+}
+
+/// Map entries sorted by key, printed like a map with `Debug`; for
+/// [`log_map!`](crate::log_map).
+#[doc(hidden)]
+pub struct SortedMap<K, V>(Vec<(K, V)>);
+
+impl<K: Ord, V> SortedMap<K, V> {
+    pub fn new(entries: impl IntoIterator<Item = (K, V)>) -> Self {
+        let mut entries: Vec<_> = entries.into_iter().collect();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Self(entries)
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for SortedMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(k, v)| (k, v)))
+            .finish()
+    }
+}
+
+/// Macro to log an expression's source text and value, then return the value.
+///
+/// This macro works like `dbg!`, but routes its output through the logger at the
+/// given level instead of printing to stderr. The expression is captured with
+/// `stringify!`, so the log line shows what produced the value.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_expr;
+/// fn parse_port() -> u16 {
+///     let port = log_expr!(debug, "8080".parse::<u16>().unwrap());
+///     // Logs: "\"8080\".parse::<u16>().unwrap() = 8080 [fn parse_port]"
+///     port
+/// }
+/// ```
+/// # Notes
+/// - The value must implement `Debug`, and is moved into and back out of the macro.
+/// - The level is one of `trace`, `debug`, `info`, `warn` or `error`.
+#[macro_export]
+macro_rules! log_expr {
+    ($level:ident, $expr:expr $(,)?) => {
+        match $expr {
+            value => {
+                $crate::__log!(
+                    $crate::__level!($level),
+                    "{} = {:?}",
+                    stringify!($expr),
+                    &value
+                );
+                value
+            }
+        }
+    };
+}
+
+/// Macro to log the concrete type of a value.
+///
+/// This macro logs the source text of the expression and its type name, as given by
+/// `std::any::type_name_of_val`, followed by the name of the function where it is
+/// invoked. Prefixing the value with `?` also logs its `Debug` representation. The
+/// value is only borrowed.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_type;
+/// fn sum<T: std::iter::Sum<T> + Copy + std::fmt::Debug>(items: &[T]) -> T {
+///     log_type!(debug, items);
+///     // Logs: "items: &[u8] [fn sum]"
+///     let total = items.iter().copied().sum();
+///     log_type!(debug, ?total);
+///     // Logs: "total: u8 = 6 [fn sum]"
+///     total
+/// }
+/// ```
+/// # Notes
+/// - Useful in generic functions to confirm which monomorphization is executing.
+/// - Like `function_name!()`, this relies on `type_name`, whose exact output is not guaranteed
+///   to be stable across compiler versions.
+#[macro_export]
+macro_rules! log_type {
+    ($level:ident, ?$value:expr $(,)?) => {{
+        let value = &$value;
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}: {} = {:?}",
+            stringify!($value),
+            ::std::any::type_name_of_val(value),
+            value
+        )
+    }};
+    ($level:ident, $value:expr $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}: {}",
+            stringify!($value),
+            ::std::any::type_name_of_val(&$value)
+        )
+    };
+}
+
+/// Macro to log several labeled values on a single line.
+///
+/// This macro logs `x=<Debug>, y=<Debug>, ...` at the given level, using the source text
+/// of each expression as its label, followed by the name of the function where it is
+/// invoked. Each value must implement `Debug` and is only borrowed.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_vars;
+/// fn resize(width: u32, height: u32, scale: f32) {
+///     log_vars!(debug, width, height, scale);
+///     // Logs: "width=800, height=600, scale=1.5 [fn resize]"
+/// }
+/// ```
+/// # Notes
+/// - Unlike entry logging with `#[logged]`, this can be used anywhere in a function.
+#[macro_export]
+macro_rules! log_vars {
+    ($level:ident, $($var:expr),+ $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}",
+            $crate::__private_api::DebugVars(&[$((stringify!($var), &$var as &dyn ::std::fmt::Debug)),+])
+        )
+    };
+}
+
+/// Macro to log a value in full if it is small, or summarized if it is large.
+///
+/// This macro logs the source text of the expression and its value, with the name of the
+/// function where it is invoked. If the value's `{:?}` form fits within the threshold set
+/// with [`set_log_smart_threshold`](crate::set_log_smart_threshold), 1024 bytes by default,
+/// the value is logged with `{:#?}`; otherwise only the start of the `{:?}` form is logged,
+/// along with its full length, so an unexpectedly large structure cannot flood the log.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_smart;
+/// fn inspect(samples: &[u32]) {
+///     log_smart!(debug, samples);
+///     // Logs, for a large slice: "samples = [1, 2, 3, ...… (48890 bytes of Debug output) [fn inspect]"
+/// }
+/// ```
+/// # Notes
+/// - The value is borrowed, not moved, and must implement `Debug`.
+#[macro_export]
+macro_rules! log_smart {
+    ($level:ident, $value:expr $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{} = {}",
+            stringify!($value),
+            $crate::__private_api::SmartDebug(&$value)
+        )
+    };
+}
+
+/// Macro to log a map's entries in key order.
+///
+/// This macro logs the source text of the expression and its entries, sorted by key and
+/// printed with `{:#?}`, with the name of the function where it is invoked. Unlike
+/// printing a `HashMap` directly, the output is the same from run to run, so logs can be
+/// diffed. Any `IntoIterator<Item = (K, V)>` with `K: Ord` works, including `&HashMap`
+/// and `&BTreeMap`; keys and values must implement `Debug`.
+///
+/// # Example
+/// ```rust
+/// # use std::collections::HashMap;
+/// # use tacky_borders_logger::log_map;
+/// fn dump_colors(colors: &HashMap<&str, u32>) {
+///     log_map!(debug, colors);
+///     // Logs: "colors = {\n    \"active\": 16711680,\n    \"inactive\": 8421504,\n} [fn dump_colors]"
+/// }
+/// ```
+/// # Notes
+/// - The entries are only collected and sorted if the record is going to be logged.
+#[macro_export]
+macro_rules! log_map {
+    ($level:ident, $map:expr $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{} = {:#?}",
+            stringify!($map).trim_start_matches('&').trim_start(),
+            $crate::__private_api::SortedMap::new($map)
+        )
+    };
+}
+
+/// Hashes a value for [`log_hash!`](crate::log_hash).
+#[doc(hidden)]
+pub fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Macro to log the hash of a value, to tell whether it changed without logging it.
+///
+/// This macro logs `hash=<hex>` with the name of the function where it is invoked, where
+/// `<hex>` is the value's 64-bit hash from [`DefaultHasher`](std::hash::DefaultHasher).
+/// Comparing the hashes logged at two points shows whether a large structure was
+/// modified in between. The value is borrowed and must implement `Hash`; byte slices and
+/// `Vec<u8>` are hashed by content.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_hash;
+/// fn regenerate(data: &mut Vec<u8>) {
+///     data.reverse();
+///     log_hash!(debug, data);
+///     // Logs: "hash=8c1f0e52a4b7d3e9 [fn regenerate]"
+/// }
+/// ```
+/// # Notes
+/// - The value is only hashed if the record is going to be logged.
+/// - Hashes are only comparable within one build: `DefaultHasher` may change between Rust
+///   releases.
+#[macro_export]
+macro_rules! log_hash {
+    ($level:ident, $value:expr $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "hash={:016x}",
+            $crate::__private_api::hash_of(&$value)
+        )
+    };
+}
+
+/// Renders the elements that differ between two slices as `[i]: old -> new`, separated by
+/// commas, with `<absent>` for elements past the end of the shorter slice and a note when the
+/// lengths differ, or `<identical>`; for [`log_slice_diff!`](crate::log_slice_diff).
+#[doc(hidden)]
+pub struct SliceDiff<'a, T>(pub &'a [T], pub &'a [T]);
+
+impl<T: PartialEq + fmt::Debug> fmt::Display for SliceDiff<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (before, after) = (self.0, self.1);
+        let mut first = true;
+        if before.len() != after.len() {
+            write!(f, "length {} -> {}", before.len(), after.len())?;
+            first = false;
+        }
+        for i in 0..before.len().max(after.len()) {
+            let (old, new) = (before.get(i), after.get(i));
+            if old == new {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            first = false;
+            write!(f, "[{i}]: ")?;
+            match old {
+                Some(old) => write!(f, "{old:?}")?,
+                None => f.write_str("<absent>")?,
+            }
+            f.write_str(" -> ")?;
+            match new {
+                Some(new) => write!(f, "{new:?}")?,
+                None => f.write_str("<absent>")?,
+            }
+        }
+        if first {
+            f.write_str("<identical>")?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders the first `max` items of a collection as a `Debug` list, followed by
+/// `… and <n> more` if there are more of them; for
+/// [`log_collection!`](crate::log_collection).
+#[doc(hidden)]
+pub fn collection_text<I>(items: I, max: usize) -> String
+where
+    I: IntoIterator,
+    I::Item: fmt::Debug,
+{
+    let mut items = items.into_iter();
+    let mut text = String::from("[");
+    for (i, item) in items.by_ref().take(max).enumerate() {
+        if i > 0 {
+            text.push_str(", ");
+        }
+        let _ = write!(text, "{item:?}");
+    }
+    let rest = items.count();
+    if rest > 0 {
+        if max > 0 {
+            text.push_str(", ");
+        }
+        let _ = write!(text, "… and {rest} more");
+    }
+    text.push(']');
+    text
+}
+
+/// Macro to log a collection, showing at most a given number of its items.
+///
+/// This macro logs the `Debug` representations of the first `max` items of anything
+/// implementing `IntoIterator`, as a list, with the name of the function where it is
+/// invoked. If the collection has more items, the list ends with `… and <n> more`, so a
+/// large collection shows a representative start rather than being dumped in full.
+/// Collections of at most `max` items are logged entirely.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_collection;
+/// fn load(ids: &[u32]) {
+///     log_collection!(debug, ids, max: 3);
+///     // Logs, for 1..=1000: "[1, 2, 3, … and 997 more] [fn load]"
+/// }
+/// # load(&(1..=1000).collect::<Vec<_>>());
+/// ```
+/// # Notes
+/// - The collection is taken by value, so pass a reference, e.g. `&items`, to keep it.
+///   It is only iterated if the record is going to be logged.
+/// - The rest of the collection is iterated to count it, which is cheap for slices and
+///   most collections but not for every iterator.
+#[macro_export]
+macro_rules! log_collection {
+    ($level:ident, $items:expr, max: $max:expr $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}",
+            $crate::__private_api::collection_text($items, $max)
+        )
+    };
+}
+
+/// Macro to log which elements of a slice changed.
+///
+/// This macro compares two slices element by element and logs the ones that differ as
+/// `[i]: old -> new`, separated by commas, with the name of the function where it is
+/// invoked, or `<identical>` if nothing changed. If the lengths differ, the line starts
+/// with `length <old> -> <new>`, and elements only one slice has show `<absent>` on the
+/// other side. Elements must implement `PartialEq` and `Debug`.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_slice_diff;
+/// fn shuffle(data: &mut Vec<u8>) {
+///     let before = data.clone();
+///     data.swap(0, 2);
+///     log_slice_diff!(debug, before, data);
+///     // Logs, for [1, 2, 3]: "[0]: 1 -> 3, [2]: 3 -> 1 [fn shuffle]"
+/// }
+/// ```
+/// # Notes
+/// - Both arguments are borrowed, and anything that derefs to a slice works, such as a
+///   `Vec` or an array.
+/// - The slices are only compared if the record is going to be logged.
+#[macro_export]
+macro_rules! log_slice_diff {
+    ($level:ident, $before:expr, $after:expr $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}",
+            $crate::__private_api::SliceDiff(&$before[..], &$after[..])
+        )
+    };
+}
+
+/// Macro to log two values if they differ.
+///
+/// This macro compares `expected` and `actual` with `!=` and, if they differ, logs
+/// `mismatch: expected=<Debug> actual=<Debug>` with the name of the function where it is
+/// invoked. Nothing is logged when they are equal. Both values are borrowed, not moved,
+/// and must implement `Debug`, and `expected` must implement `PartialEq` with `actual`.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_mismatch;
+/// fn reconcile(stored: u64, counted: u64) {
+///     log_mismatch!(warn, stored, counted);
+///     // Logs, for 10 and 9: "mismatch: expected=10 actual=9 [fn reconcile]"
+/// }
+/// ```
+/// # Notes
+/// - The values are only compared if the record is going to be logged.
+#[macro_export]
+macro_rules! log_mismatch {
+    ($level:ident, $expected:expr, $actual:expr $(,)?) => {{
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) {
+            let (expected, actual) = (&$expected, &$actual);
+            if *expected != *actual {
+                $crate::__log!(
+                    level,
+                    "mismatch: expected={:?} actual={:?}",
+                    expected,
+                    actual
+                );
+            }
+        }
+    }};
+}
+
+/// Macro to log a value only if it falls outside an expected range.
+///
+/// This macro checks whether `value` lies within `range`, which can be any range type, such
+/// as `min..=max`, `min..max` or `..max`, and, if it does not, logs the message with the
+/// name of the function where it is invoked, followed by `(outside <range>)`. Nothing is
+/// logged for values inside the range. The value is passed to the format string as its
+/// first argument; any further arguments follow it.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_out_of_range;
+/// fn check_sensor(celsius: f64) {
+///     log_out_of_range!(warn, celsius, 0.0..=90.0, "temperature {}°C");
+///     // Logs, for 95.5: "temperature 95.5°C (outside 0.0..=90.0) [fn check_sensor]"
+/// }
+/// # check_sensor(95.5);
+/// ```
+/// # Notes
+/// - The value must implement `PartialOrd` with the range's bounds, and the range `Debug`.
+///   Values that compare as neither inside nor outside, such as `NaN`, are logged.
+/// - The value and range are borrowed, not moved, and only compared if the record is going
+///   to be logged.
+#[macro_export]
+macro_rules! log_out_of_range {
+    ($level:ident, $value:expr, $range:expr, $fmt:literal $(, $arg:expr)* $(,)?) => {{
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) {
+            let (value, range) = (&$value, &$range);
+            if !::std::ops::RangeBounds::contains(range, value) {
+                $crate::__log!(
+                    level,
+                    "{} (outside {:?})",
+                    format_args!($fmt, value $(, $arg)*),
+                    range
+                );
+            }
+        }
+    }};
+}
+
+/// Renders where two byte slices first differ as `at offset K (a=0xNN b=0xMM)`, with
+/// `<end>` for a slice that ends there; for [`log_bytes_eq!`](crate::log_bytes_eq).
+#[doc(hidden)]
+pub struct BytesDiff {
+    offset: usize,
+    a: Option<u8>,
+    b: Option<u8>,
+}
+
+impl BytesDiff {
+    /// Returns where `a` and `b` first differ, or `None` if they are equal.
+    pub fn of(a: &[u8], b: &[u8]) -> Option<Self> {
+        let offset = a
+            .iter()
+            .zip(b)
+            .position(|(a, b)| a != b)
+            .unwrap_or(a.len().min(b.len()));
+        (a.len() != b.len() || offset < a.len()).then(|| Self {
+            offset,
+            a: a.get(offset).copied(),
+            b: b.get(offset).copied(),
+        })
+    }
+}
+
+impl fmt::Display for BytesDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at offset {} (", self.offset)?;
+        for (i, (name, byte)) in [("a", self.a), ("b", self.b)].into_iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            match byte {
+                Some(byte) => write!(f, "{name}=0x{byte:02X}")?,
+                None => write!(f, "{name}=<end>")?,
+            }
+        }
+        f.write_str(")")
+    }
+}
+
+/// Macro to log whether two byte buffers are identical, and where they first differ if not.
+///
+/// This macro compares two byte buffers, such as the contents of two files, and logs
+/// `<message>: MATCH (<n> bytes)` at the given level if they are identical, or
+/// `<message>: DIFFER at offset <k> (a=0xNN b=0xMM)` at warn level otherwise, with the
+/// name of the function where it is invoked. The offset is that of the first differing
+/// byte; if one buffer is a prefix of the other, its side shows `<end>`. The message is
+/// formatted like a log message.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_bytes_eq;
+/// fn verify(built: &[u8], expected: &[u8]) {
+///     log_bytes_eq!(info, built, expected, "artifact check");
+///     // Logs: "artifact check: MATCH (4096 bytes) [fn verify]"
+///     // or, at warn level: "artifact check: DIFFER at offset 17 (a=0x3F b=0x40) [fn verify]"
+/// }
+/// # verify(b"abc", b"abd");
+/// ```
+/// # Notes
+/// - Anything implementing `AsRef<[u8]>` can be compared, such as `Vec<u8>`, `&[u8]`,
+///   arrays and `str`. The buffers are borrowed, not moved.
+#[macro_export]
+macro_rules! log_bytes_eq {
+    ($level:ident, $a:expr, $b:expr, $($arg:tt)+) => {{
+        let (a, b) = (&$a, &$b);
+        let a: &[u8] = ::std::convert::AsRef::as_ref(a);
+        let b: &[u8] = ::std::convert::AsRef::as_ref(b);
+        match $crate::__private_api::BytesDiff::of(a, b) {
+            ::std::option::Option::None => $crate::__log!(
+                $crate::__level!($level),
+                "{}: MATCH ({} bytes)",
+                format_args!($($arg)+),
+                a.len()
+            ),
+            ::std::option::Option::Some(diff) => $crate::__log!(
+                $crate::__private_api::log::Level::Warn,
+                "{}: DIFFER {}",
+                format_args!($($arg)+),
+                diff
+            ),
+        }
+    }};
+}
+
+/// Renders the rows of a hexdump where two byte slices differ, for
+/// [`log_hex_diff!`](crate::log_hex_diff).
+///
+/// Only rows of 16 bytes that contain a difference are written, each twice: `-` for the first
+/// slice and `+` for the second, with the offset and the differing bytes in brackets. Past
+/// the end of the shorter slice, `--` pads its row.
+#[doc(hidden)]
+pub struct HexDiff<'a>(pub &'a [u8], pub &'a [u8]);
+
+impl HexDiff<'_> {
+    const ROW: usize = 16;
+}
+
+impl fmt::Display for HexDiff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (a, b) = (self.0, self.1);
+        let len = a.len().max(b.len());
+        let differ = (0..len).filter(|&i| a.get(i) != b.get(i)).count();
+        if differ == 0 {
+            return write!(f, "<identical> ({len} bytes)");
+        }
+        write!(f, "{differ} of {len} bytes differ")?;
+        if a.len() != b.len() {
+            write!(f, ", length {} -> {}", a.len(), b.len())?;
+        }
+
+        for start in (0..len).step_by(Self::ROW) {
+            let row = start..(start + Self::ROW).min(len);
+            if row.clone().all(|i| a.get(i) == b.get(i)) {
+                continue;
+            }
+            for (sign, bytes, other) in [('-', a, b), ('+', b, a)] {
+                write!(f, "\n  {sign}{start:04x}:")?;
+                for i in row.clone() {
+                    match bytes.get(i) {
+                        Some(byte) if other.get(i) == Some(byte) => write!(f, " {byte:02x} ")?,
+                        Some(byte) => write!(f, "[{byte:02x}]")?,
+                        None => f.write_str(" -- ")?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Macro to log a hexdump of where two byte buffers differ.
+///
+/// This macro compares two byte buffers and logs, with the name of the function where it is
+/// invoked, how many bytes differ, followed by one line per buffer for every 16-byte row
+/// that contains a difference: `-` for the first buffer and `+` for the second, with the
+/// differing bytes in brackets. If the lengths differ, both are noted, and the shorter
+/// buffer is padded with `--`. The message defaults to `hex diff`.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_hex_diff;
+/// fn check_frame(sent: &[u8], received: &[u8]) {
+///     log_hex_diff!(debug, sent, received);
+///     // Logs:
+///     // hex diff: 2 of 18 bytes differ, length 18 -> 17 [fn check_frame]
+///     //   -0000: 00  01  02 [03] 04  05 ...
+///     //   +0000: 00  01  02 [ff] 04  05 ...
+///     //   -0010: 10 [11]
+///     //   +0010: 10  --
+///     log_hex_diff!(debug, sent, received, "frame {}", 7);
+///     // Logs the same, starting with "frame 7: ".
+/// }
+/// ```
+/// # Notes
+/// - Anything implementing `AsRef<[u8]>` can be compared, such as `Vec<u8>`, `&[u8]`,
+///   arrays and `str`. The buffers are borrowed, not moved.
+/// - The dump is only built if the level is enabled.
+#[macro_export]
+macro_rules! log_hex_diff {
+    ($level:ident, $a:expr, $b:expr $(,)?) => {
+        $crate::log_hex_diff!($level, $a, $b, "hex diff")
+    };
+    ($level:ident, $a:expr, $b:expr, $($arg:tt)+) => {{
+        let (a, b) = (&$a, &$b);
+        let a: &[u8] = ::std::convert::AsRef::as_ref(a);
+        let b: &[u8] = ::std::convert::AsRef::as_ref(b);
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}: {}",
+            format_args!($($arg)+),
+            $crate::__private_api::HexDiff(a, b)
+        );
+    }};
+}
+
+/// Applies a rendering closure to a value for [`log_with!`](crate::log_with); taking the
+/// closure as an argument lets its parameter type be inferred.
+#[doc(hidden)]
+pub fn render_with<T: ?Sized, R: fmt::Display>(value: &T, render: impl FnOnce(&T) -> R) -> R {
+    render(value)
+}
+
+/// Macro to log a value rendered by a closure given at the call site.
+///
+/// This macro calls the closure with a reference to the value and logs what it returns,
+/// which can be anything implementing `Display`, at the given level with the name of the
+/// function where it is invoked. Types whose `Debug` and `Display` output do not suit the log
+/// can then be rendered inline, without a newtype wrapper.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_with;
+/// struct Window {
+///     hwnd: isize,
+///     title: String,
+/// }
+///
+/// fn track(window: &Window) {
+///     log_with!(debug, window, |w| format!("window {:#x} {:?}", w.hwnd, w.title));
+///     // Logs: "window 0x1f04 \"Terminal\" [fn track]"
+/// }
+/// # track(&Window { hwnd: 0x1f04, title: String::from("Terminal") });
+/// ```
+/// # Notes
+/// - The value is borrowed, not moved, and the closure only runs if the record is going to
+///   be logged.
+#[macro_export]
+macro_rules! log_with {
+    ($level:ident, $value:expr, $render:expr $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}",
+            $crate::__private_api::render_with(&$value, $render)
+        )
+    };
+}
+
+/// Renders the entries that differ between two maps as `+added -removed ~changed(old->new)`,
+/// in key order, or `<unchanged>`; for [`log_map_delta!`](crate::log_map_delta).
+#[doc(hidden)]
+pub struct MapDelta<'a, K, V> {
+    before: BTreeMap<&'a K, &'a V>,
+    after: BTreeMap<&'a K, &'a V>,
+}
+
+impl<'a, K: Ord, V> MapDelta<'a, K, V> {
+    pub fn new(
+        before: impl IntoIterator<Item = (&'a K, &'a V)>,
+        after: impl IntoIterator<Item = (&'a K, &'a V)>,
+    ) -> Self {
+        Self {
+            before: before.into_iter().collect(),
+            after: after.into_iter().collect(),
+        }
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: PartialEq + fmt::Debug> fmt::Display for MapDelta<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut keys: Vec<&K> = self
+            .before
+            .keys()
+            .chain(self.after.keys())
+            .copied()
+            .collect();
+        keys.sort();
+        keys.dedup();
+
+        let mut any = false;
+        for key in keys {
+            let separator = if any { " " } else { "" };
+            match (self.before.get(key), self.after.get(key)) {
+                (None, Some(_)) => write!(f, "{separator}+{key:?}")?,
+                (Some(_), None) => write!(f, "{separator}-{key:?}")?,
+                (Some(old), Some(new)) if old != new => {
+                    write!(f, "{separator}~{key:?}({old:?}->{new:?})")?
+                }
+                _ => continue,
+            }
+            any = true;
+        }
+        if !any {
+            f.write_str("<unchanged>")?;
+        }
+        Ok(())
+    }
+}
+
+/// Macro to log the entries added, removed and changed between two versions of a map.
+///
+/// This macro compares two maps and logs, at the given level with the name of the function
+/// where it is invoked, `+<key>` for every key only in the second, `-<key>` for every key only
+/// in the first and `~<key>(<old>-><new>)` for every key whose value differs, in key order,
+/// or `<unchanged>` if the maps are equal. Keys and values are shown with `Debug`.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_map_delta;
+/// use std::collections::HashMap;
+///
+/// fn refresh(before: &HashMap<&str, u32>, after: &HashMap<&str, u32>) {
+///     log_map_delta!(debug, before, after);
+///     // Logs: "-\"a\" ~\"b\"(2->3) +\"c\" [fn refresh]"
+/// }
+/// # refresh(
+/// #     &HashMap::from([("a", 1), ("b", 2)]),
+/// #     &HashMap::from([("b", 3), ("c", 4)]),
+/// # );
+/// ```
+/// # Notes
+/// - Any map whose `iter()` yields `(&K, &V)` pairs can be compared, such as `HashMap` and
+///   `BTreeMap`; keys must implement `Ord` and `Debug`, and values `PartialEq` and `Debug`.
+/// - The maps are borrowed, not moved, and only compared if the record is going to be
+///   logged.
+#[macro_export]
+macro_rules! log_map_delta {
+    ($level:ident, $before:expr, $after:expr $(,)?) => {{
+        let (before, after) = (&$before, &$after);
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}",
+            $crate::__private_api::MapDelta::new(before.iter(), after.iter())
+        )
+    }};
+}
+
+/// Renders a value's pretty `Debug` output with everything nested deeper than `max_depth`
+/// brackets replaced by `…`; for [`log_depth_limited!`](crate::log_depth_limited).
+#[doc(hidden)]
+pub struct DepthLimited<'a, T: ?Sized>(pub &'a T, pub usize);
+
+impl<T: fmt::Debug + ?Sized> fmt::Display for DepthLimited<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&limit_depth(&format!("{:#?}", self.0), self.1))
+    }
+}
+
+/// Elides the contents of brackets, braces and parentheses nested deeper than `max_depth`
+/// in `Debug` output, leaving string and character literals alone.
+fn limit_depth(debug: &str, max_depth: usize) -> String {
+    let mut limited = String::with_capacity(debug.len());
+    let mut depth = 0;
+    let mut chars = debug.chars().peekable();
+    while let Some(c) = chars.next() {
+        let shown = depth <= max_depth;
+        match c {
+            '"' => {
+                let mut literal = String::from('"');
+                let mut escaped = false;
+                for c in chars.by_ref() {
+                    literal.push(c);
+                    match c {
+                        '\\' if !escaped => escaped = true,
+                        '"' if !escaped => break,
+                        _ => escaped = false,
+                    }
+                }
+                if shown {
+                    limited.push_str(&literal);
+                }
+                continue;
+            }
+            // A character literal such as `'{'`, written by `Debug` for `char`.
+            '\'' => {
+                let mut literal = String::from('\'');
+                while let Some(c) = chars.next() {
+                    literal.push(c);
+                    if c == '\\' {
+                        literal.extend(chars.next());
+                    } else if c == '\'' {
+                        break;
+                    }
+                }
+                if shown {
+                    limited.push_str(&literal);
+                }
+                continue;
+            }
+            '(' | '[' | '{' => {
+                depth += 1;
+                if depth == max_depth + 1 {
+                    limited.push(c);
+                    // An empty collection has nothing to elide, so it is kept as it is.
+                    match chars.next_if(|&next| matches!(next, ')' | ']' | '}')) {
+                        Some(close) => {
+                            limited.push(close);
+                            depth -= 1;
+                        }
+                        None => limited.push('…'),
+                    }
+                    continue;
+                }
+            }
+            ')' | ']' | '}' => {
+                depth = depth.saturating_sub(1);
+                if depth == max_depth {
+                    limited.push(c);
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        if shown && depth <= max_depth {
+            limited.push(c);
+        }
+    }
+    limited
+}
+
+/// Macro to log a value's pretty `Debug` output, cut off below a nesting depth.
+///
+/// This macro renders the value with `{:#?}` and replaces the contents of every bracket,
+/// brace or parenthesis nested deeper than `max_depth` with `…`, so a deeply nested structure
+/// cannot flood the log. It is logged at the given level with the name of the function where
+/// it is invoked.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_depth_limited;
+/// #[derive(Debug)]
+/// struct Tree {
+///     name: &'static str,
+///     children: Vec<Tree>,
+/// }
+///
+/// fn dump(tree: &Tree) {
+///     log_depth_limited!(debug, tree, max_depth: 2);
+///     // Logs:
+///     // Tree {
+///     //     name: "root",
+///     //     children: [
+///     //         Tree {…},
+///     //     ],
+///     // } [fn dump]
+/// }
+/// # dump(&Tree {
+/// #     name: "root",
+/// #     children: vec![Tree { name: "leaf", children: vec![] }],
+/// # });
+/// ```
+/// # Notes
+/// - Depth counts brackets in the `Debug` output, so the fields of a top-level struct are at
+///   depth 1, and a `max_depth` of 0 shows just `Tree {…}`.
+/// - The value is borrowed, not moved, and only formatted if the record is going to be
+///   logged. It is still formatted in full before being cut down.
+#[macro_export]
+macro_rules! log_depth_limited {
+    ($level:ident, $value:expr, max_depth: $max_depth:expr $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}",
+            $crate::__private_api::DepthLimited(&$value, $max_depth)
+        )
+    };
+}
@@ -0,0 +1,60 @@
+//! Labels shared by every record logged from a module.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// A module path to label mapping, registered by [`module_label!`](crate::module_label).
+#[doc(hidden)]
+pub struct ModuleLabel {
+    pub module_path: &'static str,
+    pub label: &'static str,
+}
+
+inventory::collect!(ModuleLabel);
+
+/// Macro to tag every record logged from the current module with a label.
+///
+/// Invoke it once, at the top level of a module. Every record logged through this crate's
+/// macros from that module then ends with ` [<label>]`, in front of the function-name
+/// suffix, without having to pass the label at each call.
+///
+/// # Example
+/// ```rust
+/// mod net {
+///     tacky_borders_logger::module_label!("net");
+///
+///     pub fn connect() {
+///         tacky_borders_logger::info!("connected");
+///         // Logs: "connected [net] [fn connect]"
+///     }
+/// }
+/// ```
+/// # Notes
+/// - The label applies to the exact module it is declared in, not to its child modules.
+/// - Labels are collected when the first record is logged, so they are in effect from the
+///   start of the program, wherever the declaring module sits in the crate.
+/// - If a module declares more than one label, one of them is used.
+#[macro_export]
+macro_rules! module_label {
+    ($label:expr $(,)?) => {
+        $crate::__private_api::inventory::submit! {
+            $crate::__private_api::ModuleLabel {
+                module_path: module_path!(),
+                label: $label,
+            }
+        }
+    };
+}
+
+/// Returns the label registered for `module_path`, if any.
+pub(crate) fn module_label(module_path: &str) -> Option<&'static str> {
+    static LABELS: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+
+    let labels = LABELS.get_or_init(|| {
+        inventory::iter::<ModuleLabel>
+            .into_iter()
+            .map(|label| (label.module_path, label.label))
+            .collect()
+    });
+    labels.get(module_path).copied()
+}
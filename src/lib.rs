@@ -18,8 +18,12 @@
 //! - `warn!` - Logs warnings about potential issues that aren't necessarily errors.
 //! - `error!` - Logs error messages, typically when something goes wrong in the program.
 //!
+//! [`log!`] takes the level as a `log::Level` value instead, so it can be chosen at runtime; the
+//! five macros above delegate to it.
+//!
 //! # Example
 //! ```rust
+//! # use tacky_borders_logger::{debug, error, info, trace, warn};
 //! fn example_function() {
 //!     trace!("This is a trace message.");
 //!     debug!("This is a debug message.");
@@ -37,6 +41,189 @@
 //!
 //! The macros work by formatting a message and appending the function name at the end, which helps in tracing
 //! logs and identifying which function generated a particular log message.
+//!
+//! # Configuration
+//! Runtime options, such as [`set_max_message_len`], are plain functions that can be called at any
+//! point; the macros read the current configuration on every call. [`init_from_env`] applies
+//! the options that can also be set from the environment, such as a `TACKY_LOG_SUFFIX` template
+//! for the function-name suffix.
+//!
+//! # Cargo Features
+//! - `macros` (default) - Re-exports the [`logged`] attribute from `tacky-borders-logger-macros`,
+//!   which logs entry into and exit from a whole function, taking its name from the item itself.
+//! - `defmt` - Redirects `trace!`, `debug!`, `info!`, `warn!`, `error!` and `log!` to the matching
+//!   `defmt` macros, so embedded and hosted builds can share the same logging calls. The function
+//!   name is still appended, as a `{=str}` argument after the message.
+//!
+//!   Because `defmt` formats at compile time, the macros then take a literal format string in
+//!   `defmt`'s syntax (`{=u32}`, `{:?}`, ...), every argument must implement `defmt::Format`, and
+//!   the calling crate must depend on `defmt` directly. Apart from [`set_logging_enabled`], the
+//!   runtime options in this crate do not apply to these macros, as the message is only rendered
+//!   by the host decoder; the helper macros keep logging through `log`.
+//!   The feature is not additive: enabling it anywhere in the dependency graph switches the
+//!   level macros of every crate using this one, so examples and tests that read `log` output
+//!   are skipped with it.
+//!
+//!   The crate has no `no_std` mode yet: `function_name!()` allocates a `String`, so `std` (or at
+//!   least an allocator-backed `std` port) is still required when this feature is enabled.
+//! - `kv` - Attaches the fields added by macros such as [`event!`] to the record as `log`
+//!   key-value pairs, instead of rendering them as `key=value` text in the message. The timing
+//!   macros, such as [`log_duration!`], also attach the elapsed time as a `duration_ms` field.
+//!   The function name itself is attached as a `function` field, next to the module path as
+//!   `module`, instead of the `[fn name]` suffix, so formatters and filters can read it without
+//!   parsing the message. Sinks that only keep text, such as [`LogBuffer`], still get the fields
+//!   and the suffix as text.
+//! - `testing` - Provides the [`testing`] module, with a capturing logger that exposes each
+//!   record as a [`testing::CapturedRecord`] for field-by-field assertions, and `assert_no_errors!`,
+//!   which fails a test if a block logs an error.
+//! - `inline-fn-name` - Lets the string processing behind [`function_name!`] be inlined at every
+//!   call site, rather than kept in one shared function, for code that favors speed over size.
+//! - `debug-sleep` - Makes [`log_sleep!`] actually sleep after logging. Without it, the macro
+//!   only logs, so the perturbation can be left in code that ships.
+//! - `chrono-tz` - Adds [`Timezone::Named`], so the timestamps appended with
+//!   [`set_include_timestamp`] can be rendered in any zone of the IANA database.
+//! - `rayon` - Provides `log_worker!`, which tags a message with the index of the `rayon`
+//!   worker thread logging it.
+//! - `tokio` - Adds [`set_include_task_id`], which appends the id of the current `tokio` task
+//!   to every message logged inside one, and provides `log_timeout!`, which awaits a future
+//!   with a timeout and logs whether it completed in time.
+//! - `chrome_trace` - Provides `chrome_span!`, a span guard that also writes Chrome trace
+//!   events to the file set with `set_chrome_trace_file`, for viewing in `chrome://tracing` or
+//!   Perfetto.
+//! - `metrics` - Provides `log_op_metric!`, which logs the outcome of an operation like
+//!   [`log_op!`] and records its duration and failures through the `metrics` crate.
+//! - `demangle` - Provides `demangle!`, which renders a mangled Rust symbol name readably in
+//!   a log message.
+//! - `counting-allocator` - Provides `CountingAllocator`, a global allocator wrapper that
+//!   counts allocations per thread, and `log_allocs!`, which reports those made by a block.
+//! - `serde` - Provides `log_serde!`, which logs a `Serialize` value as JSON, compact or
+//!   pretty-printed, rather than with `Debug`, and `log_config_diff!`, which logs the keys that
+//!   differ between two serialized configurations.
+//! - `parking_lot` - Lets [`log_lock!`] acquire `parking_lot::Mutex` as well as
+//!   `std::sync::Mutex`.
+//! - `windows` - Provides `log_hresult!`, which logs a Windows `HRESULT` with the message
+//!   `FormatMessageW` gives for it, `log_last_error!`, which does the same for `GetLastError`,
+//!   and `fatal_dump!`, which logs a fatal error and writes a minidump of the process to the
+//!   directory set with `set_dump_dir`.
+//! - `crossbeam-channel`, `flume` - Implement [`HasLen`] for the channel types of these crates, so
+//!   their backlog can be logged with [`log_queue!`].
+
+#[cfg(feature = "counting-allocator")]
+mod alloc;
+mod artifact;
+mod atomic;
+mod audit;
+mod backtrace;
+mod banner;
+mod baseline;
+mod buffered;
+mod caller;
+mod capture;
+#[cfg(feature = "chrome_trace")]
+mod chrome_trace;
+mod clock;
+mod config;
+mod context;
+mod crash;
+mod dedup;
+#[cfg(feature = "defmt")]
+mod defmt_backend;
+#[cfg(feature = "demangle")]
+mod demangle;
+mod destructor;
+mod display;
+#[cfg(feature = "windows")]
+mod dump;
+mod edge;
+mod env;
+mod errors;
+mod event;
+mod hash_chain;
+#[cfg(feature = "windows")]
+mod hresult;
+mod http;
+mod inspect;
+mod label;
+mod lock;
+#[cfg(feature = "metrics")]
+mod metric;
+mod once;
+mod peak;
+mod poll;
+mod queue;
+mod rate;
+mod recursion;
+mod retry;
+mod samples;
+mod scheduled;
+mod secrets;
+#[cfg(feature = "serde")]
+mod serialized;
+mod socket;
+mod span;
+mod stats;
+mod steps;
+mod summary;
+mod table;
+mod tenant;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "tokio")]
+mod timeout;
+mod timestamp;
+mod timing;
+mod traceparent;
+mod verbosity;
+#[cfg(feature = "rayon")]
+mod worker;
+
+#[doc(hidden)]
+pub mod __private_api;
+
+#[cfg(feature = "counting-allocator")]
+pub use alloc::CountingAllocator;
+pub use atomic::AtomicLoad;
+pub use audit::set_audit_file;
+pub use backtrace::set_backtrace_filter;
+pub use buffered::LogBuffer;
+pub use caller::log_caller;
+#[cfg(feature = "chrome_trace")]
+pub use chrome_trace::{ChromeSpan, flush_chrome_trace, set_chrome_trace_file};
+pub use clock::{reset_clock, set_clock, set_system_clock};
+#[cfg(feature = "tokio")]
+pub use config::set_include_task_id;
+pub use config::{
+    FnSuffixStyle, NewlineStyle, SuffixFormatError, init_from_env, level_enabled, logging_enabled,
+    set_adaptive_rate, set_build_id, set_dedup_consecutive, set_dedup_ignores_suffix,
+    set_every_by_msg_capacity, set_field_order, set_flush_on, set_fn_name_in_target,
+    set_fn_suffix_denylist, set_fn_suffix_format, set_fn_suffix_style, set_include_delta,
+    set_include_profile, set_include_seq, set_include_span_elapsed, set_include_thread,
+    set_include_timestamp, set_include_traceparent, set_indent_with_depth, set_level_enabled,
+    set_log_smart_threshold, set_logging_enabled, set_max_message_len, set_newline_normalization,
+    set_newline_style, set_once_per_capacity, set_secret_detection, set_span_stats,
+    set_suffix_levels, set_timestamp_timezone,
+};
+pub use context::spawn_with_context;
+pub use crash::{crash_report, recent_logs, set_crash_buffer};
+pub use dedup::flush_dedup;
+pub use display::{DurationStyle, FmtBytes, FmtDuration, FmtDurationLong, FmtSi, SiPrefixes};
+#[cfg(feature = "windows")]
+pub use dump::set_dump_dir;
+pub use errors::{LoggableError, set_severity_mapper};
+pub use poll::LoggedPoll;
+pub use queue::HasLen;
+pub use scheduled::ScheduledLog;
+#[cfg(feature = "serde")]
+pub use serialized::Format;
+pub use socket::set_socket_sink;
+pub use span::SpanGuard;
+pub use stats::set_stats_interval;
+#[cfg(feature = "macros")]
+pub use tacky_borders_logger_macros::logged;
+pub use timestamp::Timezone;
+pub use timing::TimedFuture;
+pub use traceparent::{clear_traceparent, set_traceparent};
+pub use verbosity::parse_verbosity;
 
 /// Macro to extract the name of the current function as a string.
 ///
@@ -47,6 +234,7 @@
 ///
 /// # Example
 /// ```rust
+/// # use tacky_borders_logger::function_name;
 /// fn example_function() {
 ///     let fn_name = function_name!();
 ///     println!("{}", fn_name); // Prints "example_function"
@@ -56,21 +244,99 @@
 /// - This macro works by exploiting Rust's type system to extract the name
 ///   of the current function.
 /// - It trims the `::f` suffix and handles any nested modules if present.
+/// - Inside a closure or an `async` block or function, it returns the name of the enclosing
+///   function rather than `{{closure}}`. Inside a method, it returns the method name alone,
+///   without the type of the `impl` block.
+/// - Each call site only declares a marker function and passes its type to a small generic
+///   shim; the string processing lives in one shared function in this crate, which keeps the
+///   code emitted per log statement small. The `inline-fn-name` feature lets the compiler
+///   inline that function at every call site instead, trading size for a call.
 #[macro_export]
 macro_rules! function_name {
     () => {{
         fn f() {}
-        fn type_name_of<T>(_: T) -> String {
-            std::any::type_name::<T>().to_string()
-        }
-        let name = type_name_of(f);
-        let func_name = &name[..name.len() - 3]; // Remove the `::f`
-        let func_name_split: Vec<String> = func_name.split("::").map(|s| s.to_string()).collect();
-        let last_part = func_name_split.last().unwrap();
-        last_part.clone()
+        $crate::__private_api::fn_name_of(f)
+    }};
+}
+
+/// Macro to get the current function name qualified with the innermost module name.
+///
+/// This macro combines the last segment of `module_path!()` with the name returned by
+/// `function_name!()`, giving one level of module disambiguation without the full crate
+/// path, e.g. `net::retry` for a `retry` function in `my_app::net`.
+///
+/// # Example
+/// ```rust
+/// mod net {
+///     # use tacky_borders_logger::module_and_fn;
+///     pub fn retry() -> String {
+///         module_and_fn!() // Returns "net::retry"
+///     }
+/// }
+/// ```
+/// # Notes
+/// - At the crate root, the module name is the crate name.
+#[macro_export]
+macro_rules! module_and_fn {
+    () => {{
+        let module = module_path!();
+        let module = module.rsplit("::").next().unwrap_or(module);
+        format!("{}::{}", module, $crate::function_name!())
     }};
 }
 
+/// Macro to get the current function name qualified with its full module path.
+///
+/// This macro combines `module_path!()` with the name returned by `function_name!()`, e.g.
+/// `my_app::net::retry` for a `retry` function in `my_app::net`, which tells apart functions
+/// of the same name in different modules. [`FnSuffixStyle::Path`] makes the `[fn ...]` suffix
+/// use this form.
+///
+/// # Example
+/// ```rust
+/// mod net {
+///     # use tacky_borders_logger::function_path;
+///     pub fn retry() -> String {
+///         function_path!() // Returns "my_app::net::retry" in the `my_app` crate
+///     }
+/// }
+/// ```
+/// # Notes
+/// - Like `function_name!()`, it names a method without the type of its `impl` block.
+#[macro_export]
+macro_rules! function_path {
+    () => {
+        format!("{}::{}", module_path!(), $crate::function_name!())
+    };
+}
+
+/// Macro to log a message at a level chosen at runtime, with the current function name.
+///
+/// This macro takes a `log::Level` value, which can be computed at runtime, followed by the
+/// format string and arguments, and logs the message at that level with the name of the
+/// function where it is invoked, exactly like the macro for that level. [`trace!`],
+/// [`debug!`], [`info!`], [`warn!`] and [`error!`] all delegate to it.
+///
+/// # Example
+/// ```rust
+/// # use log::Level;
+/// # use tacky_borders_logger::log;
+/// fn retry(n: u32) {
+///     let level = if n > 3 { Level::Warn } else { Level::Debug };
+///     log!(level, "retrying {n}");
+///     // Logs: "retrying 4 [fn retry]" at warn level
+/// }
+/// # retry(4);
+/// ```
+/// # Notes
+/// - With the `defmt` feature, the level is matched to the corresponding `defmt` macro, and the
+///   message must be a literal format string in `defmt`'s syntax.
+#[cfg(not(feature = "defmt"))]
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => ($crate::__log!($level, $($arg)+));
+}
+
 /// Macro to log debug-level messages with the current function name.
 ///
 /// This macro logs a debug message along with the name of the function
@@ -78,6 +344,7 @@ macro_rules! function_name {
 ///
 /// # Example
 /// ```rust
+/// # use tacky_borders_logger::debug;
 /// fn some_function() {
 ///     debug!("This is a debug message.");
 ///     // Logs: "This is a debug message. [fn some_function]"
@@ -86,13 +353,10 @@ macro_rules! function_name {
 /// # Notes
 /// - It appends the function name dynamically for context.
 /// - Useful for detailed logging during development.
+#[cfg(not(feature = "defmt"))]
 #[macro_export]
 macro_rules! debug {
-    ($($arg:tt)+) => ({
-        let fn_name = function_name!();
-        let formatted_message = format!("{} [fn {}]", format_args!($($arg)*), fn_name);
-        log::debug!("{}", formatted_message);
-    });
+    ($($arg:tt)+) => ($crate::log!($crate::__private_api::log::Level::Debug, $($arg)+));
 }
 
 /// Macro to log info-level messages with the current function name.
@@ -102,6 +366,7 @@ macro_rules! debug {
 ///
 /// # Example
 /// ```rust
+/// # use tacky_borders_logger::info;
 /// fn example_function() {
 ///     info!("This is an info message.");
 ///     // Logs: "This is an info message. [fn example_function]"
@@ -110,13 +375,10 @@ macro_rules! debug {
 /// # Notes
 /// - This macro provides a simple way to log information along with the
 ///   function name.
+#[cfg(not(feature = "defmt"))]
 #[macro_export]
 macro_rules! info {
-    ($($arg:tt)+) => ({
-        let fn_name = function_name!();
-        let formatted_message = format!("{} [fn {}]", format_args!($($arg)*), fn_name);
-        log::info!("{}", formatted_message);
-    });
+    ($($arg:tt)+) => ($crate::log!($crate::__private_api::log::Level::Info, $($arg)+));
 }
 
 /// Macro to log error-level messages with the current function name.
@@ -126,6 +388,7 @@ macro_rules! info {
 ///
 /// # Example
 /// ```rust
+/// # use tacky_borders_logger::error;
 /// fn another_function() {
 ///     error!("An error occurred!");
 ///     // Logs: "An error occurred! [fn another_function]"
@@ -133,13 +396,10 @@ macro_rules! info {
 /// ```
 /// # Notes
 /// - Used for logging error messages with context about where they occurred.
+#[cfg(not(feature = "defmt"))]
 #[macro_export]
 macro_rules! error {
-    ($($arg:tt)+) => ({
-        let fn_name = function_name!();
-        let formatted_message = format!("{} [fn {}]", format_args!($($arg)*), fn_name);
-        log::error!("{}", formatted_message);
-    });
+    ($($arg:tt)+) => ($crate::log!($crate::__private_api::log::Level::Error, $($arg)+));
 }
 
 /// Macro to log warning-level messages with the current function name.
@@ -149,6 +409,7 @@ macro_rules! error {
 ///
 /// # Example
 /// ```rust
+/// # use tacky_borders_logger::warn;
 /// fn some_function() {
 ///     warn!("This is a warning message.");
 ///     // Logs: "This is a warning message. [fn some_function]"
@@ -157,13 +418,10 @@ macro_rules! error {
 /// # Notes
 /// - This macro allows for logging warnings while automatically appending
 ///   the function name.
+#[cfg(not(feature = "defmt"))]
 #[macro_export]
 macro_rules! warn {
-    ($($arg:tt)+) => ({
-        let fn_name = function_name!();
-        let formatted_message = format!("{} [fn {}]", format_args!($($arg)*), fn_name);
-        log::warn!("{}", formatted_message);
-    });
+    ($($arg:tt)+) => ($crate::log!($crate::__private_api::log::Level::Warn, $($arg)+));
 }
 
 /// Macro to log trace-level messages with the current function name.
@@ -173,19 +431,85 @@ macro_rules! warn {
 ///
 /// # Example
 /// ```rust
+/// # use tacky_borders_logger::trace;
 /// fn any_function() {
-///     warn!("This is a trace message.");
+///     trace!("This is a trace message.");
 ///     // Logs: "This is a trace message. [fn any_function]"
 /// }
 /// ```
 /// # Notes
 /// - This macro allows for logging traces while automatically appending
 ///   the function name.
+#[cfg(not(feature = "defmt"))]
 #[macro_export]
 macro_rules! trace {
-    ($($arg:tt)+) => ({
-        let fn_name = function_name!();
-        let formatted_message = format!("{} at [fn {}]", format_args!($($arg)*), fn_name);
-        log::trace!("{}", formatted_message);
+    ($($arg:tt)+) => ($crate::log!($crate::__private_api::log::Level::Trace, $($arg)+));
+}
+
+/// Macro to log a message only when a `cfg` predicate of the calling crate holds.
+///
+/// This macro expands to `$level!(...)`, such as [`debug!`], when the predicate holds and to
+/// nothing otherwise, so the message, its arguments and the function name lookup are not even
+/// compiled in. The predicate is evaluated in the crate invoking the macro, so libraries can
+/// gate optional instrumentation behind their own cargo features.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::flog;
+/// fn layout(width: u32) {
+///     flog!(cfg(feature = "diag"), debug, "layout width={}", width);
+///     // With the calling crate's `diag` feature, logs: "layout width=1920 [fn layout]"
+/// }
+/// ```
+/// # Notes
+/// - Any `cfg` predicate works, e.g. `cfg(all(debug_assertions, feature = "diag"))`.
+/// - When the predicate does not hold, variables used only in the message are reported as
+///   unused, as with any `#[cfg]`-gated statement.
+#[macro_export]
+macro_rules! flog {
+    (cfg($($predicate:tt)+), $level:ident, $($arg:tt)+) => {{
+        #[cfg($($predicate)+)]
+        $crate::$level!($($arg)+);
+    }};
+}
+
+/// Maps a level name (`trace`, `debug`, `info`, `warn`, `error`) to its `log::Level`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __level {
+    (trace) => {
+        $crate::__private_api::log::Level::Trace
+    };
+    (debug) => {
+        $crate::__private_api::log::Level::Debug
+    };
+    (info) => {
+        $crate::__private_api::log::Level::Info
+    };
+    (warn) => {
+        $crate::__private_api::log::Level::Warn
+    };
+    (error) => {
+        $crate::__private_api::log::Level::Error
+    };
+}
+
+/// Logs a formatted message at a `log::Level` value with the `[fn name]` suffix.
+///
+/// Shared by the helper macros so they format their output the same way as `debug!` and friends.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log {
+    ($level:expr, $($arg:tt)+) => ({
+        let level = $level;
+        if $crate::__private_api::enabled(level) {
+            let fn_name = $crate::function_name!();
+            $crate::__private_api::emit(
+                level,
+                format_args!($($arg)+),
+                &fn_name,
+                &(module_path!(), module_path!(), file!(), line!()),
+            );
+        }
     });
 }
@@ -0,0 +1,189 @@
+//! Acquiring locks while watching for contention.
+
+use std::sync::{Arc, LockResult, Mutex, MutexGuard, TryLockError};
+use std::thread;
+use std::time::Duration;
+
+use log::Level;
+
+use crate::__private_api::{self, Elapsed, Location};
+use crate::clock;
+
+/// Locks that [`log_lock!`](crate::log_lock) can acquire.
+#[doc(hidden)]
+pub trait Lockable {
+    /// What acquiring the lock returns: the guard, or, for `std` locks, a `LockResult` of it.
+    type Guard<'a>
+    where
+        Self: 'a;
+
+    /// Acquires the lock if it is free.
+    fn __try_lock(&self) -> Option<Self::Guard<'_>>;
+
+    /// Blocks until the lock is acquired.
+    fn __lock(&self) -> Self::Guard<'_>;
+}
+
+impl<T: ?Sized> Lockable for Mutex<T> {
+    type Guard<'a>
+        = LockResult<MutexGuard<'a, T>>
+    where
+        Self: 'a;
+
+    fn __try_lock(&self) -> Option<Self::Guard<'_>> {
+        match self.try_lock() {
+            Ok(guard) => Some(Ok(guard)),
+            Err(TryLockError::Poisoned(poisoned)) => Some(Err(poisoned)),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+
+    fn __lock(&self) -> Self::Guard<'_> {
+        self.lock()
+    }
+}
+
+impl<L: Lockable + ?Sized> Lockable for &L {
+    type Guard<'a>
+        = L::Guard<'a>
+    where
+        Self: 'a;
+
+    fn __try_lock(&self) -> Option<Self::Guard<'_>> {
+        (**self).__try_lock()
+    }
+
+    fn __lock(&self) -> Self::Guard<'_> {
+        (**self).__lock()
+    }
+}
+
+impl<L: Lockable + ?Sized> Lockable for Arc<L> {
+    type Guard<'a>
+        = L::Guard<'a>
+    where
+        Self: 'a;
+
+    fn __try_lock(&self) -> Option<Self::Guard<'_>> {
+        (**self).__try_lock()
+    }
+
+    fn __lock(&self) -> Self::Guard<'_> {
+        (**self).__lock()
+    }
+}
+
+#[cfg(feature = "parking_lot")]
+impl<T: ?Sized> Lockable for parking_lot::Mutex<T> {
+    type Guard<'a>
+        = parking_lot::MutexGuard<'a, T>
+    where
+        Self: 'a;
+
+    fn __try_lock(&self) -> Option<Self::Guard<'_>> {
+        self.try_lock()
+    }
+
+    fn __lock(&self) -> Self::Guard<'_> {
+        self.lock()
+    }
+}
+
+/// Acquires `lock`, found held by [`log_lock!`](crate::log_lock), retrying until `threshold`
+/// has passed, then logs that it is contended and blocks on it; once acquired, logs how long
+/// that took.
+#[doc(hidden)]
+#[cold]
+pub fn lock_contended<'a, L: Lockable>(
+    lock: &'a L,
+    threshold: Duration,
+    level: Level,
+    name: &str,
+    fn_name: &str,
+    loc: &Location,
+) -> L::Guard<'a> {
+    let start = clock::now();
+    let mut attempts = 0u32;
+    while clock::elapsed_since(start) < threshold {
+        if let Some(guard) = lock.__try_lock() {
+            return guard;
+        }
+        // Spin briefly for locks held over a short critical section, then back off.
+        attempts += 1;
+        if attempts < 64 {
+            thread::yield_now();
+        } else {
+            thread::sleep(Duration::from_micros(50));
+        }
+    }
+
+    let enabled = __private_api::enabled(level);
+    if enabled {
+        __private_api::emit(
+            level,
+            format_args!(
+                "lock {name} contended, still waiting after {}",
+                Elapsed(threshold)
+            ),
+            fn_name,
+            loc,
+        );
+    }
+    let guard = lock.__lock();
+    if enabled {
+        let elapsed = clock::elapsed_since(start);
+        __private_api::emit_timed(
+            level,
+            format_args!("lock {name} acquired after {}", Elapsed(elapsed)),
+            fn_name,
+            loc,
+            elapsed,
+        );
+    }
+    guard
+}
+
+/// Macro to acquire a mutex, logging if that takes longer than a threshold.
+///
+/// This macro acquires the mutex like `lock()` and returns what `lock()` returns. If the
+/// mutex is held, it keeps retrying until `threshold` has passed, then logs `lock <name>
+/// contended, still waiting after <threshold>`, blocks until it gets the lock, and logs
+/// `lock <name> acquired after <elapsed>`, where `<name>` is the source text of the mutex
+/// expression. Both lines are logged at the given level with the name of the function
+/// where the macro is invoked. An uncontended lock logs nothing and costs a single
+/// `try_lock`.
+///
+/// # Example
+/// ```rust
+/// # use std::sync::Mutex;
+/// # use std::time::Duration;
+/// # use tacky_borders_logger::log_lock;
+/// fn redraw(borders: &Mutex<Vec<u32>>) {
+///     let borders = log_lock!(warn, Duration::from_millis(50), borders).unwrap();
+///     // Logs, if another thread held the lock for a while:
+///     // "lock borders contended, still waiting after 50ms [fn redraw]"
+///     // "lock borders acquired after 73.2ms [fn redraw]"
+/// }
+/// ```
+/// # Notes
+/// - Works with `std::sync::Mutex`, returning a `LockResult`, and, with the `parking_lot`
+///   feature, `parking_lot::Mutex`, returning the guard, either directly, behind a reference
+///   or in an `Arc`.
+/// - The first line is logged while still waiting, so a deadlock shows up in the logs.
+#[macro_export]
+macro_rules! log_lock {
+    ($level:ident, $threshold:expr, $lock:expr $(,)?) => {{
+        let lock = &$lock;
+        match $crate::__private_api::Lockable::__try_lock(lock) {
+            ::std::option::Option::Some(guard) => guard,
+            ::std::option::Option::None => $crate::__private_api::lock_contended(
+                lock,
+                $threshold,
+                $crate::__level!($level),
+                stringify!($lock),
+                &$crate::function_name!(),
+                &(module_path!(), module_path!(), file!(), line!()),
+            ),
+        }
+    }};
+}
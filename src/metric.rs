@@ -0,0 +1,56 @@
+//! Operations logged and recorded as `metrics` at the same time.
+
+use std::time::Duration;
+
+/// Records the duration of an operation in the `<label>_duration` histogram, in seconds, and
+/// counts a failure in the `<label>_errors` counter; for
+/// [`log_op_metric!`](crate::log_op_metric).
+#[doc(hidden)]
+pub fn record_op(label: &str, elapsed: Duration, failed: bool) {
+    metrics::histogram!(format!("{label}_duration")).record(elapsed.as_secs_f64());
+    if failed {
+        metrics::counter!(format!("{label}_errors")).increment(1);
+    }
+}
+
+/// Macro to time a fallible block, log its outcome and record it as metrics.
+///
+/// This macro works like [`log_op!`](crate::log_op): it runs the block, which must evaluate
+/// to a `Result` whose error implements `Display`, logs `<label> OK in <elapsed>` at the given
+/// level, or `<label> FAILED in <elapsed>: <error>` at error level, with the name of the
+/// function where it is invoked, and returns the `Result`. In addition, it records the
+/// duration in seconds in the `<label>_duration` histogram and, on failure, increments the
+/// `<label>_errors` counter, through the recorder installed for the `metrics` crate. The
+/// level defaults to info.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_op_metric;
+/// fn load_user(id: u32) -> Result<String, String> {
+///     log_op_metric!("db_query", {
+///         if id == 0 { Err(String::from("no such user")) } else { Ok(String::from("admin")) }
+///     })
+///     // Logs: "db_query OK in 12ms [fn load_user]", records 0.012 in the
+///     // `db_query_duration` histogram, and increments `db_query_errors` on failure.
+/// }
+/// # load_user(1).ok();
+/// ```
+/// # Notes
+/// - Available with the `metrics` feature.
+/// - The label is used as the prefix of both metric names, so it should be a valid metric
+///   name itself, such as `db_query`.
+/// - Metrics are recorded even if the level is disabled, so they never miss an operation.
+#[macro_export]
+macro_rules! log_op_metric {
+    ($label:expr, $block:expr $(,)?) => {
+        $crate::log_op_metric!(info, $label, $block)
+    };
+    ($level:ident, $label:expr, $block:expr $(,)?) => {{
+        let start = $crate::__private_api::now();
+        let result = $block;
+        let elapsed = $crate::__private_api::elapsed_since(start);
+        $crate::__private_api::record_op(&$label, elapsed, result.is_err());
+        $crate::__log_outcome!($level, $label, elapsed, &result);
+        result
+    }};
+}
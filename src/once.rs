@@ -0,0 +1,162 @@
+//! Logging once per distinct value.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+use crate::config;
+
+/// A key of any type, compared by type and value.
+trait Key: Any + Send {
+    fn as_any(&self) -> &dyn Any;
+    fn eq_key(&self, other: &dyn Key) -> bool;
+    fn hash_key(&self, state: &mut dyn Hasher);
+}
+
+impl<K: Hash + Eq + Send + 'static> Key for K {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_key(&self, other: &dyn Key) -> bool {
+        other.as_any().downcast_ref::<K>() == Some(self)
+    }
+
+    fn hash_key(&self, mut state: &mut dyn Hasher) {
+        self.type_id().hash(&mut state);
+        self.hash(&mut state);
+    }
+}
+
+impl PartialEq for dyn Key {
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_key(other)
+    }
+}
+
+impl Eq for dyn Key {}
+
+impl Hash for dyn Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash_key(state);
+    }
+}
+
+/// Every key seen so far, with the tick at which it was last seen.
+struct Seen {
+    keys: HashMap<Box<dyn Key>, u64>,
+    tick: u64,
+}
+
+static SEEN: Mutex<Option<Seen>> = Mutex::new(None);
+
+/// Returns `true` the first time `key` is passed, and records it.
+///
+/// With a capacity set, the least recently seen key is forgotten when a new one does not fit.
+#[doc(hidden)]
+pub fn first_seen<K: Hash + Eq + Clone + Send + 'static>(key: &K) -> bool {
+    let mut seen = SEEN.lock().unwrap_or_else(|e| e.into_inner());
+    let seen = seen.get_or_insert_with(|| Seen {
+        keys: HashMap::new(),
+        tick: 0,
+    });
+    seen.tick += 1;
+    let tick = seen.tick;
+
+    if let Some(last_seen) = seen.keys.get_mut(key as &dyn Key) {
+        *last_seen = tick;
+        return false;
+    }
+
+    // Only runs once the set is full, so a linear scan keeps hits cheap. Ticks are unique, so
+    // each pass removes exactly one key.
+    let capacity = config::once_per_capacity().unwrap_or(usize::MAX).max(1);
+    while seen.keys.len() >= capacity
+        && let Some(oldest) = seen.keys.values().min().copied()
+    {
+        seen.keys.retain(|_, last_seen| *last_seen != oldest);
+    }
+    seen.keys.insert(Box::new(key.clone()), tick);
+    true
+}
+
+/// Macro to log a message only the first time a key is seen, anywhere in the program.
+///
+/// This macro logs the message at the given level with the name of the function where it
+/// is invoked, unless `key` was already passed to this macro, at any call site of any of
+/// the `*_once_per!` macros. Keys of different types never match, and must implement
+/// `Hash + Eq + Clone + Send + 'static`.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_once_per;
+/// fn on_connect(client_id: u64) {
+///     log_once_per!(info, client_id, "new client {}", client_id);
+///     // Logs once per client: "new client 42 [fn on_connect]"
+/// }
+/// ```
+/// # Notes
+/// - Every distinct key is remembered for the rest of the program, so memory grows with the
+///   number of keys. For unbounded key spaces, cap it with
+///   [`set_once_per_capacity`](crate::set_once_per_capacity); the least recently seen keys
+///   are then forgotten and logged again if they come back.
+#[macro_export]
+macro_rules! log_once_per {
+    ($level:ident, $key:expr, $($arg:tt)+) => {
+        if $crate::__private_api::enabled($crate::__level!($level))
+            && $crate::__private_api::first_seen(&$key)
+        {
+            $crate::__log!($crate::__level!($level), $($arg)+);
+        }
+    };
+}
+
+/// Logs a trace message the first time a key is seen; see [`log_once_per!`](crate::log_once_per).
+#[macro_export]
+macro_rules! trace_once_per {
+    ($key:expr, $($arg:tt)+) => {
+        $crate::log_once_per!(trace, $key, $($arg)+)
+    };
+}
+
+/// Logs a debug message the first time a key is seen; see [`log_once_per!`](crate::log_once_per).
+#[macro_export]
+macro_rules! debug_once_per {
+    ($key:expr, $($arg:tt)+) => {
+        $crate::log_once_per!(debug, $key, $($arg)+)
+    };
+}
+
+/// Logs an info message the first time a key is seen; see [`log_once_per!`](crate::log_once_per).
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::info_once_per;
+/// fn on_connect(client_id: u64) {
+///     info_once_per!(client_id, "new client {}", client_id);
+///     // Logs once per client: "new client 42 [fn on_connect]"
+/// }
+/// ```
+#[macro_export]
+macro_rules! info_once_per {
+    ($key:expr, $($arg:tt)+) => {
+        $crate::log_once_per!(info, $key, $($arg)+)
+    };
+}
+
+/// Logs a warning the first time a key is seen; see [`log_once_per!`](crate::log_once_per).
+#[macro_export]
+macro_rules! warn_once_per {
+    ($key:expr, $($arg:tt)+) => {
+        $crate::log_once_per!(warn, $key, $($arg)+)
+    };
+}
+
+/// Logs an error the first time a key is seen; see [`log_once_per!`](crate::log_once_per).
+#[macro_export]
+macro_rules! error_once_per {
+    ($key:expr, $($arg:tt)+) => {
+        $crate::log_once_per!(error, $key, $($arg)+)
+    };
+}
@@ -0,0 +1,120 @@
+//! High-water marks of values tracked in a function.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// The call sites of [`track_peak!`](crate::track_peak) in each function that have been
+/// reached, keyed like samples by [`samples_key`](crate::samples::samples_key).
+static SITES: Mutex<Option<HashMap<&'static str, Vec<&'static PeakSite>>>> = Mutex::new(None);
+
+/// The highest value tracked at one call site of [`track_peak!`](crate::track_peak).
+#[doc(hidden)]
+pub struct PeakSite {
+    peak: AtomicU64,
+    registered: AtomicBool,
+}
+
+impl PeakSite {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            peak: AtomicU64::new(0),
+            registered: AtomicBool::new(false),
+        }
+    }
+
+    /// Raises the peak to `value` if it is higher, and registers the site under `key` the
+    /// first time.
+    pub fn track(&'static self, key: &'static str, value: u64) {
+        self.peak.fetch_max(value, Ordering::Relaxed);
+        // Only the first call takes the lock; later ones stop at this load.
+        if !self.registered.load(Ordering::Relaxed)
+            && !self.registered.swap(true, Ordering::Relaxed)
+        {
+            let mut sites = SITES.lock().unwrap_or_else(|e| e.into_inner());
+            sites
+                .get_or_insert_with(HashMap::new)
+                .entry(key)
+                .or_default()
+                .push(self);
+        }
+    }
+}
+
+/// Returns the highest value tracked in the function under `key`, or `None` if none was.
+#[doc(hidden)]
+pub fn peak_of(key: &'static str) -> Option<u64> {
+    let sites = SITES.lock().unwrap_or_else(|e| e.into_inner());
+    sites
+        .as_ref()?
+        .get(key)?
+        .iter()
+        .map(|site| site.peak.load(Ordering::Relaxed))
+        .max()
+}
+
+/// Macro to record a value towards the peak reached in the current function.
+///
+/// This macro raises the high-water mark kept for the function where it is invoked to the
+/// value, if it is higher, with a single atomic `fetch_max`, so it can be called on every
+/// change of the value. [`log_peak!`](crate::log_peak), called in the same function, logs
+/// the peak. It logs nothing itself.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{log_peak, track_peak};
+/// fn on_connect(open_connections: usize) {
+///     track_peak!(open_connections);
+/// }
+///
+/// fn report(open_connections: usize) {
+///     track_peak!(open_connections);
+///     log_peak!(info, "max connections");
+///     // Logs: "max connections peak=42 [fn report]"
+/// }
+/// # report(42);
+/// ```
+/// # Notes
+/// - The value can be any primitive integer, and is converted to `u64` with `as`, so it must
+///   not be negative.
+/// - Peaks are grouped by function, like [`sample!`](crate::sample): every `track_peak!` in a
+///   function, and in closures defined in it, feeds the same peak, which lasts for the
+///   lifetime of the process.
+#[macro_export]
+macro_rules! track_peak {
+    ($value:expr $(,)?) => {{
+        fn f() {}
+        static SITE: $crate::__private_api::PeakSite = $crate::__private_api::PeakSite::new();
+        SITE.track($crate::__private_api::samples_key(f), $value as u64);
+    }};
+}
+
+/// Macro to log the peak of the values tracked in the current function.
+///
+/// This macro logs `<message> peak=<n>` at the given level with the name of the function
+/// where it is invoked, where `<n>` is the highest value passed to
+/// [`track_peak!`](crate::track_peak) in the same function so far, or `<message> no values
+/// tracked` if there was none. The message is formatted like a log message.
+///
+/// # Example
+/// See [`track_peak!`](crate::track_peak).
+#[macro_export]
+macro_rules! log_peak {
+    ($level:ident, $($arg:tt)+) => {{
+        fn f() {}
+        match $crate::__private_api::peak_of($crate::__private_api::samples_key(f)) {
+            ::std::option::Option::Some(peak) => $crate::__log!(
+                $crate::__level!($level),
+                "{} peak={}",
+                format_args!($($arg)+),
+                peak
+            ),
+            ::std::option::Option::None => $crate::__log!(
+                $crate::__level!($level),
+                "{} no values tracked",
+                format_args!($($arg)+)
+            ),
+        }
+    }};
+}
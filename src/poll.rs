@@ -0,0 +1,91 @@
+//! Counting how often a future is polled.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use log::Level;
+
+use crate::__private_api::{self, Location};
+
+/// Macro to count how many times a future is polled and log the total once it completes.
+///
+/// This macro wraps the future in a [`LoggedPoll`](crate::LoggedPoll), which counts every
+/// poll. When the future resolves, it logs `<label> completed after <n> polls` at the given
+/// level with the name of the function where the macro is invoked. Awaiting it yields the
+/// future's output. A future that is polled far more often than it has reason to wake up
+/// usually has a missing `Waker` registration or is busy-looping.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_poll_count;
+/// async fn fetch_theme() -> u32 {
+///     log_poll_count!(trace, "fetch", async { 42 }).await
+///     // Logs: "fetch completed after 1 polls [fn fetch_theme]"
+/// }
+/// ```
+/// # Notes
+/// - Only plain `std` futures are involved, so this works under any async runtime. A future
+///   dropped before it completes logs nothing.
+#[macro_export]
+macro_rules! log_poll_count {
+    ($level:ident, $label:expr, $future:expr $(,)?) => {
+        $crate::LoggedPoll::__new(
+            $future,
+            $crate::__level!($level),
+            ::std::string::ToString::to_string(&$label),
+            $crate::function_name!(),
+            (module_path!(), module_path!(), file!(), line!()),
+        )
+    };
+}
+
+/// A future that logs how many times it was polled on completion, created by
+/// [`log_poll_count!`](crate::log_poll_count).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct LoggedPoll<F> {
+    future: Pin<Box<F>>,
+    level: Level,
+    label: String,
+    fn_name: String,
+    loc: Location<'static>,
+    polls: u64,
+}
+
+impl<F: Future> LoggedPoll<F> {
+    #[doc(hidden)]
+    pub fn __new(
+        future: F,
+        level: Level,
+        label: String,
+        fn_name: String,
+        loc: Location<'static>,
+    ) -> Self {
+        Self {
+            future: Box::pin(future),
+            level,
+            label,
+            fn_name,
+            loc,
+            polls: 0,
+        }
+    }
+}
+
+impl<F: Future> Future for LoggedPoll<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.polls += 1;
+        let poll = self.future.as_mut().poll(cx);
+        if poll.is_ready() && __private_api::enabled(self.level) {
+            __private_api::emit(
+                self.level,
+                format_args!("{} completed after {} polls", self.label, self.polls),
+                &self.fn_name,
+                &self.loc,
+            );
+        }
+        poll
+    }
+}
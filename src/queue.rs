@@ -0,0 +1,118 @@
+//! Queue and channel backlog logging.
+
+use std::collections::{BinaryHeap, LinkedList, VecDeque};
+
+/// Collections and channels that can report how many items they currently hold.
+///
+/// Used by [`log_queue!`](crate::log_queue) to log the backlog of a queue without caring
+/// about its concrete type.
+pub trait HasLen {
+    /// Returns the number of items currently held.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if no items are currently held.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: HasLen + ?Sized> HasLen for &T {
+    fn len(&self) -> usize {
+        T::len(self)
+    }
+}
+
+impl<T: HasLen + ?Sized> HasLen for &mut T {
+    fn len(&self) -> usize {
+        T::len(self)
+    }
+}
+
+impl<T> HasLen for [T] {
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+}
+
+impl<T> HasLen for Vec<T> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+}
+
+impl<T> HasLen for VecDeque<T> {
+    fn len(&self) -> usize {
+        VecDeque::len(self)
+    }
+}
+
+impl<T> HasLen for LinkedList<T> {
+    fn len(&self) -> usize {
+        LinkedList::len(self)
+    }
+}
+
+impl<T> HasLen for BinaryHeap<T> {
+    fn len(&self) -> usize {
+        BinaryHeap::len(self)
+    }
+}
+
+#[cfg(feature = "crossbeam-channel")]
+impl<T> HasLen for crossbeam_channel::Receiver<T> {
+    fn len(&self) -> usize {
+        crossbeam_channel::Receiver::len(self)
+    }
+}
+
+#[cfg(feature = "crossbeam-channel")]
+impl<T> HasLen for crossbeam_channel::Sender<T> {
+    fn len(&self) -> usize {
+        crossbeam_channel::Sender::len(self)
+    }
+}
+
+#[cfg(feature = "flume")]
+impl<T> HasLen for flume::Receiver<T> {
+    fn len(&self) -> usize {
+        flume::Receiver::len(self)
+    }
+}
+
+#[cfg(feature = "flume")]
+impl<T> HasLen for flume::Sender<T> {
+    fn len(&self) -> usize {
+        flume::Sender::len(self)
+    }
+}
+
+/// Macro to log the number of items waiting in a queue or channel.
+///
+/// This macro logs `<queue> len=N` at the given level, followed by the name of the
+/// function where it is invoked. It accepts a reference to anything implementing
+/// [`HasLen`](crate::HasLen), and labels the line with the source text of the argument.
+///
+/// # Example
+/// ```rust
+/// # use std::collections::VecDeque;
+/// # use tacky_borders_logger::log_queue;
+/// fn drain_jobs(jobs: &mut VecDeque<u32>) {
+///     log_queue!(debug, &jobs);
+///     // Logs: "jobs len=3 [fn drain_jobs]"
+///     jobs.clear();
+/// }
+/// ```
+/// # Notes
+/// - Implementations for `crossbeam-channel` and `flume` channels are available behind the
+///   features of the same name.
+#[macro_export]
+macro_rules! log_queue {
+    ($level:ident, $queue:expr $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{} len={}",
+            stringify!($queue).trim_start_matches('&').trim_start(),
+            $crate::HasLen::len($queue)
+        )
+    };
+}
@@ -0,0 +1,889 @@
+//! Macros that limit how often a call site logs.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasher, RandomState};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::{clock, config};
+
+/// Per-call-site timestamp of the last emitted line, used by the interval-limited macros.
+#[doc(hidden)]
+pub struct Interval {
+    /// Nanoseconds since [`epoch`], offset by [`BIAS`], or zero if the site never logged.
+    last: AtomicU64,
+}
+
+impl Interval {
+    pub const fn new() -> Self {
+        Self {
+            last: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns `Some` if at least `interval` has passed since the last time this returned
+    /// `Some`, holding the time since then, or `None` for the very first call.
+    pub fn ready(&self, interval: Duration) -> Option<Option<Duration>> {
+        let now = now_nanos();
+        let last = self.last.load(Ordering::Relaxed);
+        let since = (last != 0).then(|| Duration::from_nanos(now.saturating_sub(last)));
+        if since.is_some_and(|since| since < interval) {
+            return None;
+        }
+
+        self.last
+            .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+            .ok()
+            .map(|_| since)
+    }
+}
+
+impl Default for Interval {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An [`Interval`] whose length is randomized once per call site, within a fraction of the
+/// requested interval, so sites and processes limited to the same interval drift apart.
+#[doc(hidden)]
+pub struct JitteredInterval {
+    interval: Interval,
+    /// The randomized interval in nanoseconds, or zero until it is first picked.
+    jittered: AtomicU64,
+}
+
+impl JitteredInterval {
+    pub const fn new() -> Self {
+        Self {
+            interval: Interval::new(),
+            jittered: AtomicU64::new(0),
+        }
+    }
+
+    /// Like [`Interval::ready`], with an interval picked uniformly at random within
+    /// `±jitter * interval` the first time this is called, and kept from then on.
+    pub fn ready(&self, interval: Duration, jitter: f64) -> Option<Option<Duration>> {
+        let jittered = match self.jittered.load(Ordering::Relaxed) {
+            0 => {
+                // `RandomState` is seeded randomly per process; hashing the site's address
+                // also sets apart the sites within one.
+                let random = RandomState::new().hash_one(self as *const Self as usize);
+                let unit = (random >> 11) as f64 / (1u64 << 53) as f64;
+                let factor = 1.0 + jitter.clamp(0.0, 1.0) * (2.0 * unit - 1.0);
+                let nanos = ((interval.as_nanos() as f64 * factor) as u64).max(1);
+                self.jittered.store(nanos, Ordering::Relaxed);
+                nanos
+            }
+            nanos => nanos,
+        };
+        self.interval.ready(Duration::from_nanos(jittered))
+    }
+}
+
+impl Default for JitteredInterval {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Per-call-site token bucket, used by the burst-limited macros.
+///
+/// Rather than a separate token count and refill time, the bucket is kept as the time at
+/// which it will next be full, which makes taking a token a single atomic update.
+#[doc(hidden)]
+pub struct TokenBucket {
+    /// Nanoseconds since [`epoch`], offset by [`BIAS`], at which the bucket is full again, or
+    /// zero if it was never used.
+    full_at: AtomicU64,
+    /// Lines suppressed since the last one logged.
+    suppressed: AtomicU64,
+}
+
+impl TokenBucket {
+    pub const fn new() -> Self {
+        Self {
+            full_at: AtomicU64::new(0),
+            suppressed: AtomicU64::new(0),
+        }
+    }
+
+    /// Takes a token from a bucket holding up to `capacity` of them, refilled by one every
+    /// `refill`, and returns `Some` with the number of lines suppressed since the last token
+    /// was taken, or `None` if the bucket is empty.
+    pub fn take(&self, capacity: u32, refill: Duration) -> Option<u64> {
+        let refill = refill.as_nanos().min(u128::from(u64::MAX)) as u64;
+        let burst = refill.saturating_mul(u64::from(capacity));
+        let now = now_nanos();
+        let mut full_at = self.full_at.load(Ordering::Relaxed);
+        loop {
+            // The bucket is missing one token per `refill` until `full_at`.
+            let next = full_at.max(now).saturating_add(refill);
+            if next - now > burst {
+                self.suppressed.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+            match self.full_at.compare_exchange_weak(
+                full_at,
+                next,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return Some(self.suppressed.swap(0, Ordering::Relaxed)),
+                Err(current) => full_at = current,
+            }
+        }
+    }
+}
+
+impl Default for TokenBucket {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Per-call-site sampling probability, adjusted once per second to the call rate, used by
+/// [`log_adaptive!`](crate::log_adaptive).
+#[doc(hidden)]
+pub struct AdaptiveSampler {
+    /// When the current one-second window started, as from [`now_nanos`], or zero before the
+    /// first call.
+    window_start: AtomicU64,
+    /// The calls made in the current window.
+    calls: AtomicU64,
+    /// The probability of logging a call, as the bits of an `f64`.
+    probability: AtomicU64,
+}
+
+impl AdaptiveSampler {
+    const WINDOW: u64 = 1_000_000_000;
+
+    pub const fn new() -> Self {
+        Self {
+            window_start: AtomicU64::new(0),
+            calls: AtomicU64::new(0),
+            probability: AtomicU64::new(1.0f64.to_bits()),
+        }
+    }
+
+    /// Counts a call, and returns `Some` with the probability a line is logged with if this
+    /// one is, or `None` if it is dropped.
+    pub fn sample(&self) -> Option<f64> {
+        let now = now_nanos();
+        let start = self.window_start.load(Ordering::Relaxed);
+        if start == 0 {
+            let _ =
+                self.window_start
+                    .compare_exchange(0, now, Ordering::Relaxed, Ordering::Relaxed);
+        } else if now.saturating_sub(start) >= Self::WINDOW
+            && self
+                .window_start
+                .compare_exchange(start, now, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            // Only the thread that closed the window gets here, so the rate is computed once.
+            let calls = self.calls.swap(0, Ordering::Relaxed) as f64;
+            let per_second = calls * Self::WINDOW as f64 / (now - start) as f64;
+            let target = f64::from(config::adaptive_rate());
+            let probability = if per_second <= target {
+                1.0
+            } else {
+                target / per_second
+            };
+            self.probability
+                .store(probability.to_bits(), Ordering::Relaxed);
+        }
+        self.calls.fetch_add(1, Ordering::Relaxed);
+
+        let probability = f64::from_bits(self.probability.load(Ordering::Relaxed));
+        (probability >= 1.0 || random_unit() < probability).then_some(probability)
+    }
+}
+
+impl Default for AdaptiveSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns a pseudo-random number in `[0, 1)` from a per-thread xorshift generator, seeded
+/// randomly per thread.
+pub(crate) fn random_unit() -> f64 {
+    thread_local! {
+        static STATE: Cell<u64> = Cell::new(RandomState::new().hash_one(0u64) | 1);
+    }
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        state.set(x);
+        (x >> 11) as f64 / (1u64 << 53) as f64
+    })
+}
+
+/// Renders the probability of a sampled line as ` (sampled 1 in <n>)`, or nothing while every
+/// line is logged; for [`log_adaptive!`](crate::log_adaptive).
+#[doc(hidden)]
+pub struct SampledText(pub f64);
+
+impl fmt::Display for SampledText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 >= 1.0 {
+            return Ok(());
+        }
+        write!(f, " (sampled 1 in {})", (1.0 / self.0).round() as u64)
+    }
+}
+
+/// Per-call-site previous value of a counter and when it was read, used by
+/// [`log_rate!`](crate::log_rate).
+#[doc(hidden)]
+pub struct CounterRate {
+    last: Mutex<Option<(u64, Instant)>>,
+}
+
+impl CounterRate {
+    pub const fn new() -> Self {
+        Self {
+            last: Mutex::new(None),
+        }
+    }
+
+    /// Records `value` as the counter's latest reading and returns how it changed since the
+    /// previous one, or `None` for the very first reading.
+    pub fn advance(&self, value: u64) -> Option<RateText> {
+        let now = clock::now();
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        let previous = last.replace((value, now))?;
+        Some(RateText {
+            delta: i128::from(value) - i128::from(previous.0),
+            elapsed: now.saturating_duration_since(previous.1),
+        })
+    }
+}
+
+impl Default for CounterRate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Per-call-site last message and when it was logged, used by
+/// [`log_cooldown!`](crate::log_cooldown).
+#[doc(hidden)]
+pub struct Cooldown {
+    last: Mutex<Option<(String, Instant)>>,
+}
+
+impl Cooldown {
+    pub const fn new() -> Self {
+        Self {
+            last: Mutex::new(None),
+        }
+    }
+
+    /// Returns `true` if `message` should be logged now: it differs from the last message
+    /// logged at the site, or `interval` has passed since then. Logging restarts the cooldown.
+    pub fn ready(&self, message: &str, interval: Duration) -> bool {
+        let now = clock::now();
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        match &mut *last {
+            Some((last_message, at))
+                if last_message == message && now.saturating_duration_since(*at) < interval =>
+            {
+                false
+            }
+            Some((last_message, at)) => {
+                message.clone_into(last_message);
+                *at = now;
+                true
+            }
+            None => {
+                *last = Some((message.to_owned(), now));
+                true
+            }
+        }
+    }
+}
+
+impl Default for Cooldown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Renders how much a counter changed as `+150 (30.0/s)`, or `+150 (rate unknown)` if no
+/// time passed.
+#[doc(hidden)]
+pub struct RateText {
+    delta: i128,
+    elapsed: Duration,
+}
+
+impl fmt::Display for RateText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:+}", self.delta)?;
+        match self.elapsed.as_secs_f64() {
+            0.0 => f.write_str(" (rate unknown)"),
+            secs => write!(f, " ({:.1}/s)", self.delta as f64 / secs),
+        }
+    }
+}
+
+/// When each distinct message was last logged by the message-limited macros.
+static LAST_BY_MSG: Mutex<Option<HashMap<String, Instant>>> = Mutex::new(None);
+
+/// Returns `true` if `message` was never logged, or at least `interval` ago, and records it as
+/// logged now.
+///
+/// With a capacity set, the message logged longest ago is forgotten when a new one does not
+/// fit.
+#[doc(hidden)]
+pub fn message_ready(message: &str, interval: Duration) -> bool {
+    let now = clock::now();
+    let mut last_by_msg = LAST_BY_MSG.lock().unwrap_or_else(|e| e.into_inner());
+    let last_by_msg = last_by_msg.get_or_insert_with(HashMap::new);
+
+    if let Some(last) = last_by_msg.get_mut(message) {
+        if now.saturating_duration_since(*last) < interval {
+            return false;
+        }
+        *last = now;
+        return true;
+    }
+
+    // Only runs once the map is full, so a linear scan keeps hits cheap.
+    let capacity = config::every_by_msg_capacity().unwrap_or(usize::MAX).max(1);
+    while last_by_msg.len() >= capacity
+        && let Some(oldest) = last_by_msg
+            .iter()
+            .min_by_key(|(_, last)| **last)
+            .map(|(message, _)| message.clone())
+    {
+        last_by_msg.remove(&oldest);
+    }
+    last_by_msg.insert(message.to_owned(), now);
+    true
+}
+
+fn epoch() -> Instant {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    *EPOCH.get_or_init(clock::now)
+}
+
+/// Offsets times from [`epoch`], so times before it, which a fake clock can return, stay
+/// positive and distinct from zero; it leaves about 146 years on either side.
+const BIAS: u64 = 1 << 62;
+
+/// Returns the current time in nanoseconds since [`epoch`], offset by [`BIAS`].
+fn now_nanos() -> u64 {
+    let (now, epoch) = (clock::now(), epoch());
+    match now.checked_duration_since(epoch) {
+        Some(since) => BIAS.saturating_add(since.as_nanos() as u64),
+        None => BIAS.saturating_sub(epoch.duration_since(now).as_nanos() as u64),
+    }
+}
+
+/// Macro to log a liveness signal from a long-running loop at most once per interval.
+///
+/// Called on every iteration, this macro logs the message the first time it runs, and
+/// afterwards only once `interval` has passed since the previous heartbeat, noting how
+/// long ago that was. The name of the function where it is invoked is appended as usual.
+///
+/// # Example
+/// ```rust
+/// # use std::time::Duration;
+/// # use tacky_borders_logger::heartbeat;
+/// fn run_worker(jobs: &[u32]) {
+///     for _job in jobs {
+///         heartbeat!(info, Duration::from_secs(30), "worker alive");
+///         // Logs: "worker alive (previous heartbeat 30.0012s ago) [fn run_worker]"
+///     }
+/// }
+/// ```
+/// # Notes
+/// - Each call site keeps its own timestamp in a static atomic, so the check is cheap.
+#[macro_export]
+macro_rules! heartbeat {
+    ($level:ident, $interval:expr, $($arg:tt)+) => ({
+        static HEARTBEAT: $crate::__private_api::Interval = $crate::__private_api::Interval::new();
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) {
+            match HEARTBEAT.ready($interval) {
+                Some(Some(since)) => $crate::__log!(
+                    level,
+                    "{} (previous heartbeat {:?} ago)",
+                    format_args!($($arg)+),
+                    since
+                ),
+                Some(None) => $crate::__log!(level, $($arg)+),
+                None => {}
+            }
+        }
+    });
+}
+
+/// Macro to log a message at most once per interval, randomized per call site.
+///
+/// This macro logs the message the first time it runs, and afterwards only once the call
+/// site's interval has passed since the previous line, like [`heartbeat!`](crate::heartbeat)
+/// without the note. Each call site picks its interval at random, once, within `±jitter`
+/// times `interval`, where `jitter` is a fraction such as `0.2` for ±20%. Many processes,
+/// or call sites, limited to the same interval then spread their lines out instead of
+/// logging in synchronized bursts.
+///
+/// # Example
+/// ```rust
+/// # use std::time::Duration;
+/// # use tacky_borders_logger::log_every_jittered;
+/// fn poll_upstream(healthy: bool) {
+///     if !healthy {
+///         log_every_jittered!(warn, Duration::from_secs(10), 0.2, "upstream unhealthy");
+///         // Logs at most once every 8 to 12 seconds, by site:
+///         // "upstream unhealthy [fn poll_upstream]"
+///     }
+/// }
+/// ```
+/// # Notes
+/// - `jitter` is an `f64`, clamped to `0.0..=1.0`; `0.0` keeps the interval as given.
+/// - The interval of a call site is picked the first time it logs and kept afterwards, so
+///   changing `interval` or `jitter` later has no effect.
+#[macro_export]
+macro_rules! log_every_jittered {
+    ($level:ident, $interval:expr, $jitter:expr, $($arg:tt)+) => {{
+        static EVERY: $crate::__private_api::JitteredInterval =
+            $crate::__private_api::JitteredInterval::new();
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) && EVERY.ready($interval, $jitter).is_some() {
+            $crate::__log!(level, $($arg)+);
+        }
+    }};
+}
+
+/// Logs a warning at most once per randomized interval; see
+/// [`log_every_jittered!`](crate::log_every_jittered).
+#[macro_export]
+macro_rules! warn_every_jittered {
+    ($interval:expr, $jitter:expr, $($arg:tt)+) => {
+        $crate::log_every_jittered!(warn, $interval, $jitter, $($arg)+)
+    };
+}
+
+/// Macro to log bursts of messages up to a capacity, then at most at a steady rate.
+///
+/// Each call site has a token bucket holding up to `capacity` tokens, which starts full and
+/// regains one token every `refill`. Logging a message takes a token; when none is left,
+/// the message is suppressed. Short bursts of up to `capacity` lines thus go through
+/// untouched, while a sustained flood is held to one line per `refill`. The first line
+/// logged after some were suppressed notes how many, as `(<n> suppressed)`.
+///
+/// # Example
+/// ```rust
+/// # use std::time::Duration;
+/// # use tacky_borders_logger::log_bucket;
+/// fn on_packet_dropped(peer: u32) {
+///     log_bucket!(warn, capacity: 5, refill: Duration::from_secs(1), "dropped packet from {}", peer);
+///     // Logs the first 5 drops of a burst, then one per second:
+///     // "dropped packet from 7 (12 suppressed) [fn on_packet_dropped]"
+/// }
+/// ```
+/// # Notes
+/// - `capacity` is a `u32`; a capacity of 0 suppresses every message.
+/// - The bucket is shared by all threads logging from the call site.
+#[macro_export]
+macro_rules! log_bucket {
+    ($level:ident, capacity: $capacity:expr, refill: $refill:expr, $($arg:tt)+) => {{
+        static BUCKET: $crate::__private_api::TokenBucket = $crate::__private_api::TokenBucket::new();
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) {
+            match BUCKET.take($capacity, $refill) {
+                Some(0) => $crate::__log!(level, $($arg)+),
+                Some(suppressed) => $crate::__log!(
+                    level,
+                    "{} ({} suppressed)",
+                    format_args!($($arg)+),
+                    suppressed
+                ),
+                None => {}
+            }
+        }
+    }};
+}
+
+/// Logs a warning through a per-site token bucket; see [`log_bucket!`](crate::log_bucket).
+#[macro_export]
+macro_rules! warn_bucket {
+    (capacity: $capacity:expr, refill: $refill:expr, $($arg:tt)+) => {
+        $crate::log_bucket!(warn, capacity: $capacity, refill: $refill, $($arg)+)
+    };
+}
+
+/// Macro to log a message with a sampling rate that adapts to how often the call site is hit.
+///
+/// Each call site counts its calls over one-second windows. At the end of each window, it
+/// sets the probability of logging a call to what would have kept that window within the
+/// target rate, [`set_adaptive_rate`](crate::set_adaptive_rate) lines per second, 10 unless
+/// set otherwise: every call is logged while traffic is below the target, and a random
+/// fraction of them while it is above, so the volume stays near the target however busy the
+/// code gets. Lines logged while sampling end with `(sampled 1 in <n>)`, to scale counts
+/// taken from the log back up.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_adaptive;
+/// fn on_event(id: u64) {
+///     log_adaptive!(debug, "handling event {}", id);
+///     // Logs every event at low traffic, and at 1,000 events a second about 10 a second:
+///     // "handling event 4242 (sampled 1 in 100) [fn on_event]"
+/// }
+/// ```
+/// # Notes
+/// - The probability is based on the previous window, so a burst is logged in full for up to
+///   a second before sampling catches up, and sampling continues for up to a second after
+///   traffic drops.
+/// - The counters are atomics shared by all threads logging from the call site, and calls
+///   are counted even while the level is disabled.
+#[macro_export]
+macro_rules! log_adaptive {
+    ($level:ident, $($arg:tt)+) => {{
+        static SAMPLER: $crate::__private_api::AdaptiveSampler =
+            $crate::__private_api::AdaptiveSampler::new();
+        if let ::std::option::Option::Some(probability) = SAMPLER.sample() {
+            $crate::__log!(
+                $crate::__level!($level),
+                "{}{}",
+                format_args!($($arg)+),
+                $crate::__private_api::SampledText(probability)
+            );
+        }
+    }};
+}
+
+/// Logs a trace message sampled to a target rate; see [`log_adaptive!`](crate::log_adaptive).
+#[macro_export]
+macro_rules! trace_adaptive {
+    ($($arg:tt)+) => {
+        $crate::log_adaptive!(trace, $($arg)+)
+    };
+}
+
+/// Logs a debug message sampled to a target rate; see [`log_adaptive!`](crate::log_adaptive).
+#[macro_export]
+macro_rules! debug_adaptive {
+    ($($arg:tt)+) => {
+        $crate::log_adaptive!(debug, $($arg)+)
+    };
+}
+
+/// Logs an info message sampled to a target rate; see [`log_adaptive!`](crate::log_adaptive).
+#[macro_export]
+macro_rules! info_adaptive {
+    ($($arg:tt)+) => {
+        $crate::log_adaptive!(info, $($arg)+)
+    };
+}
+
+/// Logs a warning sampled to a target rate; see [`log_adaptive!`](crate::log_adaptive).
+#[macro_export]
+macro_rules! warn_adaptive {
+    ($($arg:tt)+) => {
+        $crate::log_adaptive!(warn, $($arg)+)
+    };
+}
+
+/// Logs an error sampled to a target rate; see [`log_adaptive!`](crate::log_adaptive).
+#[macro_export]
+macro_rules! error_adaptive {
+    ($($arg:tt)+) => {
+        $crate::log_adaptive!(error, $($arg)+)
+    };
+}
+
+/// Macro to log how much a counter advanced since the previous call, and at what rate.
+///
+/// This macro keeps the counter's previous value and the time it was read at each call
+/// site, and logs `<message>: +<delta> (<rate>/s)` at the given level with the name of the
+/// function where it is invoked, where the rate is the delta divided by the time since the
+/// previous call. The first call has nothing to compare with, so it logs
+/// `<message>: <value>` instead. `value` may be any unsigned integer up to `u64`; the
+/// message is formatted like a log message.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_rate;
+/// fn report(messages_received: u64) {
+///     log_rate!(info, messages_received, "messages");
+///     // Logs, for 1000 then 1150 five seconds later:
+///     // "messages: 1000 [fn report]", then "messages: +150 (30.0/s) [fn report]"
+/// }
+/// ```
+/// # Notes
+/// - A counter that went down, e.g. because it was reset, logs a negative delta and rate.
+/// - The previous value is recorded even if the level is disabled, so the next logged line
+///   always covers the time since the previous call.
+#[macro_export]
+macro_rules! log_rate {
+    ($level:ident, $value:expr, $($arg:tt)+) => {{
+        static RATE: $crate::__private_api::CounterRate = $crate::__private_api::CounterRate::new();
+        let value = ::std::primitive::u64::from($value);
+        match RATE.advance(value) {
+            ::std::option::Option::Some(rate) => $crate::__log!(
+                $crate::__level!($level),
+                "{}: {}",
+                format_args!($($arg)+),
+                rate
+            ),
+            ::std::option::Option::None => $crate::__log!(
+                $crate::__level!($level),
+                "{}: {}",
+                format_args!($($arg)+),
+                value
+            ),
+        }
+    }};
+}
+
+/// Macro to log a message at most once per interval across all call sites that log the
+/// same text.
+///
+/// Unlike [`heartbeat!`](crate::heartbeat) and the other interval-limited macros, which limit
+/// each call site on its own, this macro limits by the formatted message: two call sites
+/// that log the same text share one interval, so identical messages arising from different
+/// code paths are logged once per interval between them. The first occurrence of a message
+/// is always logged. The name of the function where it is invoked is appended as usual, but
+/// is not part of the comparison.
+///
+/// # Example
+/// ```rust
+/// # use std::time::Duration;
+/// # use tacky_borders_logger::warn_every_by_msg;
+/// fn read_sensor(id: u32) {
+///     warn_every_by_msg!(Duration::from_secs(60), "sensor {} not responding", id);
+///     // Logs "sensor 3 not responding [fn read_sensor]" at most once a minute, also
+///     // counting the same message from any other call site.
+/// }
+/// ```
+/// # Notes
+/// - The message is formatted before it is checked, even when it turns out to be
+///   suppressed, so this costs more than a per-site limit.
+/// - Every distinct message is remembered for the rest of the program, so memory grows with
+///   the number of messages, which is unbounded if they contain ids, timestamps or other
+///   varying data. For those, cap it with
+///   [`set_every_by_msg_capacity`](crate::set_every_by_msg_capacity); the message logged
+///   longest ago is then forgotten, and logged again if it comes back.
+#[macro_export]
+macro_rules! log_every_by_msg {
+    ($level:ident, $interval:expr, $($arg:tt)+) => {{
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) {
+            let message = ::std::format!($($arg)+);
+            if $crate::__private_api::message_ready(&message, $interval) {
+                $crate::__log!(level, "{}", message);
+            }
+        }
+    }};
+}
+
+/// Logs a warning at most once per interval across all call sites logging the same text;
+/// see [`log_every_by_msg!`](crate::log_every_by_msg).
+#[macro_export]
+macro_rules! warn_every_by_msg {
+    ($interval:expr, $($arg:tt)+) => {
+        $crate::log_every_by_msg!(warn, $interval, $($arg)+)
+    };
+}
+
+/// Macro to log a message at most once per cooldown, unless the message changes.
+///
+/// This macro formats the message and compares it with the last one logged at the call
+/// site. An identical message is suppressed until `interval` has passed since it was last
+/// logged; a different one is logged right away and restarts the cooldown. The name of the
+/// function where it is invoked is appended as usual, but is not part of the comparison.
+/// Changes are therefore reported promptly, while a steady condition is repeated only once
+/// per interval.
+///
+/// # Example
+/// ```rust
+/// # use std::time::Duration;
+/// # use tacky_borders_logger::warn_cooldown;
+/// fn check_battery(percent: u8) {
+///     warn_cooldown!(Duration::from_secs(60), "battery low: {}%", percent);
+///     // Logs "battery low: 15% [fn check_battery]" once a minute while it stays at 15%,
+///     // and "battery low: 14% [fn check_battery]" as soon as it drops.
+/// }
+/// ```
+/// # Notes
+/// - The message is formatted before it is checked, even when it turns out to be
+///   suppressed. Only the last message is remembered, so a site alternating between two
+///   messages logs every one of them.
+#[macro_export]
+macro_rules! log_cooldown {
+    ($level:ident, $interval:expr, $($arg:tt)+) => {{
+        static COOLDOWN: $crate::__private_api::Cooldown = $crate::__private_api::Cooldown::new();
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) {
+            let message = ::std::format!($($arg)+);
+            if COOLDOWN.ready(&message, $interval) {
+                $crate::__log!(level, "{}", message);
+            }
+        }
+    }};
+}
+
+/// Logs a warning at most once per cooldown unless it changes; see
+/// [`log_cooldown!`](crate::log_cooldown).
+#[macro_export]
+macro_rules! warn_cooldown {
+    ($interval:expr, $($arg:tt)+) => {
+        $crate::log_cooldown!(warn, $interval, $($arg)+)
+    };
+}
+
+/// Returns `true` if the `count`th occurrence at a call site should be logged by the
+/// count-limited macros: every `n`th one, and the first if `first` is set.
+#[doc(hidden)]
+pub fn nth_due(count: u64, n: u64, first: bool) -> bool {
+    count.is_multiple_of(n.max(1)) || (first && count == 1)
+}
+
+/// Macro to log only every `n`th time a call site runs.
+///
+/// This macro counts how many times it has run at each call site, across all threads, and
+/// logs the message on the `n`th, `2n`th, `3n`th, ... run, followed by the count as `(#<count>)`
+/// and the name of the function where it is invoked. With `first` after `n`, the very first
+/// run is logged too, so the loop shows up in the log right away. It is the count-based
+/// counterpart of the interval-limited macros such as [`heartbeat!`](crate::heartbeat), for
+/// loops whose speed makes a time window the wrong measure.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_every_n;
+/// fn process(items: &[u32]) {
+///     for (i, item) in items.iter().enumerate() {
+///         log_every_n!(debug, 1000, "processing item {} ({})", i, item);
+///         // Logs on the 1000th, 2000th, ... iteration:
+///         // "processing item 999 (42) (#1000) [fn process]"
+///         log_every_n!(debug, 1000, first, "still going");
+///         // Logs on the 1st, 1000th, 2000th, ... iteration.
+///     }
+/// }
+/// ```
+/// # Notes
+/// - Runs are counted even while the level is disabled, so the count stays accurate.
+/// - An `n` of 0 is treated as 1, logging every run.
+#[macro_export]
+macro_rules! log_every_n {
+    (@ $level:ident, $n:expr, $first:expr, $($arg:tt)+) => {{
+        static COUNT: ::std::sync::atomic::AtomicU64 = ::std::sync::atomic::AtomicU64::new(0);
+        let count = COUNT.fetch_add(1, ::std::sync::atomic::Ordering::Relaxed) + 1;
+        if $crate::__private_api::nth_due(count, $n as u64, $first) {
+            $crate::__log!(
+                $crate::__level!($level),
+                "{} (#{})",
+                format_args!($($arg)+),
+                count
+            );
+        }
+    }};
+    ($level:ident, $n:expr, first, $($arg:tt)+) => {
+        $crate::log_every_n!(@ $level, $n, true, $($arg)+)
+    };
+    ($level:ident, $n:expr, $($arg:tt)+) => {
+        $crate::log_every_n!(@ $level, $n, false, $($arg)+)
+    };
+}
+
+/// Logs a trace message every `n`th time the call site runs; see [`log_every_n!`](crate::log_every_n).
+#[macro_export]
+macro_rules! trace_every_n {
+    ($($arg:tt)+) => {
+        $crate::log_every_n!(trace, $($arg)+)
+    };
+}
+
+/// Logs a debug message every `n`th time the call site runs; see [`log_every_n!`](crate::log_every_n).
+#[macro_export]
+macro_rules! debug_every_n {
+    ($($arg:tt)+) => {
+        $crate::log_every_n!(debug, $($arg)+)
+    };
+}
+
+/// Logs an info message every `n`th time the call site runs; see [`log_every_n!`](crate::log_every_n).
+#[macro_export]
+macro_rules! info_every_n {
+    ($($arg:tt)+) => {
+        $crate::log_every_n!(info, $($arg)+)
+    };
+}
+
+/// Logs a warning every `n`th time the call site runs; see [`log_every_n!`](crate::log_every_n).
+#[macro_export]
+macro_rules! warn_every_n {
+    ($($arg:tt)+) => {
+        $crate::log_every_n!(warn, $($arg)+)
+    };
+}
+
+/// Logs an error every `n`th time the call site runs; see [`log_every_n!`](crate::log_every_n).
+#[macro_export]
+macro_rules! error_every_n {
+    ($($arg:tt)+) => {
+        $crate::log_every_n!(error, $($arg)+)
+    };
+}
+
+/// Returns `current` as a whole percentage of `total`, or 100 when `total` is zero, as there is
+/// then nothing left to do.
+#[doc(hidden)]
+pub fn percent(current: u64, total: u64) -> u64 {
+    match total {
+        0 => 100,
+        total => (u128::from(current) * 100 / u128::from(total)) as u64,
+    }
+}
+
+/// Macro to log the progress of a long-running loop at most once per interval.
+///
+/// This macro logs `progress: <percent>% (<current>/<total>)` at the given level with
+/// the name of the function where it is invoked. The first call logs, and later calls
+/// only log once at least `interval` has passed since the previous line, so it can be
+/// called on every iteration. A `total` of zero counts as 100% done. `current` and
+/// `total` may be any integers.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_progress;
+/// use std::time::Duration;
+///
+/// fn reindex(items: &[u32]) {
+///     for (i, _item) in items.iter().enumerate() {
+///         // ... process the item ...
+///         log_progress!(info, i + 1, items.len(), Duration::from_secs(5));
+///         // Logs every 5s: "progress: 42% (4200/10000) [fn reindex]"
+///     }
+/// }
+/// ```
+/// # Notes
+/// - The interval is tracked per call site, across all threads, like [`heartbeat!`](crate::heartbeat).
+#[macro_export]
+macro_rules! log_progress {
+    ($level:ident, $current:expr, $total:expr, $interval:expr $(,)?) => {{
+        static PROGRESS: $crate::__private_api::Interval = $crate::__private_api::Interval::new();
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) && PROGRESS.ready($interval).is_some() {
+            let (current, total) = ($current as u64, $total as u64);
+            $crate::__log!(
+                level,
+                "progress: {}% ({}/{})",
+                $crate::__private_api::percent(current, total),
+                current,
+                total
+            );
+        }
+    }};
+}
@@ -0,0 +1,77 @@
+//! Guards against runaway recursion.
+
+use std::cell::Cell;
+use std::thread::LocalKey;
+
+/// Keeps the recursion depth of one call site raised while alive; created by
+/// [`recursion_guard!`](crate::recursion_guard).
+#[doc(hidden)]
+#[must_use]
+pub struct RecursionGuard {
+    depth: &'static LocalKey<Cell<usize>>,
+}
+
+impl RecursionGuard {
+    /// Enters one level of recursion, returning the guard and the depth including this call.
+    #[doc(hidden)]
+    pub fn __enter(depth: &'static LocalKey<Cell<usize>>) -> (Self, usize) {
+        let current = depth.with(|depth| {
+            depth.set(depth.get() + 1);
+            depth.get()
+        });
+        (Self { depth }, current)
+    }
+}
+
+impl Drop for RecursionGuard {
+    fn drop(&mut self) {
+        self.depth.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// Macro to guard a recursive function against running too deep.
+///
+/// Invoke it at the top of the function. Each call raises a per-thread depth counter for
+/// this call site until the function returns. When the depth first goes past `max`, the
+/// macro logs `recursion depth <depth> exceeds limit <max>` at error level with the name of
+/// the function, and with `abort` it then aborts the process instead of letting the stack
+/// overflow.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::recursion_guard;
+/// fn walk(depth: u32) {
+///     recursion_guard!(max: 1000);
+///     if depth > 0 {
+///         walk(depth - 1);
+///     }
+///     // Logs, once the depth passes 1000: "recursion depth 1001 exceeds limit 1000 [fn walk]"
+/// }
+/// # walk(10);
+/// ```
+/// # Notes
+/// - Only the crossing is logged, not every deeper call, so the log stays readable.
+/// - The counter is per call site and per thread; mutual recursion needs a guard in each
+///   function.
+#[macro_export]
+macro_rules! recursion_guard {
+    (max: $max:expr $(, $abort:ident)? $(,)?) => {
+        ::std::thread_local! {
+            static DEPTH: ::std::cell::Cell<usize> = const { ::std::cell::Cell::new(0) };
+        }
+        let (_recursion_guard, depth) = $crate::__private_api::RecursionGuard::__enter(&DEPTH);
+        let max: usize = $max;
+        if depth == max + 1 {
+            $crate::__log!(
+                $crate::__private_api::log::Level::Error,
+                "recursion depth {} exceeds limit {}",
+                depth,
+                max
+            );
+            $($crate::recursion_guard!(@ $abort);)?
+        }
+    };
+    (@ abort) => {
+        ::std::process::abort()
+    };
+}
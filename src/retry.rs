@@ -0,0 +1,152 @@
+//! Macros for making retry policies visible in the logs.
+
+use std::fmt;
+use std::time::Duration;
+
+/// Renders the delays before each of `attempts` attempts, the first immediate and each later
+/// one `factor` times the previous, starting at `base`, as `[0ms, 100ms, 200ms] (total
+/// 300ms)`; for [`log_backoff_plan!`](crate::log_backoff_plan).
+#[doc(hidden)]
+pub struct BackoffPlan {
+    pub attempts: u32,
+    pub base: Duration,
+    pub factor: f64,
+}
+
+impl BackoffPlan {
+    fn delays(&self) -> impl Iterator<Item = Duration> + '_ {
+        (0..self.attempts).map(|attempt| match attempt {
+            0 => Duration::ZERO,
+            n => {
+                let secs = self.base.as_secs_f64() * self.factor.powi(n as i32 - 1);
+                Duration::try_from_secs_f64(secs).unwrap_or(Duration::MAX)
+            }
+        })
+    }
+}
+
+impl fmt::Display for BackoffPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        let mut total = Duration::ZERO;
+        for (i, delay) in self.delays().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", Delay(delay))?;
+            total = total.saturating_add(delay);
+        }
+        write!(f, "] (total {})", Delay(total))
+    }
+}
+
+/// Renders a delay with `Duration`'s `Debug` form, or `0ms` for zero.
+struct Delay(Duration);
+
+impl fmt::Display for Delay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Duration::ZERO => f.write_str("0ms"),
+            delay => write!(f, "{delay:?}"),
+        }
+    }
+}
+
+/// Macro to log the delays a retry loop with exponential backoff will wait.
+///
+/// This macro logs `backoff plan: [<delays>] (total <sum>)` with the name of the function
+/// where it is invoked, listing the delay before each of `attempts` attempts: none before
+/// the first, `base` before the second, and `factor` times the previous delay before each
+/// one after that. Logging it before the loop documents the effective policy at runtime.
+///
+/// # Example
+/// ```rust
+/// # use std::time::Duration;
+/// # use tacky_borders_logger::log_backoff_plan;
+/// fn connect() {
+///     log_backoff_plan!(info, 4, base: Duration::from_millis(100), factor: 2.0);
+///     // Logs: "backoff plan: [0ms, 100ms, 200ms, 400ms] (total 700ms) [fn connect]"
+/// }
+/// ```
+/// # Notes
+/// - `attempts` is a `u32` counting the first attempt; `factor` is an `f64`.
+/// - Delays too long for a `Duration` are shown as its maximum.
+#[macro_export]
+macro_rules! log_backoff_plan {
+    ($level:ident, $attempts:expr, base: $base:expr, factor: $factor:expr $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "backoff plan: {}",
+            $crate::__private_api::BackoffPlan {
+                attempts: $attempts,
+                base: $base,
+                factor: $factor,
+            }
+        )
+    };
+}
+
+/// Macro to run a fallible block up to a number of times, tagging each attempt's log lines
+/// with its attempt number.
+///
+/// This macro evaluates the block, which must return a `Result` whose error implements
+/// `Display`, inside an [`attempt_scope!`](crate::attempt_scope), so every record logged
+/// while it runs is tagged with `[attempt=<n>]`, counting from 1. When an attempt fails and
+/// attempts remain, `attempt failed, retrying: <error>` is logged at the given level and the
+/// block runs again; when the last attempt fails, `giving up after <n> attempts: <error>` is
+/// logged at error level. Both lines carry the attempt's tag and the name of the function
+/// where the macro is invoked. The macro evaluates to the first `Ok`, or to the last `Err`.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{debug, log_retry};
+/// fn connect(ports: &mut impl Iterator<Item = u16>) -> Result<u16, String> {
+///     log_retry!(warn, 3, {
+///         let port = ports.next().unwrap();
+///         debug!("trying port {}", port);
+///         // Logs: "trying port 8080 [attempt=1] [fn connect]"
+///         if port == 8080 { Err(format!("port {port} refused")) } else { Ok(port) }
+///         // Logs: "attempt failed, retrying: port 8080 refused [attempt=1] [fn connect]"
+///     })
+///     // Logs: "trying port 8081 [attempt=2] [fn connect]"
+/// }
+/// # assert_eq!(connect(&mut [8080, 8081].into_iter()), Ok(8081));
+/// ```
+/// # Notes
+/// - `attempts` is a `u32` counting the first attempt; the block runs at least once.
+/// - The block runs again immediately; sleep inside it to back off between attempts.
+/// - A `break` or `continue` in the block applies to the macro's own loop, not an enclosing
+///   one.
+#[macro_export]
+macro_rules! log_retry {
+    ($level:ident, $attempts:expr, $body:block) => {{
+        let attempts: u32 = $attempts;
+        let mut attempt: u32 = 1;
+        loop {
+            let result = $crate::attempt_scope!(attempt, {
+                let result = $body;
+                if let ::std::result::Result::Err(err) = &result {
+                    if attempt < attempts {
+                        $crate::__log!(
+                            $crate::__level!($level),
+                            "attempt failed, retrying: {}",
+                            err
+                        );
+                    } else {
+                        $crate::__log!(
+                            $crate::__private_api::log::Level::Error,
+                            "giving up after {} attempts: {}",
+                            attempt,
+                            err
+                        );
+                    }
+                }
+                result
+            });
+            if result.is_ok() || attempt >= attempts {
+                break result;
+            }
+            attempt += 1;
+        }
+    }};
+}
@@ -0,0 +1,167 @@
+//! Periodic minimum, maximum and average of values sampled in a function.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::clock;
+
+/// The samples of each function, keyed by the type name of a marker function defined in it.
+static SAMPLES: Mutex<Option<HashMap<&'static str, Samples>>> = Mutex::new(None);
+
+/// The values sampled in one function since its last report.
+struct Samples {
+    min: f64,
+    max: f64,
+    sum: f64,
+    count: u64,
+    window_start: Instant,
+}
+
+impl Samples {
+    fn new(window_start: Instant) -> Self {
+        Self {
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            sum: 0.0,
+            count: 0,
+            window_start,
+        }
+    }
+}
+
+/// Returns the key shared by [`sample!`](crate::sample) and
+/// [`log_samples_if_due!`](crate::log_samples_if_due), or [`track_peak!`](crate::track_peak)
+/// and [`log_peak!`](crate::log_peak), in the function defining `marker`.
+///
+/// Items declared in a function's blocks are all named after the function, so every marker
+/// defined in the same function has the same type name.
+#[doc(hidden)]
+pub fn samples_key<F>(_marker: F) -> &'static str {
+    std::any::type_name::<F>()
+}
+
+/// Adds `value` to the samples under `key`.
+#[doc(hidden)]
+pub fn add_sample(key: &'static str, value: f64) {
+    let now = clock::now();
+    let mut samples = SAMPLES.lock().unwrap_or_else(|e| e.into_inner());
+    let samples = samples
+        .get_or_insert_with(HashMap::new)
+        .entry(key)
+        .or_insert_with(|| Samples::new(now));
+    samples.min = samples.min.min(value);
+    samples.max = samples.max.max(value);
+    samples.sum += value;
+    samples.count += 1;
+}
+
+/// Returns the statistics of the samples under `key` and starts a new window if `interval`
+/// has elapsed since the current one started.
+#[doc(hidden)]
+pub fn take_due_samples(key: &'static str, interval: Duration) -> Option<SampleStats> {
+    let now = clock::now();
+    let mut samples = SAMPLES.lock().unwrap_or_else(|e| e.into_inner());
+    let samples = samples
+        .get_or_insert_with(HashMap::new)
+        .entry(key)
+        .or_insert_with(|| Samples::new(now));
+    if now.saturating_duration_since(samples.window_start) < interval {
+        return None;
+    }
+    let stats = SampleStats {
+        min: samples.min,
+        max: samples.max,
+        avg: samples.sum / samples.count as f64,
+        count: samples.count,
+    };
+    *samples = Samples::new(now);
+    Some(stats)
+}
+
+/// Renders the statistics of a window as `min=2 max=40 avg=11.25 (8 samples)`, or
+/// `no samples`; for [`log_samples_if_due!`](crate::log_samples_if_due).
+#[doc(hidden)]
+pub struct SampleStats {
+    min: f64,
+    max: f64,
+    avg: f64,
+    count: u64,
+}
+
+impl fmt::Display for SampleStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.count == 0 {
+            return f.write_str("no samples");
+        }
+        write!(f, "min={} max={} avg=", self.min, self.max)?;
+        if self.avg.fract() == 0.0 {
+            write!(f, "{}", self.avg)?;
+        } else {
+            write!(f, "{:.2}", self.avg)?;
+        }
+        write!(f, " ({} samples)", self.count)
+    }
+}
+
+/// Macro to record a value for the periodic statistics of the current function.
+///
+/// This macro adds a numeric value to the samples collected in the function where it is
+/// invoked, which [`log_samples_if_due!`](crate::log_samples_if_due), called in the same
+/// function, reports and resets once per interval. It logs nothing itself.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{log_samples_if_due, sample};
+/// use std::time::Duration;
+///
+/// fn handle_frame(latency_ms: u32) {
+///     sample!(latency_ms);
+///     log_samples_if_due!(info, Duration::from_secs(10), "latency");
+///     // Every 10 seconds, logs e.g.
+///     // "latency min=2 max=40 avg=11.25 (812 samples) [fn handle_frame]"
+/// }
+/// # handle_frame(12);
+/// ```
+/// # Notes
+/// - The value can be any primitive number; it is converted to `f64` with `as`.
+/// - Each sample takes a lock, which is cheap next to formatting a log line, but not free.
+/// - Samples are grouped by function, so every `sample!` in a function feeds the same
+///   statistics, and closures defined in it share them too.
+#[macro_export]
+macro_rules! sample {
+    ($value:expr $(,)?) => {{
+        fn f() {}
+        $crate::__private_api::add_sample($crate::__private_api::samples_key(f), $value as f64);
+    }};
+}
+
+/// Macro to log the minimum, maximum and average of the values sampled in the current
+/// function, once per interval.
+///
+/// This macro is meant to be called on every iteration of the code that calls
+/// [`sample!`](crate::sample). It does nothing until `interval` has passed since the window
+/// started, at the first sample or check; then it logs
+/// `<message> min=<min> max=<max> avg=<avg> (<n> samples)`, or `<message> no samples`, at the
+/// given level with the name of the function where it is invoked, and starts a new window.
+/// The message is formatted like a log message.
+///
+/// # Example
+/// See [`sample!`](crate::sample).
+///
+/// # Notes
+/// - The window restarts when the report is due even if the level is disabled, so the
+///   statistics always cover a single interval.
+#[macro_export]
+macro_rules! log_samples_if_due {
+    ($level:ident, $interval:expr, $($arg:tt)+) => {{
+        fn f() {}
+        if let ::std::option::Option::Some(stats) = $crate::__private_api::take_due_samples(
+            $crate::__private_api::samples_key(f),
+            $interval,
+        ) {
+            $crate::__log!($crate::__level!($level), "{} {}", format_args!($($arg)+), stats);
+        }
+    }};
+}
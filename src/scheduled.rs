@@ -0,0 +1,101 @@
+//! Logging of a message after a delay, unless canceled first.
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use log::Level;
+
+use crate::__private_api::{Location, emit, enabled};
+
+/// Guard for a message scheduled with [`log_after!`](crate::log_after).
+///
+/// The message is logged once the delay has passed, unless the guard is dropped, or
+/// [`cancel`](ScheduledLog::cancel)ed, before then. Keep it alive for as long as the message
+/// should stay scheduled, typically until the operation it watches has finished.
+#[must_use = "the scheduled message is canceled as soon as the guard is dropped"]
+pub struct ScheduledLog {
+    canceled: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl ScheduledLog {
+    #[doc(hidden)]
+    pub fn __schedule(
+        delay: Duration,
+        level: Level,
+        message: String,
+        fn_name: String,
+        loc: Location<'static>,
+    ) -> Self {
+        let canceled = Arc::new((Mutex::new(false), Condvar::new()));
+        let timer = Arc::clone(&canceled);
+        let spawned = thread::Builder::new()
+            .name("log-after".to_owned())
+            .spawn(move || {
+                let (canceled, changed) = &*timer;
+                let canceled = canceled.lock().unwrap_or_else(|e| e.into_inner());
+                let (canceled, _) = changed
+                    .wait_timeout_while(canceled, delay, |canceled| !*canceled)
+                    .unwrap_or_else(|e| e.into_inner());
+                if !*canceled && enabled(level) {
+                    emit(level, format_args!("{message}"), &fn_name, &loc);
+                }
+            });
+        // Without a thread there is no way to wait, so the message is dropped rather than
+        // logged early.
+        drop(spawned);
+        Self { canceled }
+    }
+
+    /// Cancels the scheduled message, like dropping the guard.
+    pub fn cancel(self) {}
+}
+
+impl Drop for ScheduledLog {
+    fn drop(&mut self) {
+        let (canceled, changed) = &*self.canceled;
+        *canceled.lock().unwrap_or_else(|e| e.into_inner()) = true;
+        changed.notify_one();
+    }
+}
+
+/// Macro to log a message after a delay, unless it is canceled first.
+///
+/// This macro formats the message right away, with the name of the function where it is
+/// invoked, and returns a [`ScheduledLog`](crate::ScheduledLog) guard. A timer thread logs
+/// the message at the given level once `delay` has passed, unless the guard has been dropped
+/// or canceled by then. It turns "warn if this hasn't finished by now" into a single line,
+/// without blocking the caller.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_after;
+/// use std::time::Duration;
+///
+/// fn sync_borders() {
+///     let _watchdog = log_after!(Duration::from_secs(5), warn, "still syncing after 5s");
+///     // ... sync ...
+///     // If this takes longer than 5s, logs: "still syncing after 5s [fn sync_borders]"
+///     // Returning drops `_watchdog`, which cancels the message.
+/// }
+/// # sync_borders();
+/// ```
+/// # Notes
+/// - Bind the guard to a named variable such as `_watchdog`; `let _ = log_after!(...)` drops
+///   it, and cancels the message, immediately.
+/// - Each call starts its own short-lived thread, so this suits occasional watchdogs rather
+///   than hot loops.
+/// - The message is logged from the timer thread, so thread-local state such as context
+///   tags and span depth is that thread's, not the caller's.
+#[macro_export]
+macro_rules! log_after {
+    ($delay:expr, $level:ident, $($arg:tt)+) => {
+        $crate::ScheduledLog::__schedule(
+            $delay,
+            $crate::__level!($level),
+            ::std::format!($($arg)+),
+            $crate::function_name!(),
+            (module_path!(), module_path!(), file!(), line!()),
+        )
+    };
+}
@@ -0,0 +1,135 @@
+//! Detecting likely secrets in messages, for [`set_secret_detection`](crate::set_secret_detection).
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+/// Prefixes that well-known services put in front of their API keys and access tokens.
+const KEY_PREFIXES: &[&str] = &[
+    "sk-",
+    "sk_live_",
+    "sk_test_",
+    "rk_live_",
+    "ghp_",
+    "gho_",
+    "ghu_",
+    "ghs_",
+    "github_pat_",
+    "glpat-",
+    "xoxb-",
+    "xoxp-",
+    "AKIA",
+    "AIza",
+];
+
+/// The call sites that have already been warned about, as `(file, line)`.
+static WARNED: Mutex<Option<HashSet<(&'static str, u32)>>> = Mutex::new(None);
+
+/// Returns whether a redacted secret at this call site has not been warned about yet.
+pub(crate) fn first_at(file: &'static str, line: u32) -> bool {
+    let mut warned = WARNED.lock().unwrap_or_else(|e| e.into_inner());
+    warned.get_or_insert_with(HashSet::new).insert((file, line))
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+' | '/')
+}
+
+/// Returns the message with every token that looks like a secret replaced by `***`, or
+/// `None` if there is none.
+pub(crate) fn redact(message: &str) -> Option<String> {
+    let mut redacted = String::new();
+    // The end of the text already copied to `redacted`.
+    let mut copied = 0;
+    let mut rest = message.char_indices().peekable();
+    while let Some((start, c)) = rest.next() {
+        if !is_token_char(c) {
+            continue;
+        }
+        let mut end = start + c.len_utf8();
+        while let Some(&(i, c)) = rest.peek() {
+            if !is_token_char(c) {
+                break;
+            }
+            end = i + c.len_utf8();
+            rest.next();
+        }
+        // Base64 padding belongs to the token, unless the `=` starts a value, as in `a=b`.
+        while message[end..].starts_with('=') {
+            end += 1;
+        }
+        if message[end..].starts_with(is_token_char) {
+            end -= message[..end].len() - message[..end].trim_end_matches('=').len();
+        }
+        // A token ending a sentence keeps its full stop.
+        let token = message[start..end].trim_end_matches('.');
+        if looks_secret(token) {
+            redacted.push_str(&message[copied..start]);
+            redacted.push_str("***");
+            copied = start + token.len();
+        }
+        while rest.peek().is_some_and(|&(i, _)| i < end) {
+            rest.next();
+        }
+    }
+    (copied > 0).then(|| {
+        redacted.push_str(&message[copied..]);
+        redacted
+    })
+}
+
+fn looks_secret(token: &str) -> bool {
+    is_jwt(token) || has_key_prefix(token) || is_high_entropy(token)
+}
+
+/// A JSON Web Token: three base64url parts joined by `.`, the first an encoded `{"`.
+fn is_jwt(token: &str) -> bool {
+    let parts: Vec<&str> = token.split('.').collect();
+    token.starts_with("eyJ")
+        && token.len() >= 30
+        && parts.len() == 3
+        && parts[..2].iter().all(|part| !part.is_empty())
+        && parts.iter().all(|part| {
+            part.chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '='))
+        })
+}
+
+fn has_key_prefix(token: &str) -> bool {
+    KEY_PREFIXES.iter().any(|prefix| {
+        token.strip_prefix(prefix).is_some_and(|key| {
+            key.len() >= 16
+                && key
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-'))
+        })
+    })
+}
+
+/// A long token mixing upper case, lower case and digits, with characters about as varied as
+/// in random text.
+fn is_high_entropy(token: &str) -> bool {
+    if token.len() < 24
+        || !token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+' | '/' | '='))
+        || !token.contains(|c: char| c.is_ascii_uppercase())
+        || !token.contains(|c: char| c.is_ascii_lowercase())
+        || !token.contains(|c: char| c.is_ascii_digit())
+    {
+        return false;
+    }
+    let mut counts = [0u32; 128];
+    for b in token.bytes() {
+        counts[b as usize] += 1;
+    }
+    let len = token.len() as f64;
+    let entropy: f64 = counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum();
+    entropy >= 4.0
+}
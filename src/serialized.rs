@@ -0,0 +1,210 @@
+//! Logging values in their `serde` serialized form.
+
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// The format [`log_serde!`](crate::log_serde) serializes values to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// Compact JSON on a single line, e.g. `{"name":"main","width":2}`.
+    #[default]
+    Json,
+    /// JSON indented over several lines.
+    JsonPretty,
+}
+
+/// Renders a value serialized to a [`Format`], or `<unserializable: <error>>` if it cannot
+/// be; for [`log_serde!`](crate::log_serde).
+#[doc(hidden)]
+pub struct Serialized<'a, T: ?Sized>(pub &'a T, pub Format);
+
+impl<T: Serialize + ?Sized> fmt::Display for Serialized<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let serialized = match self.1 {
+            Format::Json => serde_json::to_string(self.0),
+            Format::JsonPretty => serde_json::to_string_pretty(self.0),
+        };
+        match serialized {
+            Ok(serialized) => f.write_str(&serialized),
+            Err(err) => write!(f, "<unserializable: {err}>"),
+        }
+    }
+}
+
+/// Macro to log a value in its serialized form rather than its `Debug` representation.
+///
+/// This macro serializes the value, which must implement `serde::Serialize`, to the given
+/// [`Format`](crate::Format), and logs it at the given level with the name of the function
+/// where it is invoked. The format defaults to [`Format::Json`](crate::Format::Json). If
+/// serialization fails, `<unserializable: <error>>` is logged instead.
+///
+/// # Example
+/// ```rust
+/// # use serde::Serialize;
+/// # use tacky_borders_logger::{Format, log_serde};
+/// #[derive(Serialize)]
+/// struct Border {
+///     width: u32,
+///     color: &'static str,
+/// }
+///
+/// fn apply(border: &Border) {
+///     log_serde!(debug, border, Format::Json);
+///     // Logs: "{"width":2,"color":"#ff0000"} [fn apply]"
+/// }
+/// # apply(&Border { width: 2, color: "#ff0000" });
+/// ```
+/// # Notes
+/// - Available with the `serde` feature.
+/// - The value is borrowed, not moved, and only serialized if the record is going to be
+///   logged.
+#[macro_export]
+macro_rules! log_serde {
+    ($level:ident, $value:expr $(,)?) => {
+        $crate::log_serde!($level, $value, $crate::Format::Json)
+    };
+    ($level:ident, $value:expr, $format:expr $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}",
+            $crate::__private_api::Serialized(&$value, $format)
+        )
+    };
+}
+
+/// A key that differs between two serialized configurations; for
+/// [`log_config_diff!`](crate::log_config_diff).
+///
+/// Renders as `server.port: 8080 -> 9090`, `server.tls: added true` or
+/// `server.debug: removed (was false)`, with the values as compact JSON.
+#[doc(hidden)]
+pub enum ConfigChange {
+    Changed(String, Value, Value),
+    Added(String, Value),
+    Removed(String, Value),
+}
+
+impl fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigChange::Changed(path, old, new) => write!(f, "{path}: {old} -> {new}"),
+            ConfigChange::Added(path, new) => write!(f, "{path}: added {new}"),
+            ConfigChange::Removed(path, old) => write!(f, "{path}: removed (was {old})"),
+        }
+    }
+}
+
+/// Serializes both values and returns the keys that differ between them, or the
+/// serialization error. Within each object, keys added in `new` come last.
+#[doc(hidden)]
+pub fn config_diff<T: Serialize + ?Sized>(
+    old: &T,
+    new: &T,
+) -> Result<Vec<ConfigChange>, serde_json::Error> {
+    let (old, new) = (serde_json::to_value(old)?, serde_json::to_value(new)?);
+    let mut changes = Vec::new();
+    diff_values(&mut String::new(), old, new, &mut changes);
+    Ok(changes)
+}
+
+/// Compares objects key by key, descending into nested ones; anything else, arrays
+/// included, is compared as a whole.
+fn diff_values(path: &mut String, old: Value, new: Value, changes: &mut Vec<ConfigChange>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(mut new)) => {
+            for (key, old) in old {
+                let len = push_key(path, &key);
+                match new.remove(&key) {
+                    Some(new) => diff_values(path, old, new, changes),
+                    None => changes.push(ConfigChange::Removed(path.clone(), old)),
+                }
+                path.truncate(len);
+            }
+            for (key, new) in new {
+                let len = push_key(path, &key);
+                changes.push(ConfigChange::Added(path.clone(), new));
+                path.truncate(len);
+            }
+        }
+        (old, new) if old != new => {
+            let path = if path.is_empty() { "<root>" } else { path };
+            changes.push(ConfigChange::Changed(path.to_owned(), old, new));
+        }
+        _ => {}
+    }
+}
+
+/// Appends `key` to a dotted path and returns the path's previous length.
+fn push_key(path: &mut String, key: &str) -> usize {
+    let len = path.len();
+    if len != 0 {
+        path.push('.');
+    }
+    path.push_str(key);
+    len
+}
+
+/// Macro to log what changed between two versions of a configuration.
+///
+/// This macro serializes both values, which must implement `serde::Serialize`, compares them
+/// key by key, descending into nested objects, and logs one record per key that differs at
+/// the given level, with the name of the function where it is invoked: `<path>: <old> ->
+/// <new>` for a changed value, `<path>: added <new>` for a new key and `<path>: removed (was
+/// <old>)` for a key that is gone. Paths join nested keys with dots, and values are shown as
+/// compact JSON. If nothing changed, it logs `config unchanged`; if either value cannot be
+/// serialized, it logs `cannot diff config: <error>` at warn level.
+///
+/// # Example
+/// ```rust
+/// # use serde::Serialize;
+/// # use tacky_borders_logger::log_config_diff;
+/// #[derive(Serialize)]
+/// struct Server {
+///     port: u16,
+///     host: &'static str,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Config {
+///     server: Server,
+/// }
+///
+/// fn reload(old: &Config, new: &Config) {
+///     log_config_diff!(info, old, new);
+///     // Logs: "server.port: 8080 -> 9090 [fn reload]"
+/// }
+/// # reload(
+/// #     &Config { server: Server { port: 8080, host: "localhost" } },
+/// #     &Config { server: Server { port: 9090, host: "localhost" } },
+/// # );
+/// ```
+/// # Notes
+/// - Available with the `serde` feature.
+/// - Arrays are compared as a whole, so a changed element shows the entire array on both
+///   sides.
+/// - The values are borrowed, not moved, and only serialized if the level is enabled.
+#[macro_export]
+macro_rules! log_config_diff {
+    ($level:ident, $old:expr, $new:expr $(,)?) => {{
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) {
+            match $crate::__private_api::config_diff(&$old, &$new) {
+                ::std::result::Result::Ok(changes) if changes.is_empty() => {
+                    $crate::__log!(level, "config unchanged");
+                }
+                ::std::result::Result::Ok(changes) => {
+                    for change in &changes {
+                        $crate::__log!(level, "{}", change);
+                    }
+                }
+                ::std::result::Result::Err(err) => $crate::__log!(
+                    $crate::__private_api::log::Level::Warn,
+                    "cannot diff config: {}",
+                    err
+                ),
+            }
+        }
+    }};
+}
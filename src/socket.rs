@@ -0,0 +1,153 @@
+//! Sending records to a local collector over a Unix domain socket or a Windows named pipe.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use log::Level;
+
+/// The delay before the first attempt to reconnect, doubled after each failed attempt.
+const FIRST_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+static SINK: Mutex<Option<Sink>> = Mutex::new(None);
+/// Whether a socket sink is set, so records can skip locking it otherwise.
+static SINK_ENABLED: AtomicBool = AtomicBool::new(false);
+
+struct Sink {
+    path: PathBuf,
+    connection: Option<Box<dyn Write + Send>>,
+    /// When to try to reconnect next, while not connected.
+    retry_at: Instant,
+    backoff: Duration,
+}
+
+impl Sink {
+    fn disconnected(&mut self, now: Instant) {
+        self.connection = None;
+        self.retry_at = now + self.backoff;
+        eprintln!(
+            "tacky-borders-logger: retrying socket sink {} in {:?}",
+            self.path.display(),
+            self.backoff
+        );
+        self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+#[cfg(unix)]
+fn connect(path: &Path) -> io::Result<Box<dyn Write + Send>> {
+    Ok(Box::new(std::os::unix::net::UnixStream::connect(path)?))
+}
+
+#[cfg(windows)]
+fn connect(path: &Path) -> io::Result<Box<dyn Write + Send>> {
+    // The client end of a named pipe is opened like a file.
+    Ok(Box::new(
+        std::fs::OpenOptions::new().write(true).open(path)?,
+    ))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn connect(_path: &Path) -> io::Result<Box<dyn Write + Send>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// Sends every record to a local collector listening on a Unix domain socket, or on Windows
+/// a named pipe such as `\\.\pipe\tacky-borders`, replacing any socket set before.
+///
+/// Each record is written as `<LEVEL> <message>` and a newline, with the message as the
+/// logger receives it, in addition to being logged. If the connection fails, or is lost
+/// later, records are dropped while the sink reconnects in the background of later records,
+/// waiting 100ms before the first attempt and twice as long after each failed one, up to
+/// 30s. Reconnection attempts are reported on stderr rather than logged, so a collector
+/// that is down cannot cause a loop.
+///
+/// If the first connection fails, the error is also logged at error level and returned, and
+/// the sink keeps trying to connect, for a collector that starts after the program.
+///
+/// # Example
+/// ```rust
+/// # let dir = std::env::temp_dir();
+/// tacky_borders_logger::set_socket_sink(dir.join("collector.sock")).ok();
+/// ```
+#[track_caller]
+pub fn set_socket_sink(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    let connected = connect(path);
+    let mut sink = Sink {
+        path: path.to_path_buf(),
+        connection: None,
+        retry_at: Instant::now(),
+        backoff: FIRST_BACKOFF,
+    };
+    let result = match connected {
+        Ok(connection) => {
+            sink.connection = Some(connection);
+            Ok(())
+        }
+        Err(err) => {
+            sink.disconnected(Instant::now());
+            Err(err)
+        }
+    };
+    *SINK.lock().unwrap_or_else(|e| e.into_inner()) = Some(sink);
+    SINK_ENABLED.store(true, Ordering::Relaxed);
+    if let Err(err) = &result {
+        // Logged after the sink is stored and unlocked, as this record goes through it too.
+        crate::log_caller(
+            Level::Error,
+            format_args!("cannot connect to socket sink {}: {err}", path.display()),
+        );
+    }
+    result
+}
+
+/// Writes `line` to the socket sink, if one is set, reconnecting first if it is due.
+pub(crate) fn write(level: Level, line: &dyn fmt::Display) {
+    if !SINK_ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    // Formatted before locking, so user code never runs under the lock.
+    let line = format!("{level:<5} {line}\n");
+    let mut sink = SINK.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(sink) = sink.as_mut() else {
+        return;
+    };
+    let now = Instant::now();
+    if sink.connection.is_none() {
+        if now < sink.retry_at {
+            return;
+        }
+        match connect(&sink.path) {
+            Ok(connection) => {
+                eprintln!(
+                    "tacky-borders-logger: reconnected to socket sink {}",
+                    sink.path.display()
+                );
+                sink.connection = Some(connection);
+                sink.backoff = FIRST_BACKOFF;
+            }
+            Err(err) => {
+                eprintln!(
+                    "tacky-borders-logger: cannot connect to socket sink {}: {err}",
+                    sink.path.display()
+                );
+                sink.disconnected(now);
+                return;
+            }
+        }
+    }
+    if let Some(connection) = sink.connection.as_mut()
+        && let Err(err) = connection.write_all(line.as_bytes())
+    {
+        eprintln!(
+            "tacky-borders-logger: lost connection to socket sink {}: {err}",
+            sink.path.display()
+        );
+        sink.disconnected(now);
+    }
+}
@@ -0,0 +1,213 @@
+//! Guards that log entry into and exit from a scope, and track how deeply they are nested.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+use log::Level;
+
+use crate::__private_api::{self, Elapsed, Location};
+use crate::{clock, config};
+
+thread_local! {
+    /// The spans entered on this thread, outermost first.
+    static SPANS: RefCell<Vec<Span>> = const { RefCell::new(Vec::new()) };
+}
+
+/// An entered span, with the statistics collected while
+/// [`set_span_stats`](crate::set_span_stats) is enabled.
+struct Span {
+    name: String,
+    start: Instant,
+    /// The number of records logged while the span was entered.
+    logs: usize,
+    /// The greatest number of spans entered at once while the span was, itself included.
+    peak_depth: usize,
+}
+
+/// Returns how many spans are currently entered on this thread.
+pub(crate) fn depth() -> usize {
+    SPANS.with_borrow(Vec::len)
+}
+
+/// Returns how long ago the innermost span entered on this thread was entered, if any.
+pub(crate) fn innermost_elapsed() -> Option<Duration> {
+    SPANS.with_borrow(|spans| spans.last().map(|span| clock::elapsed_since(span.start)))
+}
+
+/// Counts a record towards every span entered on this thread.
+pub(crate) fn count_record() {
+    SPANS.with_borrow_mut(|spans| spans.iter_mut().for_each(|span| span.logs += 1));
+}
+
+/// Renders the names of the spans entered on this thread, outermost first, joined with
+/// ` > `, or the given function name if there are none; for
+/// [`log_span_path!`](crate::log_span_path).
+#[doc(hidden)]
+pub struct SpanPath<'a>(pub &'a str);
+
+impl fmt::Display for SpanPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        SPANS.with_borrow(|spans| {
+            if spans.is_empty() {
+                return f.write_str(self.0);
+            }
+            for (i, span) in spans.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(" > ")?;
+                }
+                f.write_str(&span.name)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// A scope entered with [`log_span!`](crate::log_span), which logs its exit when dropped.
+///
+/// Spans are tracked per thread, so a guard cannot be sent to another thread.
+#[must_use = "the span is exited as soon as the guard is dropped"]
+pub struct SpanGuard {
+    level: Level,
+    fn_name: String,
+    loc: Location<'static>,
+    start: Instant,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl SpanGuard {
+    #[doc(hidden)]
+    pub fn __enter(level: Level, name: String, fn_name: String, loc: Location<'static>) -> Self {
+        if __private_api::enabled(level) {
+            __private_api::emit(level, format_args!("enter {name}"), &fn_name, &loc);
+        }
+        SPANS.with_borrow_mut(|spans| {
+            let depth = spans.len() + 1;
+            for span in spans.iter_mut() {
+                span.peak_depth = span.peak_depth.max(depth);
+            }
+            spans.push(Span {
+                name,
+                start: clock::now(),
+                logs: 0,
+                peak_depth: depth,
+            });
+        });
+        Self {
+            level,
+            fn_name,
+            loc,
+            start: clock::now(),
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl Drop for SpanGuard {
+    fn drop(&mut self) {
+        let Some(span) = SPANS.with_borrow_mut(Vec::pop) else {
+            return;
+        };
+        if !__private_api::enabled(self.level) {
+            return;
+        }
+        let elapsed = Elapsed(clock::elapsed_since(self.start));
+        if config::span_stats() {
+            __private_api::emit_fields(
+                self.level,
+                format_args!("span {} closed", span.name),
+                &self.fn_name,
+                &self.loc,
+                &[
+                    ("duration", &elapsed),
+                    ("logs", &span.logs),
+                    ("peak_depth", &span.peak_depth),
+                ],
+            );
+        } else {
+            __private_api::emit(
+                self.level,
+                format_args!("exit {} ({elapsed})", span.name),
+                &self.fn_name,
+                &self.loc,
+            );
+        }
+    }
+}
+
+/// Macro to log entry into a scope now and exit from it when the returned guard is dropped.
+///
+/// This macro logs `enter <name>` at the given level with the name of the function where
+/// it is invoked, and returns a [`SpanGuard`](crate::SpanGuard). When the guard is
+/// dropped, it logs `exit <name> (<elapsed>)`, or, with
+/// [`set_span_stats`](crate::set_span_stats), `span <name> closed duration=<elapsed>
+/// logs=<n> peak_depth=<n>`. The name is formatted like a log message.
+/// Spans nest: each thread keeps a stack of the spans it is in, which options such as
+/// [`set_indent_with_depth`](crate::set_indent_with_depth) use.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{debug, log_span};
+/// fn reload(path: &str) {
+///     let _span = log_span!(debug, "reload {}", path);
+///     // Logs: "enter reload config.yaml [fn reload]"
+///     debug!("parsing");
+/// }   // Logs: "exit reload config.yaml (1.2ms) [fn reload]"
+/// ```
+/// # Notes
+/// - Bind the guard to a named variable such as `_span`; `let _ = log_span!(...)` drops it,
+///   and exits the span, immediately.
+/// - Guards must be dropped in the reverse order they were created, which scoping does
+///   naturally. They cannot be held across an `.await` in a future that may move between
+///   threads.
+#[macro_export]
+macro_rules! log_span {
+    ($level:ident, $($arg:tt)+) => {
+        $crate::SpanGuard::__enter(
+            $crate::__level!($level),
+            ::std::format!($($arg)+),
+            $crate::function_name!(),
+            (module_path!(), module_path!(), file!(), line!()),
+        )
+    };
+}
+
+/// Macro to log a message prefixed with the path of spans it is logged in.
+///
+/// This macro logs `<outer> > ... > <inner>: <message>` at the given level with the name of
+/// the function where it is invoked, where the path lists the names of the spans entered
+/// with [`log_span!`](crate::log_span) on the current thread, outermost first. Outside any
+/// span, the path is just the function name. The message is formatted like a log message.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{log_span, log_span_path};
+/// fn validate() {
+///     log_span_path!(debug, "checkpoint");
+///     // Logs, when called inside spans "handle" and "parse":
+///     // "handle > parse: checkpoint [fn validate]"
+/// }
+/// # let _handle = log_span!(debug, "handle");
+/// # let _parse = log_span!(debug, "parse");
+/// # validate();
+/// ```
+#[macro_export]
+macro_rules! log_span_path {
+    ($level:ident, $($arg:tt)+) => {{
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) {
+            let fn_name = $crate::function_name!();
+            $crate::__private_api::emit(
+                level,
+                format_args!(
+                    "{}: {}",
+                    $crate::__private_api::SpanPath(&fn_name),
+                    format_args!($($arg)+)
+                ),
+                &fn_name,
+                &(module_path!(), module_path!(), file!(), line!()),
+            );
+        }
+    }};
+}
@@ -0,0 +1,140 @@
+//! Per-level record counts reported at a fixed interval from the application's own loop.
+
+use std::fmt;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use log::Level;
+
+use crate::clock;
+use crate::display::FmtDuration;
+
+/// The interval in nanoseconds, or 0 while counting is off.
+static INTERVAL: AtomicU64 = AtomicU64::new(0);
+/// Records logged in the current window, indexed by `Level as usize - 1`.
+static COUNTS: [AtomicU64; 5] = [const { AtomicU64::new(0) }; 5];
+/// When the current window started.
+static WINDOW_START: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Sets how often [`log_stats_if_due!`](crate::log_stats_if_due) reports the number of
+/// records logged at each level, or turns counting off with `None`.
+///
+/// While an interval is set, every record logged through this crate is counted by level. The
+/// counts start from zero and a new window begins whenever this is called. Nothing runs in the
+/// background: the report is only logged when `log_stats_if_due!` is called after the
+/// interval has elapsed. Counting is off by default.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+///
+/// tacky_borders_logger::set_stats_interval(Duration::from_secs(30));
+/// ```
+pub fn set_stats_interval(interval: impl Into<Option<Duration>>) {
+    let nanos = interval.into().map_or(0, |interval| {
+        interval.as_nanos().clamp(1, u64::MAX as u128) as u64
+    });
+    let mut start = WINDOW_START.lock().unwrap_or_else(|e| e.into_inner());
+    INTERVAL.store(nanos, Ordering::Relaxed);
+    COUNTS
+        .iter()
+        .for_each(|count| count.store(0, Ordering::Relaxed));
+    *start = (nanos != 0).then(clock::now);
+}
+
+/// Counts a record logged at `level` if an interval is set.
+pub(crate) fn count(level: Level) {
+    if INTERVAL.load(Ordering::Relaxed) != 0 {
+        COUNTS[level as usize - 1].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Returns the counts of the current window and starts a new one if the interval has
+/// elapsed.
+#[doc(hidden)]
+pub fn take_due_stats() -> Option<Stats> {
+    let interval = INTERVAL.load(Ordering::Relaxed);
+    if interval == 0 {
+        return None;
+    }
+
+    let mut start = WINDOW_START.lock().unwrap_or_else(|e| e.into_inner());
+    let elapsed = clock::elapsed_since((*start)?);
+    if elapsed < Duration::from_nanos(interval) {
+        return None;
+    }
+    *start = Some(clock::now());
+    let counts = COUNTS
+        .each_ref()
+        .map(|count| count.swap(0, Ordering::Relaxed));
+    Some(Stats { counts, elapsed })
+}
+
+/// Renders the counts of a window as `stats: debug=5 warn=2 in last 30.00s`, leaving out
+/// levels with no records; for [`log_stats_if_due!`](crate::log_stats_if_due).
+#[doc(hidden)]
+pub struct Stats {
+    counts: [u64; 5],
+    elapsed: Duration,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const NAMES: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
+
+        f.write_str("stats:")?;
+        let mut any = false;
+        // Listed from the most verbose level down, as in the level filter.
+        for (name, count) in NAMES.iter().zip(self.counts).rev() {
+            if count != 0 {
+                write!(f, " {name}={count}")?;
+                any = true;
+            }
+        }
+        if !any {
+            f.write_str(" no records")?;
+        }
+        write!(f, " in last {}", FmtDuration(self.elapsed))
+    }
+}
+
+/// Macro to log how many records were logged at each level since the last report, once the
+/// interval set with [`set_stats_interval`](crate::set_stats_interval) has elapsed.
+///
+/// This macro is meant to be called on every iteration of an application's main loop. It
+/// does nothing until the interval has elapsed; then it logs the count of records at each
+/// level since the previous report, with the name of the function where it is invoked, and
+/// resets the counts. It logs at info level unless another level is given.
+///
+/// # Example
+/// ```rust
+/// # use std::time::Duration;
+/// # use tacky_borders_logger::log_stats_if_due;
+/// fn main() {
+///     tacky_borders_logger::set_stats_interval(Duration::from_secs(30));
+///     loop {
+///         // ... handle events ...
+///         log_stats_if_due!();
+///         // Every 30 seconds, logs e.g.
+///         // "stats: trace=10 debug=5 warn=2 error=1 in last 30.00s [fn main]"
+/// #       break;
+///     }
+/// }
+/// ```
+/// # Notes
+/// - Levels without records in the window are left out.
+/// - The report itself is counted in the next window.
+/// - The window restarts when the report is due even if its level is disabled, so the
+///   counts always cover a single interval.
+#[macro_export]
+macro_rules! log_stats_if_due {
+    () => {
+        $crate::log_stats_if_due!(info)
+    };
+    ($level:ident $(,)?) => {
+        if let ::std::option::Option::Some(stats) = $crate::__private_api::take_due_stats() {
+            $crate::__log!($crate::__level!($level), "{}", stats);
+        }
+    };
+}
@@ -0,0 +1,305 @@
+//! One-line summaries of which steps of a multi-stage operation succeeded, and of how long
+//! each took.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+use log::Level;
+
+use crate::__private_api::{self, Elapsed, Location};
+use crate::clock;
+
+thread_local! {
+    /// The steps recorded in each [`steps_log!`](crate::steps_log) block running on this
+    /// thread, outermost first, as `(name, succeeded)`.
+    static STEPS: RefCell<Vec<Vec<(String, bool)>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Outcomes that [`step!`](crate::step) can record.
+#[doc(hidden)]
+pub trait StepOutcome {
+    fn __succeeded(&self) -> bool;
+}
+
+impl<T, E> StepOutcome for Result<T, E> {
+    fn __succeeded(&self) -> bool {
+        self.is_ok()
+    }
+}
+
+impl<T> StepOutcome for Option<T> {
+    fn __succeeded(&self) -> bool {
+        self.is_some()
+    }
+}
+
+impl StepOutcome for bool {
+    fn __succeeded(&self) -> bool {
+        *self
+    }
+}
+
+/// Records a step in the innermost [`steps_log!`](crate::steps_log) block on this thread, if
+/// any.
+#[doc(hidden)]
+pub fn record_step(name: &str, succeeded: bool) {
+    STEPS.with_borrow_mut(|blocks| {
+        if let Some(steps) = blocks.last_mut() {
+            steps.push((name.to_owned(), succeeded));
+        }
+    });
+}
+
+/// A [`steps_log!`](crate::steps_log) block, which logs its steps when dropped.
+#[doc(hidden)]
+pub struct StepsGuard {
+    level: Level,
+    fn_name: String,
+    loc: Location<'static>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl StepsGuard {
+    pub fn __enter(level: Level, fn_name: String, loc: Location<'static>) -> Self {
+        STEPS.with_borrow_mut(|blocks| blocks.push(Vec::new()));
+        Self {
+            level,
+            fn_name,
+            loc,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl Drop for StepsGuard {
+    fn drop(&mut self) {
+        let steps = STEPS.with_borrow_mut(Vec::pop).unwrap_or_default();
+        if __private_api::enabled(self.level) {
+            __private_api::emit(
+                self.level,
+                format_args!("steps:{}", Steps(&steps)),
+                &self.fn_name,
+                &self.loc,
+            );
+        }
+    }
+}
+
+/// Renders steps as ` parse✓ validate✓ save✗`.
+struct Steps<'a>(&'a [(String, bool)]);
+
+impl fmt::Display for Steps<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|(name, succeeded)| {
+            let mark = if *succeeded { '✓' } else { '✗' };
+            write!(f, " {name}{mark}")
+        })
+    }
+}
+
+/// Macro to run a block of fallible steps and log a one-line summary of their outcomes.
+///
+/// This macro runs the block, recording each [`step!`](crate::step) evaluated in it, then
+/// logs `steps: <name>✓ <name>✗ ...` with the name of the function where it is invoked.
+/// The summary is also logged when the block is left early, with `return`, `?` or a panic,
+/// listing only the steps reached. The macro evaluates to the value of the block.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{step, steps_log};
+/// # fn parse(s: &str) -> Result<u32, String> { s.parse().map_err(|_| "parse".to_owned()) }
+/// # fn validate(n: u32) -> Result<u32, String> { Ok(n) }
+/// # fn save(n: u32) -> Result<(), String> { Err("disk full".to_owned()) }
+/// fn import(input: &str) -> Result<(), String> {
+///     steps_log!(info, {
+///         let value = step!("parse", parse(input))?;
+///         let value = step!("validate", validate(value))?;
+///         step!("save", save(value))
+///     })
+///     // Logs: "steps: parse✓ validate✓ save✗ [fn import]"
+/// }
+/// # import("42").unwrap_err();
+/// ```
+/// # Notes
+/// - Steps are recorded per thread, in the innermost block; the block must not be held across
+///   an `.await` in a future that may move between threads.
+#[macro_export]
+macro_rules! steps_log {
+    ($level:ident, $body:block) => {{
+        let _steps = $crate::__private_api::StepsGuard::__enter(
+            $crate::__level!($level),
+            $crate::function_name!(),
+            (module_path!(), module_path!(), file!(), line!()),
+        );
+        $body
+    }};
+}
+
+/// Macro to record the outcome of a step for the enclosing [`steps_log!`](crate::steps_log)
+/// block.
+///
+/// This macro evaluates `expr`, records whether it succeeded under `name`, and returns it
+/// unchanged, so it can be followed by `?`. A `Result` succeeds if it is `Ok`, an `Option`
+/// if it is `Some`, and a `bool` if it is `true`. Outside of a `steps_log!` block, nothing
+/// is recorded.
+///
+/// # Example
+/// See [`steps_log!`](crate::steps_log).
+#[macro_export]
+macro_rules! step {
+    ($name:expr, $expr:expr $(,)?) => {{
+        let outcome = $expr;
+        $crate::__private_api::record_step(
+            ::std::convert::AsRef::<str>::as_ref(&$name),
+            $crate::__private_api::StepOutcome::__succeeded(&outcome),
+        );
+        outcome
+    }};
+}
+
+thread_local! {
+    /// The steps timed in each [`timed_steps!`](crate::timed_steps) block running on this
+    /// thread, outermost first, as `(name, elapsed)`.
+    static TIMED_STEPS: RefCell<Vec<Vec<(String, Duration)>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A [`timed_steps!`](crate::timed_steps) block, which logs its step timings when dropped.
+#[doc(hidden)]
+pub struct TimedStepsGuard {
+    level: Level,
+    fn_name: String,
+    loc: Location<'static>,
+    start: Instant,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl TimedStepsGuard {
+    pub fn __enter(level: Level, fn_name: String, loc: Location<'static>) -> Self {
+        TIMED_STEPS.with_borrow_mut(|blocks| blocks.push(Vec::new()));
+        Self {
+            level,
+            fn_name,
+            loc,
+            start: clock::now(),
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl Drop for TimedStepsGuard {
+    fn drop(&mut self) {
+        let steps = TIMED_STEPS.with_borrow_mut(Vec::pop).unwrap_or_default();
+        if __private_api::enabled(self.level) {
+            let total = clock::elapsed_since(self.start);
+            __private_api::emit_timed(
+                self.level,
+                format_args!("total={} [{}]", Elapsed(total), TimedSteps(&steps)),
+                &self.fn_name,
+                &self.loc,
+                total,
+            );
+        }
+    }
+}
+
+/// A step being timed by [`timed_step!`](crate::timed_step), which records its duration in
+/// the innermost [`timed_steps!`](crate::timed_steps) block on this thread when dropped.
+#[doc(hidden)]
+pub struct TimedStep<'a> {
+    name: &'a str,
+    start: Instant,
+}
+
+impl<'a> TimedStep<'a> {
+    pub fn __start(name: &'a str) -> Self {
+        Self {
+            name,
+            start: clock::now(),
+        }
+    }
+}
+
+impl Drop for TimedStep<'_> {
+    fn drop(&mut self) {
+        let elapsed = clock::elapsed_since(self.start);
+        TIMED_STEPS.with_borrow_mut(|blocks| {
+            if let Some(steps) = blocks.last_mut() {
+                steps.push((self.name.to_owned(), elapsed));
+            }
+        });
+    }
+}
+
+/// Renders step timings as `parse=30ms validate=20ms save=50ms`.
+struct TimedSteps<'a>(&'a [(String, Duration)]);
+
+impl fmt::Display for TimedSteps<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, elapsed)) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{name}={}", Elapsed(*elapsed))?;
+        }
+        Ok(())
+    }
+}
+
+/// Macro to run a block of timed steps and log a one-line breakdown of where the time went.
+///
+/// This macro runs the block, recording the duration of each
+/// [`timed_step!`](crate::timed_step) evaluated in it, then logs `total=<elapsed>
+/// [<name>=<elapsed> ...]` at the given level with the name of the function where it is
+/// invoked. The total also counts time spent outside the steps. The breakdown is also logged
+/// when the block is left early, with `return`, `?` or a panic, listing only the steps
+/// reached. The macro evaluates to the value of the block.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{timed_step, timed_steps};
+/// # fn parse(s: &str) -> u32 { s.parse().unwrap() }
+/// # fn save(_: u32) {}
+/// fn import(input: &str) {
+///     timed_steps!(info, {
+///         let value = timed_step!("parse", { parse(input) });
+///         timed_step!("validate", { assert!(value > 0) });
+///         timed_step!("save", { save(value) });
+///     });
+///     // Logs: "total=100ms [parse=30ms validate=20ms save=50ms] [fn import]"
+/// }
+/// # import("42");
+/// ```
+/// # Notes
+/// - A step run several times, such as in a loop, is listed once per run.
+/// - Like [`steps_log!`](crate::steps_log), steps are recorded per thread, in the innermost
+///   block.
+#[macro_export]
+macro_rules! timed_steps {
+    ($level:ident, $body:block) => {{
+        let _steps = $crate::__private_api::TimedStepsGuard::__enter(
+            $crate::__level!($level),
+            $crate::function_name!(),
+            (module_path!(), module_path!(), file!(), line!()),
+        );
+        $body
+    }};
+}
+
+/// Macro to time a step for the enclosing [`timed_steps!`](crate::timed_steps) block.
+///
+/// This macro runs the block, records how long it took under `name`, and evaluates to the
+/// value of the block. The step is also recorded if the block is left early, with `?` or
+/// `return`. Outside of a `timed_steps!` block, nothing is recorded.
+///
+/// # Example
+/// See [`timed_steps!`](crate::timed_steps).
+#[macro_export]
+macro_rules! timed_step {
+    ($name:expr, $body:block) => {{
+        let name = &$name;
+        let _step = $crate::__private_api::TimedStep::__start(::std::convert::AsRef::as_ref(name));
+        $body
+    }};
+}
@@ -0,0 +1,100 @@
+//! Named counters accumulated during a run and reported together, typically at shutdown.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+
+/// The metrics added with [`summary_add!`](crate::summary_add), by name.
+static METRICS: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
+
+/// Adds `value` to the metric `name`, starting it at 0 if it is new.
+#[doc(hidden)]
+pub fn summary_add(name: &str, value: u64) {
+    let mut metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    match metrics.get_mut(name) {
+        Some(total) => *total = total.saturating_add(value),
+        None => {
+            metrics.insert(name.to_owned(), value);
+        }
+    }
+}
+
+/// Renders every metric as a line of a two-column table, names left and values right
+/// aligned, under a `summary:` heading; for [`summary_log!`](crate::summary_log).
+#[doc(hidden)]
+pub struct Summary;
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+        if metrics.is_empty() {
+            return f.write_str("summary: no metrics");
+        }
+
+        let name_width = metrics.keys().map(|name| name.chars().count()).max();
+        let value_width = metrics.values().map(|value| value.to_string().len()).max();
+        let (name_width, value_width) = (name_width.unwrap_or(0), value_width.unwrap_or(0));
+        f.write_str("summary:")?;
+        for (name, value) in metrics.iter() {
+            write!(f, "\n  {name:<name_width$}  {value:>value_width$}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Macro to add to a named metric of the end-of-run summary.
+///
+/// This macro adds `value`, a `u64`, to the metric `name` in a process-wide table, creating
+/// it at 0 if needed. It logs nothing; [`summary_log!`](crate::summary_log) reports every
+/// metric at once. It can be called from any thread.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::summary_add;
+/// fn handle_request(bytes: &[u8]) {
+///     summary_add!("requests", 1);
+///     summary_add!("bytes received", bytes.len() as u64);
+/// }
+/// ```
+/// # Notes
+/// - Metrics saturate at `u64::MAX` rather than wrapping.
+/// - Updates take a lock shared by all metrics, which is fine for per-request counters but
+///   too costly for the innermost loop of hot code; count locally there and add the total.
+#[macro_export]
+macro_rules! summary_add {
+    ($name:expr, $value:expr $(,)?) => {
+        $crate::__private_api::summary_add(::std::convert::AsRef::<str>::as_ref(&$name), $value)
+    };
+}
+
+/// Macro to log every metric added with [`summary_add!`](crate::summary_add) as a table.
+///
+/// This macro logs a single record with a `summary:` heading followed by one line per
+/// metric, sorted by name, with names and values aligned in columns, and the name of the
+/// function where it is invoked. It is meant for the end of `main` or a shutdown hook.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{summary_add, summary_log};
+/// fn main() {
+///     summary_add!("requests", 1024);
+///     summary_add!("errors", 3);
+///     summary_log!(info);
+///     // Logs:
+///     // summary:
+///     //   errors       3
+///     //   requests  1024 [fn main]
+/// }
+/// ```
+/// # Notes
+/// - The metrics are not reset, so logging the summary again reports the running totals.
+#[macro_export]
+macro_rules! summary_log {
+    ($level:ident $(,)?) => {
+        $crate::__log!(
+            $crate::__level!($level),
+            "{}",
+            $crate::__private_api::Summary
+        )
+    };
+}
@@ -0,0 +1,93 @@
+//! Column-aligned rows for dumping tabular data.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+
+thread_local! {
+    /// The column widths of every table logged on this thread, by table id.
+    static WIDTHS: RefCell<HashMap<String, Vec<usize>>> = RefCell::new(HashMap::new());
+}
+
+/// Renders a row of `table`, widening its columns to fit, and returns it along with a header
+/// naming the columns if this is the table's first row on this thread.
+#[doc(hidden)]
+pub fn table_row(table: &str, cells: &[(&str, &dyn fmt::Display)]) -> (Option<String>, String) {
+    let values: Vec<String> = cells.iter().map(|(_, value)| value.to_string()).collect();
+
+    WIDTHS.with_borrow_mut(|tables| {
+        let (widths, is_new) = match tables.get_mut(table) {
+            Some(widths) => (widths, false),
+            None => (tables.entry(table.to_owned()).or_default(), true),
+        };
+        widths.resize(widths.len().max(cells.len()), 0);
+        for (width, ((name, _), value)) in widths.iter_mut().zip(cells.iter().zip(&values)) {
+            *width = (*width)
+                .max(name.chars().count())
+                .max(value.chars().count());
+        }
+
+        let header = is_new.then(|| join(cells.iter().map(|(name, _)| *name), widths));
+        let row = join(values.iter().map(String::as_str), widths);
+        (header, row)
+    })
+}
+
+/// Joins cells with two spaces, padding every cell but the last to its column's width.
+fn join<'a>(cells: impl ExactSizeIterator<Item = &'a str>, widths: &[usize]) -> String {
+    let last = cells.len().saturating_sub(1);
+    let mut line = String::new();
+    for (i, (cell, width)) in cells.zip(widths).enumerate() {
+        if i == last {
+            line.push_str(cell);
+        } else {
+            let _ = write!(line, "{cell:<width$}  ");
+        }
+    }
+    line
+}
+
+/// Macro to log a row of values as a column-aligned table line.
+///
+/// This macro logs the values, rendered with `Display`, padded so consecutive rows of the
+/// same table line up, with the name of the function where it is invoked. The first row of
+/// a table is preceded by a header line with the column names. Column widths grow to fit
+/// the widest value seen so far on the current thread.
+///
+/// Rows logged from the same call site form one table. To continue a table from several
+/// call sites, name it with `table: "id"`.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_row;
+/// fn dump_windows(windows: &[(u32, &str)]) {
+///     for &(id, title) in windows {
+///         log_row!(debug, [("id", id), ("title", title)]);
+///     }
+///     // Logs: "id      title [fn dump_windows]"
+///     //       "264058  Terminal [fn dump_windows]"
+///     //       "1312    Explorer [fn dump_windows]"
+/// }
+/// ```
+/// # Notes
+/// - Widening a column only affects the rows logged after it, so earlier rows may be
+///   narrower than later ones.
+#[macro_export]
+macro_rules! log_row {
+    ($level:ident, table: $table:expr, [$(($name:expr, $value:expr)),+ $(,)?] $(,)?) => {{
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) {
+            let (header, row) = $crate::__private_api::table_row(
+                $table,
+                &[$(($name, &$value as &dyn ::std::fmt::Display)),+],
+            );
+            if let ::std::option::Option::Some(header) = header {
+                $crate::__log!(level, "{}", header);
+            }
+            $crate::__log!(level, "{}", row);
+        }
+    }};
+    ($level:ident, [$($cells:tt)+] $(,)?) => {
+        $crate::log_row!($level, table: concat!(file!(), ":", line!()), [$($cells)+])
+    };
+}
@@ -0,0 +1,110 @@
+//! Logging to a target chosen at runtime, such as one per tenant.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt::{self, Write as _};
+use std::sync::{Arc, Mutex};
+
+static TARGETS: Mutex<Option<HashSet<Arc<str>>>> = Mutex::new(None);
+
+thread_local! {
+    /// Reused to render targets, so only new ones allocate.
+    static KEY: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Returns the target `tenant::<tenant>`, shared with every earlier record for the same
+/// tenant; for [`log_tenant!`](crate::log_tenant).
+#[doc(hidden)]
+pub fn tenant_target(tenant: &dyn fmt::Display) -> Arc<str> {
+    KEY.with_borrow_mut(|key| {
+        key.clear();
+        let _ = write!(key, "tenant::{tenant}");
+
+        let mut targets = TARGETS.lock().unwrap_or_else(|e| e.into_inner());
+        let targets = targets.get_or_insert_with(HashSet::new);
+        if let Some(target) = targets.get(key.as_str()) {
+            return Arc::clone(target);
+        }
+        let target: Arc<str> = Arc::from(key.as_str());
+        targets.insert(Arc::clone(&target));
+        target
+    })
+}
+
+/// Macro to log a message with a target chosen at runtime from a tenant id.
+///
+/// This macro logs the message at the given level with the name of the function where it is
+/// invoked, like the level macros, but sets the record's target to `tenant::<tenant>` rather
+/// than the module path. Loggers and filters that select records by target, such as
+/// `RUST_LOG=tenant::42=debug`, can then isolate the records of a single tenant. The tenant
+/// can be anything implementing `Display`.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::info_tenant;
+/// fn handle_request(tenant_id: u32) {
+///     info_tenant!(tenant_id, "request accepted");
+///     // Logs "request accepted [fn handle_request]" with the target "tenant::42".
+/// }
+/// # handle_request(42);
+/// ```
+/// # Notes
+/// - Each target is allocated once, the first time its tenant logs, and kept for the rest of
+///   the program, so memory grows with the number of distinct tenants.
+/// - The target is only rendered if the record is going to be logged.
+#[macro_export]
+macro_rules! log_tenant {
+    ($level:ident, $tenant:expr, $($arg:tt)+) => {{
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) {
+            let target = $crate::__private_api::tenant_target(&$tenant);
+            let fn_name = $crate::function_name!();
+            $crate::__private_api::emit(
+                level,
+                format_args!($($arg)+),
+                &fn_name,
+                &(&*target, module_path!(), file!(), line!()),
+            );
+        }
+    }};
+}
+
+/// Logs a trace message with the target `tenant::<tenant>`; see [`log_tenant!`](crate::log_tenant).
+#[macro_export]
+macro_rules! trace_tenant {
+    ($tenant:expr, $($arg:tt)+) => {
+        $crate::log_tenant!(trace, $tenant, $($arg)+)
+    };
+}
+
+/// Logs a debug message with the target `tenant::<tenant>`; see [`log_tenant!`](crate::log_tenant).
+#[macro_export]
+macro_rules! debug_tenant {
+    ($tenant:expr, $($arg:tt)+) => {
+        $crate::log_tenant!(debug, $tenant, $($arg)+)
+    };
+}
+
+/// Logs an info message with the target `tenant::<tenant>`; see [`log_tenant!`](crate::log_tenant).
+#[macro_export]
+macro_rules! info_tenant {
+    ($tenant:expr, $($arg:tt)+) => {
+        $crate::log_tenant!(info, $tenant, $($arg)+)
+    };
+}
+
+/// Logs a warning with the target `tenant::<tenant>`; see [`log_tenant!`](crate::log_tenant).
+#[macro_export]
+macro_rules! warn_tenant {
+    ($tenant:expr, $($arg:tt)+) => {
+        $crate::log_tenant!(warn, $tenant, $($arg)+)
+    };
+}
+
+/// Logs an error with the target `tenant::<tenant>`; see [`log_tenant!`](crate::log_tenant).
+#[macro_export]
+macro_rules! error_tenant {
+    ($tenant:expr, $($arg:tt)+) => {
+        $crate::log_tenant!(error, $tenant, $($arg)+)
+    };
+}
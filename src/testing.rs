@@ -0,0 +1,193 @@
+//! An in-memory logger for asserting on what the macros logged.
+
+use std::fmt::Write;
+use std::sync::{Mutex, MutexGuard};
+
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+/// A record captured by [`CapturingLogger`], split into the fields this crate logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedRecord {
+    /// The record's level.
+    pub level: Level,
+    /// The record's target, normally the module path of the call site.
+    pub target: String,
+    /// The message, without the function-name suffix.
+    pub message: String,
+    /// The name of the function that logged the record, or `None` if it carried none, e.g.
+    /// because the name is on the suffix denylist or the record was logged by another crate.
+    /// It is read from the `function` field with the `kv` feature, and otherwise from the
+    /// default `[fn name]` suffix; custom suffix templates are not recognized.
+    pub fn_name: Option<String>,
+    /// The source file of the call site.
+    pub file: Option<String>,
+    /// The source line of the call site.
+    pub line: Option<u32>,
+}
+
+impl CapturedRecord {
+    fn from_record(record: &Record) -> Self {
+        let text = record.args().to_string();
+        #[cfg(feature = "kv")]
+        let (message, fn_name) = (
+            text,
+            record
+                .key_values()
+                .get(log::kv::Key::from_str("function"))
+                .map(|value| value.to_string()),
+        );
+        #[cfg(not(feature = "kv"))]
+        let (message, fn_name) = match split_fn_suffix(&text) {
+            Some((message, fn_name)) => (message.to_owned(), Some(fn_name.to_owned())),
+            None => (text, None),
+        };
+
+        Self {
+            level: record.level(),
+            target: record.target().to_owned(),
+            message,
+            fn_name,
+            file: record.file().map(str::to_owned),
+            line: record.line(),
+        }
+    }
+}
+
+/// Splits `message [fn name]` into the message and the name.
+#[cfg(not(feature = "kv"))]
+fn split_fn_suffix(text: &str) -> Option<(&str, &str)> {
+    let rest = text.strip_suffix(']')?;
+    let start = rest.rfind(" [fn ")?;
+    let fn_name = &rest[start + " [fn ".len()..];
+    Some((&rest[..start], fn_name))
+}
+
+/// A `log::Log` implementation that keeps every record in memory as a [`CapturedRecord`].
+///
+/// # Example
+#[cfg_attr(feature = "defmt", doc = "```ignore")]
+#[cfg_attr(not(feature = "defmt"), doc = "```rust")]
+/// # use tacky_borders_logger::{info, testing::install_capturing_logger};
+/// fn load_borders() {
+///     info!("loaded {} borders", 3);
+/// }
+///
+/// let logger = install_capturing_logger().unwrap();
+/// load_borders();
+///
+/// let records = logger.take();
+/// assert_eq!(records[0].message, "loaded 3 borders");
+/// assert_eq!(records[0].fn_name.as_deref(), Some("load_borders"));
+/// ```
+#[derive(Debug, Default)]
+pub struct CapturingLogger {
+    records: Mutex<Vec<CapturedRecord>>,
+}
+
+impl CapturingLogger {
+    /// Creates an empty logger. It only captures once installed with `log::set_logger`.
+    pub const fn new() -> Self {
+        Self {
+            records: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns a copy of the records captured so far.
+    pub fn records(&self) -> Vec<CapturedRecord> {
+        self.lock().clone()
+    }
+
+    /// Returns the records captured so far and clears them.
+    pub fn take(&self) -> Vec<CapturedRecord> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    /// Discards the records captured so far.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<CapturedRecord>> {
+        self.records.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Log for CapturingLogger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.lock().push(CapturedRecord::from_record(record));
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs a global [`CapturingLogger`] and enables every level.
+///
+/// # Errors
+/// Fails if a logger has already been installed, as `log` only allows one per process.
+pub fn install_capturing_logger() -> Result<&'static CapturingLogger, SetLoggerError> {
+    static LOGGER: CapturingLogger = CapturingLogger::new();
+    log::set_logger(&LOGGER)?;
+    log::set_max_level(LevelFilter::Trace);
+    Ok(&LOGGER)
+}
+
+/// Panics, listing them, if any of `lines` is at `level` or more severe; for
+/// [`assert_no_errors!`](crate::assert_no_errors).
+#[doc(hidden)]
+#[track_caller]
+pub fn __assert_no_records_at(lines: Vec<(Level, String)>, level: Level) {
+    let mut offending = String::new();
+    let mut count = 0;
+    for (line_level, line) in lines.iter().filter(|(line_level, _)| *line_level <= level) {
+        let _ = write!(offending, "\n  {line_level:<5} {line}");
+        count += 1;
+    }
+    if count > 0 {
+        panic!("{count} record(s) at {level} level or above were logged:{offending}");
+    }
+}
+
+/// Macro to assert that a block logs nothing at error level, or at another given level
+/// or above.
+///
+/// This macro runs the block while capturing what it logs on the current thread, like
+/// [`capture_logs!`](crate::capture_logs), and panics, listing the offending lines, if
+/// any record is at error level or, with a leading level such as `warn`, at that level or
+/// above. It evaluates to the value of the block. Code that swallows errors by logging them
+/// thus fails the test instead of passing silently.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{assert_no_errors, debug};
+/// fn apply_theme() -> bool {
+///     debug!("theme applied");
+///     true
+/// }
+///
+/// let applied = assert_no_errors!({ apply_theme() });
+/// assert!(applied);
+/// assert_no_errors!(warn, { apply_theme() });
+/// ```
+/// # Notes
+/// - The captured records do not reach the logger.
+/// - Only the current thread is captured; records logged by threads the block spawns are not
+///   checked.
+#[macro_export]
+macro_rules! assert_no_errors {
+    ($body:block) => {
+        $crate::assert_no_errors!(error, $body)
+    };
+    ($level:ident, $body:block) => {{
+        let capture = $crate::__private_api::Capture::__start();
+        let value = $body;
+        $crate::testing::__assert_no_records_at(
+            capture.__finish_with_levels(),
+            $crate::__level!($level),
+        );
+        value
+    }};
+}
@@ -0,0 +1,99 @@
+//! Racing futures against a deadline and logging the outcome.
+
+use std::time::Duration;
+
+use log::Level;
+
+use crate::__private_api::{self, Elapsed, Location};
+use crate::clock;
+
+/// Awaits `future` for at most `timeout`, and logs whether it completed in time, for
+/// [`log_timeout!`](crate::log_timeout).
+#[doc(hidden)]
+#[allow(clippy::too_many_arguments)]
+pub async fn log_timeout<F: Future>(
+    future: F,
+    timeout: Duration,
+    level: Level,
+    timeout_level: Level,
+    label: String,
+    fn_name: String,
+    loc: Location<'static>,
+) -> Result<F::Output, tokio::time::error::Elapsed> {
+    let start = clock::now();
+    let result = tokio::time::timeout(timeout, future).await;
+    let elapsed = clock::elapsed_since(start);
+    match &result {
+        Ok(_) if __private_api::enabled(level) => __private_api::emit_timed(
+            level,
+            format_args!("{label} OK in {}", Elapsed(elapsed)),
+            &fn_name,
+            &loc,
+            elapsed,
+        ),
+        Err(_) if __private_api::enabled(timeout_level) => __private_api::emit_timed(
+            timeout_level,
+            format_args!("{label} timed out after {}", Elapsed(timeout)),
+            &fn_name,
+            &loc,
+            elapsed,
+        ),
+        _ => {}
+    }
+    result
+}
+
+/// Macro to await a future with a timeout and log whether it completed in time.
+///
+/// This macro returns a future that races the given one against `timeout`. If it completes
+/// first, `<label> OK in <elapsed>` is logged at the given level and its output is returned
+/// as `Ok`; if the deadline is hit first, the future is dropped, `<label> timed out after
+/// <timeout>` is logged at warn level, or the level given with `on_timeout:`, and
+/// `Err(tokio::time::error::Elapsed)` is returned. Both lines carry the name of the function
+/// where the macro is invoked.
+///
+/// # Example
+/// ```rust
+/// # use std::time::Duration;
+/// # use tacky_borders_logger::log_timeout;
+/// async fn fetch() -> Option<u32> {
+///     log_timeout!(info, Duration::from_secs(5), "fetch", async { 42 }).await.ok()
+///     // Logs: "fetch OK in 3.1µs [fn fetch]"
+/// }
+///
+/// async fn hang() {
+///     let pending = std::future::pending::<()>();
+///     let _ = log_timeout!(info, Duration::from_millis(10), "hang", pending, on_timeout: error)
+///         .await;
+///     // Logs at error level: "hang timed out after 10ms [fn hang]"
+/// }
+/// # tokio::runtime::Builder::new_current_thread()
+/// #     .enable_time()
+/// #     .build()
+/// #     .unwrap()
+/// #     .block_on(async {
+/// #         fetch().await;
+/// #         hang().await;
+/// #     });
+/// ```
+/// # Notes
+/// - Available with the `tokio` feature, and must run in a `tokio` runtime with the time
+///   driver enabled.
+/// - `timeout` is a `Duration`, and the label can be any `Display` value.
+#[macro_export]
+macro_rules! log_timeout {
+    ($level:ident, $timeout:expr, $label:expr, $future:expr $(,)?) => {
+        $crate::log_timeout!($level, $timeout, $label, $future, on_timeout: warn)
+    };
+    ($level:ident, $timeout:expr, $label:expr, $future:expr, on_timeout: $timeout_level:ident $(,)?) => {
+        $crate::__private_api::log_timeout(
+            $future,
+            $timeout,
+            $crate::__level!($level),
+            $crate::__level!($timeout_level),
+            ::std::string::ToString::to_string(&$label),
+            $crate::function_name!(),
+            (module_path!(), module_path!(), file!(), line!()),
+        )
+    };
+}
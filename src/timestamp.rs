@@ -0,0 +1,145 @@
+//! Wall-clock timestamps rendered by the crate itself, independently of the logger.
+
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The time zone in which [`set_include_timestamp`](crate::set_include_timestamp) renders
+/// timestamps; set with [`set_timestamp_timezone`](crate::set_timestamp_timezone).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Timezone {
+    /// Coordinated Universal Time, rendered with a `Z` suffix. This is the default, so logs
+    /// from hosts in different regions line up.
+    #[default]
+    Utc,
+    /// The host's time zone, with its offset at the time of each record.
+    Local,
+    /// A fixed offset from UTC, in seconds east of Greenwich.
+    Fixed(i32),
+    /// A zone from the IANA time zone database, such as `Europe/Berlin`, following its
+    /// daylight saving rules.
+    #[cfg(feature = "chrono-tz")]
+    Named(chrono_tz::Tz),
+}
+
+impl Timezone {
+    /// Returns the offset from UTC at `secs` seconds since the epoch, in seconds.
+    fn offset_at(self, secs: i64) -> i32 {
+        match self {
+            Timezone::Utc => 0,
+            Timezone::Local => local_offset(secs),
+            Timezone::Fixed(offset) => offset,
+            #[cfg(feature = "chrono-tz")]
+            Timezone::Named(tz) => {
+                use chrono::{Offset, TimeZone};
+                tz.offset_from_utc_datetime(
+                    &chrono::DateTime::from_timestamp(secs, 0)
+                        .unwrap_or_default()
+                        .naive_utc(),
+                )
+                .fix()
+                .local_minus_utc()
+            }
+        }
+    }
+}
+
+/// Renders a point in time as an RFC 3339 timestamp in a time zone, e.g.
+/// `2025-01-31T08:15:00.123Z` or `2025-01-31T09:15:00.123+01:00`.
+pub(crate) struct Timestamp {
+    pub(crate) time: SystemTime,
+    pub(crate) timezone: Timezone,
+    /// Whether to include milliseconds.
+    pub(crate) millis: bool,
+}
+
+impl Timestamp {
+    /// The current time, in UTC and to the second.
+    pub(crate) fn utc_now() -> Self {
+        Self {
+            time: crate::clock::system_now(),
+            timezone: Timezone::Utc,
+            millis: false,
+        }
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let since_epoch = self.time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let utc_secs = since_epoch.as_secs() as i64;
+        let offset = self.timezone.offset_at(utc_secs);
+        let secs = utc_secs + i64::from(offset);
+        let (days, secs_of_day) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+
+        // Civil date from days since the epoch, after Howard Hinnant's `civil_from_days`.
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        write!(
+            f,
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}",
+            secs_of_day / 3_600,
+            secs_of_day / 60 % 60,
+            secs_of_day % 60
+        )?;
+        if self.millis {
+            write!(f, ".{:03}", since_epoch.subsec_millis())?;
+        }
+        match offset {
+            0 => f.write_str("Z"),
+            offset => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let minutes = offset.unsigned_abs() / 60;
+                write!(f, "{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+            }
+        }
+    }
+}
+
+/// Returns the host's offset from UTC at `secs` seconds since the epoch, or 0 if it cannot be
+/// determined.
+fn local_offset(secs: i64) -> i32 {
+    #[cfg(unix)]
+    {
+        let time = secs as libc::time_t;
+        // SAFETY: `tm` is plain data, for which all zeroes is a valid value.
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        // SAFETY: both pointers are valid for the duration of the call; `localtime_r`, unlike
+        // `localtime`, does not share its result between threads.
+        match unsafe { libc::localtime_r(&time, &mut tm) } {
+            ptr if ptr.is_null() => 0,
+            _ => tm.tm_gmtoff as i32,
+        }
+    }
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::System::Time::{GetTimeZoneInformation, TIME_ZONE_INFORMATION};
+
+        let _ = secs;
+        // SAFETY: `TIME_ZONE_INFORMATION` is plain data, for which all zeroes is a valid value.
+        let mut info: TIME_ZONE_INFORMATION = unsafe { std::mem::zeroed() };
+        // SAFETY: the pointer is valid for writes for the duration of the call.
+        let bias = match unsafe { GetTimeZoneInformation(&mut info) } {
+            // `TIME_ZONE_ID_DAYLIGHT`: daylight saving time is in effect.
+            2 => info.Bias + info.DaylightBias,
+            // `TIME_ZONE_ID_INVALID`.
+            u32::MAX => 0,
+            _ => info.Bias + info.StandardBias,
+        };
+        // The bias is in minutes, and west of Greenwich is positive.
+        -bias * 60
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = secs;
+        0
+    }
+}
@@ -0,0 +1,641 @@
+//! Macros and types for logging how long an operation took.
+
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::OnceLock;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use log::Level;
+
+use crate::__private_api::{self, Elapsed, Location};
+use crate::clock;
+
+/// Like `__log!`, attaching `elapsed` to the record as a `duration_ms` field with the `kv`
+/// feature.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_timed {
+    ($level:expr, $elapsed:expr, $($arg:tt)+) => ({
+        let level = $level;
+        if $crate::__private_api::enabled(level) {
+            let fn_name = $crate::function_name!();
+            $crate::__private_api::emit_timed(
+                level,
+                format_args!($($arg)+),
+                &fn_name,
+                &(module_path!(), module_path!(), file!(), line!()),
+                $elapsed,
+            );
+        }
+    });
+}
+
+/// Macro to time a block and log how long it took.
+///
+/// This macro runs the block, logs `<label> took <elapsed>` at the given level with
+/// the name of the function where it is invoked, and returns the block's value.
+/// Elapsed times of a minute or more are written as `1h 23m 45s`.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_duration;
+/// fn load_config() -> String {
+///     log_duration!(info, "read config", {
+///         String::from("[borders]")
+///     })
+///     // Logs: "read config took 1.2µs [fn load_config]"
+/// }
+/// ```
+/// # Notes
+/// - The block cannot `.await`; use [`log_duration_async!`](crate::log_duration_async) for futures.
+#[macro_export]
+macro_rules! log_duration {
+    ($level:ident, $label:expr, $block:expr $(,)?) => {{
+        let start = $crate::__private_api::now();
+        let value = $block;
+        let elapsed = $crate::__private_api::elapsed_since(start);
+        $crate::__log_timed!(
+            $crate::__level!($level),
+            elapsed,
+            "{} took {}",
+            $label,
+            $crate::__private_api::Elapsed(elapsed)
+        );
+        value
+    }};
+}
+
+/// Macro to time a fallible block and log whether it succeeded and how long it took.
+///
+/// This macro runs the block, which must evaluate to a `Result` whose error implements
+/// `Display`. On `Ok` it logs `<label> OK in <elapsed>` at the given level; on `Err` it logs
+/// `<label> FAILED in <elapsed>: <error>` at error level. Both lines end with the name of the
+/// function where it is invoked, and the `Result` is returned unchanged.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_op;
+/// fn save_user(name: &str) -> Result<(), String> {
+///     log_op!(info, "save user", {
+///         if name.is_empty() { Err(String::from("empty name")) } else { Ok(()) }
+///     })
+///     // Logs: "save user OK in 12ms [fn save_user]"
+///     // or:   "save user FAILED in 8ms: empty name [fn save_user]"
+/// }
+/// ```
+#[macro_export]
+macro_rules! log_op {
+    ($level:ident, $label:expr, $block:expr $(,)?) => {{
+        let start = $crate::__private_api::now();
+        let result = $block;
+        let elapsed = $crate::__private_api::elapsed_since(start);
+        $crate::__log_outcome!($level, $label, elapsed, &result);
+        result
+    }};
+}
+
+/// Logs the outcome of a timed operation for [`log_op!`](crate::log_op) and
+/// `log_op_metric!`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_outcome {
+    ($level:ident, $label:expr, $elapsed:expr, $result:expr) => {
+        match $result {
+            ::std::result::Result::Ok(_) => $crate::__log_timed!(
+                $crate::__level!($level),
+                $elapsed,
+                "{} OK in {}",
+                $label,
+                $crate::__private_api::Elapsed($elapsed)
+            ),
+            ::std::result::Result::Err(err) => $crate::__log_timed!(
+                $crate::__private_api::log::Level::Error,
+                $elapsed,
+                "{} FAILED in {}: {}",
+                $label,
+                $crate::__private_api::Elapsed($elapsed),
+                err
+            ),
+        }
+    };
+}
+
+/// Macro to time a block and log a warning only if it was slow.
+///
+/// This macro runs the block, and if it took longer than `threshold`, logs
+/// `<label> took <elapsed> (threshold <threshold>)` at warn level with the name of the
+/// function where it is invoked. Fast runs log nothing. The block's value is returned
+/// either way.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::warn_if_slow;
+/// use std::time::Duration;
+///
+/// fn query_borders() -> Vec<u32> {
+///     warn_if_slow!(Duration::from_millis(100), "db query", {
+///         vec![1, 2, 3]
+///     })
+///     // Logs, only if the query was slow: "db query took 153ms (threshold 100ms) [fn query_borders]"
+/// }
+/// ```
+#[macro_export]
+macro_rules! warn_if_slow {
+    ($threshold:expr, $label:expr, $block:expr $(,)?) => {{
+        let threshold: ::std::time::Duration = $threshold;
+        let start = $crate::__private_api::now();
+        let value = $block;
+        let elapsed = $crate::__private_api::elapsed_since(start);
+        if elapsed > threshold {
+            $crate::__log_timed!(
+                $crate::__private_api::log::Level::Warn,
+                elapsed,
+                "{} took {} (threshold {:?})",
+                $label,
+                $crate::__private_api::Elapsed(elapsed),
+                threshold
+            );
+        }
+        value
+    }};
+}
+
+/// Macro to log the time between two `Instant`s.
+///
+/// This macro logs `<label> took <elapsed>` at the given level with the name of the
+/// function where it is invoked, for instants the caller captured itself, e.g. in
+/// different functions. If `end` is earlier than `start`, it logs a warning naming the
+/// reversed instants instead of panicking.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::elapsed_between;
+/// use std::time::Instant;
+///
+/// fn validate(parsed_at: Instant) {
+///     // ... validate ...
+///     elapsed_between!(info, "parse->validate", parsed_at, Instant::now());
+///     // Logs: "parse->validate took 2.1ms [fn validate]"
+/// }
+/// ```
+#[macro_export]
+macro_rules! elapsed_between {
+    ($level:ident, $label:expr, $start:expr, $end:expr $(,)?) => {{
+        let start: ::std::time::Instant = $start;
+        let end: ::std::time::Instant = $end;
+        match end.checked_duration_since(start) {
+            ::std::option::Option::Some(elapsed) => $crate::__log_timed!(
+                $crate::__level!($level),
+                elapsed,
+                "{} took {}",
+                $label,
+                $crate::__private_api::Elapsed(elapsed)
+            ),
+            ::std::option::Option::None => $crate::__log!(
+                $crate::__private_api::log::Level::Warn,
+                "{}: end is {} before start, instants reversed?",
+                $label,
+                $crate::__private_api::Elapsed(start - end)
+            ),
+        }
+    }};
+}
+
+/// Macro to log how much time is left until a deadline.
+///
+/// This macro logs `<message>: <remaining> until deadline` at the given level with the name
+/// of the function where it is invoked. If the deadline has passed, it logs
+/// `<message>: deadline EXCEEDED by <overrun>` at warn level instead. A call exactly at the
+/// deadline counts as on time, logging `0ns until deadline`. The message is formatted like a
+/// log message.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_deadline;
+/// use std::time::{Duration, Instant};
+///
+/// fn process(deadline: Instant) {
+///     // ... process ...
+///     log_deadline!(debug, deadline: deadline, "processing");
+///     // Logs: "processing: 230ms until deadline [fn process]"
+///     // or, once it has passed: "processing: deadline EXCEEDED by 50ms [fn process]"
+/// }
+/// # process(Instant::now() + Duration::from_millis(250));
+/// ```
+/// # Notes
+/// - The current time is read through the clock set with
+///   [`set_clock`](crate::set_clock), if any.
+#[macro_export]
+macro_rules! log_deadline {
+    ($level:ident, deadline: $deadline:expr, $($arg:tt)+) => {{
+        let deadline: ::std::time::Instant = $deadline;
+        let now = $crate::__private_api::now();
+        match deadline.checked_duration_since(now) {
+            ::std::option::Option::Some(remaining) => $crate::__log!(
+                $crate::__level!($level),
+                "{}: {} until deadline",
+                format_args!($($arg)+),
+                $crate::__private_api::Elapsed(remaining)
+            ),
+            ::std::option::Option::None => $crate::__log!(
+                $crate::__private_api::log::Level::Warn,
+                "{}: deadline EXCEEDED by {}",
+                format_args!($($arg)+),
+                $crate::__private_api::Elapsed(now - deadline)
+            ),
+        }
+    }};
+}
+
+/// Macro to log how long an operation took at a level that depends on how slow it was.
+///
+/// This macro logs `<message> took <elapsed>` with the name of the function where it is
+/// invoked: at info level if `elapsed` is below `warn_at`, at warn level with
+/// `(over <warn_at>)` appended if it is below `error_at`, and at error level with
+/// `(over <error_at>)` appended otherwise. This gives tiered latency alerting in one call.
+/// The message is formatted like a log message.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_deadline_severity;
+/// use std::time::{Duration, Instant};
+///
+/// fn handle_request() {
+///     let start = Instant::now();
+///     // ... handle the request ...
+///     log_deadline_severity!(
+///         start.elapsed(),
+///         warn_at: Duration::from_millis(100),
+///         error_at: Duration::from_secs(1),
+///         "request"
+///     );
+///     // Logs: "request took 12ms [fn handle_request]"
+///     // or, at warn level: "request took 230ms (over 100ms) [fn handle_request]"
+///     // or, at error level: "request took 1.5s (over 1s) [fn handle_request]"
+/// }
+/// # handle_request();
+/// ```
+/// # Notes
+/// - A time exactly at a threshold counts as over it.
+#[macro_export]
+macro_rules! log_deadline_severity {
+    ($elapsed:expr, warn_at: $warn_at:expr, error_at: $error_at:expr, $($arg:tt)+) => {{
+        let elapsed: ::std::time::Duration = $elapsed;
+        let (warn_at, error_at): (::std::time::Duration, ::std::time::Duration) =
+            ($warn_at, $error_at);
+        if elapsed < warn_at {
+            $crate::__log_timed!(
+                $crate::__private_api::log::Level::Info,
+                elapsed,
+                "{} took {}",
+                format_args!($($arg)+),
+                $crate::__private_api::Elapsed(elapsed)
+            );
+        } else {
+            let (level, threshold) = if elapsed < error_at {
+                ($crate::__private_api::log::Level::Warn, warn_at)
+            } else {
+                ($crate::__private_api::log::Level::Error, error_at)
+            };
+            $crate::__log_timed!(
+                level,
+                elapsed,
+                "{} took {} (over {})",
+                format_args!($($arg)+),
+                $crate::__private_api::Elapsed(elapsed),
+                $crate::__private_api::Elapsed(threshold)
+            );
+        }
+    }};
+}
+
+/// Macro to log how much data an I/O operation moved and at what rate.
+///
+/// This macro logs `<label>: <bytes> in <elapsed> (<rate>/s)` at the given level with
+/// the name of the function where it is invoked. Sizes and rates use binary prefixes.
+/// `bytes` may be any integer; `elapsed` is a `Duration`, such as one measured with
+/// `Instant::elapsed`. A zero duration logs `(rate unknown)` instead of a rate.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_throughput;
+/// use std::time::Instant;
+///
+/// fn upload(data: &[u8]) {
+///     let start = Instant::now();
+///     // ... send data ...
+///     log_throughput!(info, "upload", data.len(), start.elapsed());
+///     // Logs: "upload: 4.2 MiB in 1.20s (3.5 MiB/s) [fn upload]"
+/// }
+/// ```
+#[macro_export]
+macro_rules! log_throughput {
+    ($level:ident, $label:expr, $bytes:expr, $elapsed:expr $(,)?) => {{
+        let elapsed: ::std::time::Duration = $elapsed;
+        $crate::__log_timed!(
+            $crate::__level!($level),
+            elapsed,
+            "{}: {}",
+            $label,
+            $crate::__private_api::Throughput {
+                bytes: $bytes as u64,
+                elapsed,
+            }
+        )
+    }};
+}
+
+/// Macro to log a message and then sleep, to perturb timing while reproducing races.
+///
+/// This macro logs the message at the given level with the name of the function where
+/// it is invoked, then blocks the current thread for `duration`. The sleep only happens
+/// when this crate's `debug-sleep` feature is enabled; otherwise the macro is a plain log
+/// statement, so it is safe to leave in code that ships.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_sleep;
+/// use std::time::Duration;
+///
+/// fn swap_buffers() {
+///     log_sleep!(debug, Duration::from_millis(50), "before critical section");
+///     // Logs: "before critical section [fn swap_buffers]", then sleeps with `debug-sleep`
+/// }
+/// ```
+#[macro_export]
+macro_rules! log_sleep {
+    ($level:ident, $duration:expr, $($arg:tt)+) => {{
+        let duration: ::std::time::Duration = $duration;
+        $crate::__log!($crate::__level!($level), $($arg)+);
+        $crate::__private_api::debug_sleep(duration);
+    }};
+}
+
+static PROGRAM_START: OnceLock<Instant> = OnceLock::new();
+
+pub fn mark_start() {
+    PROGRAM_START.get_or_init(clock::now);
+}
+
+pub fn since_start() -> Option<Duration> {
+    PROGRAM_START.get().copied().map(clock::elapsed_since)
+}
+
+/// Macro to record the start of the program, for [`phase!`](crate::phase).
+///
+/// Call it once, as early as possible in `main`. Later calls keep the first start time.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::mark_start;
+/// fn main() {
+///     mark_start!();
+///     // ... startup ...
+/// }
+/// ```
+#[macro_export]
+macro_rules! mark_start {
+    () => {
+        $crate::__private_api::mark_start()
+    };
+}
+
+/// Macro to log a startup phase with the time elapsed since [`mark_start!`](crate::mark_start).
+///
+/// This macro logs `<label> (+<elapsed> since start)` at the given level with the name
+/// of the function where it is invoked, so a series of calls forms a startup timeline.
+/// If `mark_start!()` has not been called yet, it logs the label at warn level with a
+/// reminder instead.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::{mark_start, phase};
+/// fn main() {
+///     mark_start!();
+///     phase!(info, "config loaded");
+///     // Logs: "config loaded (+12.3ms since start) [fn main]"
+/// }
+/// ```
+#[macro_export]
+macro_rules! phase {
+    ($level:ident, $label:expr $(,)?) => {
+        match $crate::__private_api::since_start() {
+            ::std::option::Option::Some(elapsed) => $crate::__log_timed!(
+                $crate::__level!($level),
+                elapsed,
+                "{} (+{} since start)",
+                $label,
+                $crate::__private_api::Elapsed(elapsed)
+            ),
+            ::std::option::Option::None => $crate::__log!(
+                $crate::__private_api::log::Level::Warn,
+                "{} (no start time, call mark_start!() first)",
+                $label
+            ),
+        }
+    };
+}
+
+thread_local! {
+    static LAST_RECORD: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Returns the time since the previous call on this thread, or `None` on the first call, for
+/// [`set_include_delta`](crate::set_include_delta).
+pub(crate) fn since_last_record() -> Option<Duration> {
+    let now = clock::now();
+    LAST_RECORD
+        .replace(Some(now))
+        .map(|last| now.saturating_duration_since(last))
+}
+
+/// Returns the CPU time consumed by the whole process so far, or `None` where the platform
+/// offers no way to read it.
+pub fn process_cpu_time() -> Option<Duration> {
+    #[cfg(unix)]
+    {
+        let mut time = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // SAFETY: `time` is a valid, writable `timespec`.
+        let result = unsafe { libc::clock_gettime(libc::CLOCK_PROCESS_CPUTIME_ID, &mut time) };
+        (result == 0).then(|| Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+    }
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Foundation::FILETIME;
+        use windows_sys::Win32::System::Threading::{GetCurrentProcess, GetProcessTimes};
+
+        let zero = FILETIME {
+            dwLowDateTime: 0,
+            dwHighDateTime: 0,
+        };
+        let (mut creation, mut exit, mut kernel, mut user) = (zero, zero, zero, zero);
+        // SAFETY: the pseudo-handle of the current process is always valid, and every pointer
+        // refers to a writable `FILETIME`.
+        let result = unsafe {
+            GetProcessTimes(
+                GetCurrentProcess(),
+                &mut creation,
+                &mut exit,
+                &mut kernel,
+                &mut user,
+            )
+        };
+        // `FILETIME`s count 100 ns intervals.
+        let ticks =
+            |time: FILETIME| u64::from(time.dwHighDateTime) << 32 | u64::from(time.dwLowDateTime);
+        (result != 0).then(|| Duration::from_nanos((ticks(kernel) + ticks(user)) * 100))
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        None
+    }
+}
+
+/// Macro to time a block and log both its wall-clock and CPU time.
+///
+/// This macro runs the block and logs `<label> took <elapsed> (cpu <cpu>)` at the given
+/// level with the name of the function where it is invoked, then returns the block's
+/// value. A CPU time close to the wall-clock time means the block was compute-bound; a
+/// much lower one means it mostly waited. Where the process CPU time cannot be read, only
+/// the wall-clock time is logged.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_duration_cpu;
+/// fn render() -> u64 {
+///     log_duration_cpu!(info, "render", {
+///         (0..1_000u64).sum()
+///     })
+///     // Logs: "render took 1.2ms (cpu 1.1ms) [fn render]"
+/// }
+/// ```
+/// # Notes
+/// - CPU time is measured for the whole process, so other busy threads inflate it.
+#[macro_export]
+macro_rules! log_duration_cpu {
+    ($level:ident, $label:expr, $block:expr $(,)?) => {{
+        let cpu_start = $crate::__private_api::process_cpu_time();
+        let start = $crate::__private_api::now();
+        let value = $block;
+        let elapsed = $crate::__private_api::elapsed_since(start);
+        let cpu_end = $crate::__private_api::process_cpu_time();
+        match (cpu_start, cpu_end) {
+            (::std::option::Option::Some(cpu_start), ::std::option::Option::Some(cpu_end)) => {
+                $crate::__log_timed!(
+                    $crate::__level!($level),
+                    elapsed,
+                    "{} took {} (cpu {})",
+                    $label,
+                    $crate::__private_api::Elapsed(elapsed),
+                    $crate::__private_api::Elapsed(cpu_end.saturating_sub(cpu_start))
+                )
+            }
+            _ => $crate::__log_timed!(
+                $crate::__level!($level),
+                elapsed,
+                "{} took {}",
+                $label,
+                $crate::__private_api::Elapsed(elapsed)
+            ),
+        }
+        value
+    }};
+}
+
+/// Macro to time a future and log how long it took once it completes.
+///
+/// This macro wraps the future in a [`TimedFuture`](crate::TimedFuture), which measures
+/// from the first poll to completion. When the future resolves, it logs the wall-clock
+/// duration along with the time actually spent being polled, and the name of the
+/// function where the macro is invoked. Awaiting it yields the future's output.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::log_duration_async;
+/// async fn fetch_theme() -> u32 {
+///     log_duration_async!(info, "fetch", async { 42 }).await
+///     // Logs: "fetch took 3.1µs (busy 2.0µs) [fn fetch_theme]"
+/// }
+/// ```
+/// # Notes
+/// - Wall-clock time includes time spent waiting to be woken; busy time only counts polls.
+#[macro_export]
+macro_rules! log_duration_async {
+    ($level:ident, $label:expr, $future:expr $(,)?) => {
+        $crate::TimedFuture::__new(
+            $future,
+            $crate::__level!($level),
+            ::std::string::ToString::to_string(&$label),
+            $crate::function_name!(),
+            (module_path!(), module_path!(), file!(), line!()),
+        )
+    };
+}
+
+/// A future that logs its execution time on completion, created by
+/// [`log_duration_async!`](crate::log_duration_async).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct TimedFuture<F> {
+    future: Pin<Box<F>>,
+    level: Level,
+    label: String,
+    fn_name: String,
+    loc: Location<'static>,
+    start: Option<Instant>,
+    busy: Duration,
+}
+
+impl<F: Future> TimedFuture<F> {
+    #[doc(hidden)]
+    pub fn __new(
+        future: F,
+        level: Level,
+        label: String,
+        fn_name: String,
+        loc: Location<'static>,
+    ) -> Self {
+        Self {
+            future: Box::pin(future),
+            level,
+            label,
+            fn_name,
+            loc,
+            start: None,
+            busy: Duration::ZERO,
+        }
+    }
+}
+
+impl<F: Future> Future for TimedFuture<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let poll_start = clock::now();
+        let start = *self.start.get_or_insert(poll_start);
+
+        let poll = self.future.as_mut().poll(cx);
+        self.busy += clock::elapsed_since(poll_start);
+
+        if poll.is_ready() && __private_api::enabled(self.level) {
+            let elapsed = clock::elapsed_since(start);
+            __private_api::emit_timed(
+                self.level,
+                format_args!(
+                    "{} took {} (busy {})",
+                    self.label,
+                    Elapsed(elapsed),
+                    Elapsed(self.busy)
+                ),
+                &self.fn_name,
+                &self.loc,
+                elapsed,
+            );
+        }
+        poll
+    }
+}
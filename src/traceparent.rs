@@ -0,0 +1,107 @@
+//! W3C Trace Context ids attached to the records logged while handling a traced request.
+
+use std::cell::Cell;
+use std::fmt;
+
+use crate::config;
+
+thread_local! {
+    /// The trace id and parent span id of the `traceparent` set on this thread, if any.
+    static TRACE_CONTEXT: Cell<Option<(u128, u64)>> = const { Cell::new(None) };
+}
+
+/// Parses `len` lowercase hex digits, which W3C Trace Context requires for its fields.
+fn parse_hex(field: &str, len: usize) -> Option<u128> {
+    if field.len() != len
+        || !field
+            .bytes()
+            .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    {
+        return None;
+    }
+    u128::from_str_radix(field, 16).ok()
+}
+
+/// Parses a `traceparent` header into its trace id and parent span id.
+fn parse(header: &str) -> Option<(u128, u64)> {
+    // Later versions may append fields after the first four, which version 00 ends with.
+    let (known, extra) = header.trim().split_at_checked(55)?;
+    let mut fields = known.split('-');
+    let (version, trace_id, span_id, flags) = (
+        fields.next()?,
+        fields.next()?,
+        fields.next()?,
+        fields.next()?,
+    );
+    if fields.next().is_some() {
+        return None;
+    }
+    match parse_hex(version, 2)? {
+        0xff => return None,
+        0x00 if !extra.is_empty() => return None,
+        _ if !extra.is_empty() && !extra.starts_with('-') => return None,
+        _ => {}
+    }
+    let trace_id = parse_hex(trace_id, 32)?;
+    let span_id = parse_hex(span_id, 16)? as u64;
+    parse_hex(flags, 2)?;
+    // All-zero ids are invalid.
+    (trace_id != 0 && span_id != 0).then_some((trace_id, span_id))
+}
+
+/// Sets the W3C Trace Context of the request being handled on this thread from its
+/// `traceparent` header, and returns whether the header was valid.
+///
+/// The header has the form `00-<trace-id>-<parent-id>-<flags>`, with a 32-digit trace id and
+/// a 16-digit parent span id in lowercase hex. While
+/// [`set_include_traceparent`](crate::set_include_traceparent) is enabled, every record logged
+/// on this thread afterwards carries `trace=<trace-id> span=<parent-id>`, so it can be joined
+/// with the spans of the distributed trace. A malformed header, including one with all-zero
+/// ids, clears the context instead, as if the request had none, so a previous request's ids
+/// are never reused.
+///
+/// # Example
+/// ```rust
+/// use tacky_borders_logger::{info, set_include_traceparent, set_traceparent};
+///
+/// set_include_traceparent(true);
+/// set_traceparent("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01");
+/// info!("handling request");
+/// // Logs: "handling request [fn main] trace=4bf92f3577b34da6a3ce929d0e0e4736
+/// //        span=00f067aa0ba902b7"
+/// ```
+/// # Notes
+/// - The context is kept per thread, like the tags of
+///   [`request_scope!`](crate::request_scope): set it again in tasks that may move between
+///   threads, and clear it with [`clear_traceparent`] once the request is handled.
+pub fn set_traceparent(header: &str) -> bool {
+    let parsed = parse(header);
+    TRACE_CONTEXT.set(parsed);
+    parsed.is_some()
+}
+
+/// Clears the W3C Trace Context set on this thread with [`set_traceparent`].
+///
+/// # Example
+/// ```rust
+/// tacky_borders_logger::clear_traceparent();
+/// ```
+pub fn clear_traceparent() {
+    TRACE_CONTEXT.set(None);
+}
+
+/// Renders the trace context set on this thread as ` trace=<trace-id> span=<span-id>`, if
+/// enabled and set.
+pub(crate) struct TraceText;
+
+impl fmt::Display for TraceText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !config::include_traceparent() {
+            return Ok(());
+        }
+        match TRACE_CONTEXT.get() {
+            Some((trace_id, span_id)) => write!(f, " trace={trace_id:032x} span={span_id:016x}"),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Logging gated on a verbosity level fixed when the program is compiled.
+
+/// Parses a verbosity level from the value of a compile-time environment variable, for
+/// defining the `VERBOSITY` constant read by [`cverbose!`](crate::cverbose).
+///
+/// Returns `default` if `value` is `None`, or is not a decimal number from 0 to 255. Being a
+/// `const fn`, it can be called with the result of `option_env!`.
+///
+/// # Example
+/// ```rust
+/// const VERBOSITY: u8 = tacky_borders_logger::parse_verbosity(option_env!("MY_LIB_VERBOSITY"), 1);
+/// ```
+pub const fn parse_verbosity(value: Option<&str>, default: u8) -> u8 {
+    let bytes = match value {
+        Some(value) if !value.is_empty() => value.as_bytes(),
+        _ => return default,
+    };
+    let mut level: u32 = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let digit = bytes[i];
+        if !digit.is_ascii_digit() {
+            return default;
+        }
+        level = level * 10 + (digit - b'0') as u32;
+        if level > u8::MAX as u32 {
+            return default;
+        }
+        i += 1;
+    }
+    level as u8
+}
+
+/// Macro to log a debug-level message only if a compile-time verbosity constant is high
+/// enough.
+///
+/// This macro logs like [`debug!`](crate::debug) when `level` is at most the `VERBOSITY`
+/// constant in scope at the call site, which must be a `u8`. The comparison is evaluated in
+/// a `const` block, so it is settled at compile time: when the threshold is too low, the
+/// branch is constant `false` and the optimizer removes the statement entirely, arguments
+/// and all. Unlike `log`'s max level, this lets each library pick its own threshold.
+///
+/// # Example
+/// ```rust
+/// # use tacky_borders_logger::cverbose;
+/// const VERBOSITY: u8 = tacky_borders_logger::parse_verbosity(option_env!("MY_LIB_VERBOSITY"), 1);
+///
+/// fn layout(width: u32) {
+///     cverbose!(1, "layout width={}", width);
+///     // Logs: "layout width=1920 [fn layout]"
+///     cverbose!(3, "layout cache dump");
+///     // Not compiled in unless built with MY_LIB_VERBOSITY=3 or higher.
+/// }
+/// ```
+/// # Notes
+/// - `VERBOSITY` is looked up where the macro is invoked, so it can be defined once at the
+///   crate root and imported, or differ between modules.
+/// - `cargo expand` shows each statement wrapped in `if const { level <= VERBOSITY }`; the
+///   check itself costs nothing at runtime, and in optimized builds the disabled statements
+///   leave no code behind.
+/// - Environment variables read with `option_env!` are fixed at build time; changing one
+///   requires rebuilding the crate that reads it.
+#[macro_export]
+macro_rules! cverbose {
+    ($level:expr, $($arg:tt)+) => {
+        if const { $level <= VERBOSITY } {
+            $crate::debug!($($arg)+);
+        }
+    };
+}
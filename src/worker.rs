@@ -0,0 +1,56 @@
+//! Logging which `rayon` worker thread a record comes from.
+
+use std::fmt;
+
+/// Renders the index of the current `rayon` worker thread, or `main` outside of a pool; for
+/// [`log_worker!`](crate::log_worker).
+#[doc(hidden)]
+pub struct Worker;
+
+impl fmt::Display for Worker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match rayon::current_thread_index() {
+            Some(index) => write!(f, "{index}"),
+            None => f.write_str("main"),
+        }
+    }
+}
+
+/// Macro to log a message with the index of the `rayon` worker thread logging it.
+///
+/// This macro logs the message followed by `worker=<index>`, where `index` is the
+/// position of the current thread in its `rayon` pool, or `worker=main` on a thread that
+/// is not a `rayon` worker, with the name of the function where it is invoked. Unlike
+/// thread names, worker indices are small and stable, so imbalance between workers is easy
+/// to spot.
+///
+/// # Example
+/// ```rust
+/// # use rayon::prelude::*;
+/// # use tacky_borders_logger::log_worker;
+/// fn process(chunks: &[Vec<u8>]) {
+///     chunks.par_iter().for_each(|chunk| {
+///         log_worker!(debug, "chunk done, {} bytes", chunk.len());
+///         // Logs: "chunk done, 4096 bytes worker=3 [fn process]"
+///     });
+/// }
+/// ```
+/// # Notes
+/// - Available with the `rayon` feature.
+/// - With the `kv` feature, the index is attached as a `worker` key-value pair instead.
+#[macro_export]
+macro_rules! log_worker {
+    ($level:ident, $($arg:tt)+) => {{
+        let level = $crate::__level!($level);
+        if $crate::__private_api::enabled(level) {
+            let fn_name = $crate::function_name!();
+            $crate::__private_api::emit_fields(
+                level,
+                format_args!($($arg)+),
+                &fn_name,
+                &(module_path!(), module_path!(), file!(), line!()),
+                &[("worker", &$crate::__private_api::Worker)],
+            );
+        }
+    }};
+}
@@ -0,0 +1,90 @@
+//! Lines held back by `buffer_logs!` must reach the logger even when the scope returns early.
+//!
+//! With `defmt`, the level macros do not log through `log`, so `buffer_logs!` never sees them.
+#![cfg(not(feature = "defmt"))]
+
+use std::sync::Mutex;
+
+use log::{LevelFilter, Log, Metadata, Record};
+use tacky_borders_logger::{buffer_logs, debug, info};
+
+static LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct CapturingLogger;
+
+impl Log for CapturingLogger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let line = format!("{} {}", record.level(), record.args());
+        LINES.lock().unwrap().push(line);
+    }
+
+    fn flush(&self) {}
+}
+
+fn parse(input: &str) -> Option<u32> {
+    let buffer = buffer_logs!();
+    debug!("parsing {:?}", input);
+    assert!(
+        LINES.lock().unwrap().is_empty(),
+        "lines logged before the flush"
+    );
+    buffer.flush();
+    info!("validating");
+    let value = input.parse().ok()?;
+    info!("parsed {}", value);
+    Some(value)
+}
+
+/// Like [`parse`], without flushing, so only dropping the buffer delivers its lines.
+fn parse_held_back(input: &str) -> Option<u32> {
+    let _buffer = buffer_logs!();
+    debug!("parsing {:?}", input);
+    info!("validating");
+    assert!(
+        LINES.lock().unwrap().is_empty(),
+        "lines logged before the scope ended"
+    );
+    let value = input.parse().ok()?;
+    info!("parsed {}", value);
+    Some(value)
+}
+
+#[test]
+fn early_return_flushes_buffer() {
+    log::set_logger(&CapturingLogger).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    assert_eq!(parse("not a number"), None);
+    assert_eq!(
+        *LINES.lock().unwrap(),
+        [
+            "DEBUG parsing \"not a number\" [fn parse]",
+            "INFO validating [fn parse]",
+        ]
+    );
+    LINES.lock().unwrap().clear();
+
+    assert_eq!(parse("42"), Some(42));
+    assert_eq!(
+        *LINES.lock().unwrap(),
+        [
+            "DEBUG parsing \"42\" [fn parse]",
+            "INFO validating [fn parse]",
+            "INFO parsed 42 [fn parse]",
+        ]
+    );
+    LINES.lock().unwrap().clear();
+
+    assert_eq!(parse_held_back("not a number"), None);
+    assert_eq!(
+        *LINES.lock().unwrap(),
+        [
+            "DEBUG parsing \"not a number\" [fn parse_held_back]",
+            "INFO validating [fn parse_held_back]",
+        ]
+    );
+}
@@ -0,0 +1,41 @@
+//! `log_depth_limited!` elides nested contents, but not empty collections or literals.
+
+use tacky_borders_logger::{capture_logs, log_depth_limited};
+
+fn dump<T: std::fmt::Debug>(value: &T, max_depth: usize) -> String {
+    let mut lines = capture_logs!({
+        log_depth_limited!(debug, value, max_depth: max_depth);
+    });
+    lines.remove(0)
+}
+
+#[test]
+fn nested_contents_are_elided() {
+    assert_eq!(
+        dump(&vec![vec![1], vec![2]], 1),
+        "[\n    […],\n    […],\n] [fn dump]"
+    );
+}
+
+#[test]
+fn empty_collections_are_kept() {
+    assert_eq!(
+        dump(&vec![vec![], vec![1]], 1),
+        "[\n    [],\n    […],\n] [fn dump]"
+    );
+}
+
+#[test]
+fn brackets_in_char_literals_are_ignored() {
+    assert_eq!(
+        dump(&vec![('{', 1)], 2),
+        "[\n    (\n        '{',\n        1,\n    ),\n] [fn dump]"
+    );
+    assert_eq!(dump(&vec![('[', 1)], 1), "[\n    (…),\n] [fn dump]");
+}
+
+#[test]
+fn brackets_in_string_literals_are_ignored() {
+    assert_eq!(dump(&vec!["a\"{b"], 1), "[\n    \"a\\\"{b\",\n] [fn dump]");
+    assert_eq!(dump(&vec![vec!["a\"{b"]], 1), "[\n    […],\n] [fn dump]");
+}
@@ -0,0 +1,31 @@
+//! The `Display` wrappers pick their unit after rounding to the precision they show.
+
+use std::time::Duration;
+
+use tacky_borders_logger::{FmtBytes, FmtDuration, FmtSi, SiPrefixes};
+
+#[test]
+fn bytes_round_up_to_the_next_unit() {
+    assert_eq!(FmtBytes(1023).to_string(), "1023 B");
+    assert_eq!(FmtBytes(1024).to_string(), "1.0 KiB");
+    assert_eq!(FmtBytes(1_048_575).to_string(), "1.0 MiB");
+    assert_eq!(FmtBytes(1023 * 1024 + 1023).to_string(), "1.0 MiB");
+    assert_eq!(FmtBytes(1_048_576).to_string(), "1.0 MiB");
+    assert_eq!(FmtBytes(1_073_741_823).to_string(), "1.0 GiB");
+}
+
+#[test]
+fn durations_round_up_to_the_next_unit() {
+    let fmt = |nanos| FmtDuration(Duration::from_nanos(nanos)).to_string();
+    assert_eq!(fmt(999), "999ns");
+    assert_eq!(fmt(999_999), "1.00ms");
+    assert_eq!(fmt(999_999_999), "1.00s");
+}
+
+#[test]
+fn si_values_round_up_to_the_next_prefix() {
+    let fmt = |value| FmtSi(value, "W", SiPrefixes::Decimal).to_string();
+    assert_eq!(fmt(999.999), "1 kW");
+    assert_eq!(fmt(0.999999), "1 W");
+    assert_eq!(fmt(0.999), "999 mW");
+}
@@ -0,0 +1,99 @@
+//! `function_name!()` names the enclosing function, looking past closures and `async` bodies.
+
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use tacky_borders_logger::function_name;
+
+/// Polls a future that never waits to completion.
+fn block_on<T>(future: impl Future<Output = T>) -> T {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    match future.as_mut().poll(&mut cx) {
+        Poll::Ready(value) => value,
+        Poll::Pending => panic!("future is not ready"),
+    }
+}
+
+fn free_fn() -> String {
+    function_name!()
+}
+
+struct Foo;
+
+impl Foo {
+    fn bar(&self) -> String {
+        function_name!()
+    }
+}
+
+trait Named {
+    fn name(&self) -> String;
+}
+
+impl Named for Foo {
+    fn name(&self) -> String {
+        function_name!()
+    }
+}
+
+async fn fetch() -> String {
+    function_name!()
+}
+
+fn baz() -> Vec<String> {
+    [1].iter().map(|_| function_name!()).collect()
+}
+
+fn nested() -> String {
+    let outer = || {
+        let inner = || function_name!();
+        inner()
+    };
+    outer()
+}
+
+fn f() -> String {
+    function_name!()
+}
+
+#[test]
+fn free_function() {
+    assert_eq!(free_fn(), "free_fn");
+}
+
+#[test]
+fn impl_method() {
+    assert_eq!(Foo.bar(), "bar");
+}
+
+#[test]
+fn trait_impl_method() {
+    assert_eq!(Foo.name(), "name");
+}
+
+#[test]
+fn async_fn() {
+    assert_eq!(block_on(fetch()), "fetch");
+}
+
+#[test]
+fn async_block() {
+    assert_eq!(block_on(async { function_name!() }), "async_block");
+}
+
+#[test]
+fn closure() {
+    assert_eq!(baz(), ["baz"]);
+}
+
+#[test]
+fn nested_closure() {
+    assert_eq!(nested(), "nested");
+}
+
+#[test]
+fn function_named_like_the_marker() {
+    assert_eq!(f(), "f");
+}
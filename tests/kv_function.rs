@@ -0,0 +1,88 @@
+//! With the `kv` feature, the function name and module path are key-value fields of the record
+//! rather than a suffix of the message.
+#![cfg(all(feature = "kv", not(feature = "defmt")))]
+
+use std::sync::Mutex;
+
+use log::kv::Key;
+use log::{LevelFilter, Log, Metadata, Record};
+use tacky_borders_logger::{capture_logs, event, info};
+
+/// A captured record: its message and its key-value pairs.
+type Captured = (String, Vec<(String, String)>);
+
+static RECORDS: Mutex<Vec<Captured>> = Mutex::new(Vec::new());
+
+struct CapturingLogger;
+
+impl Log for CapturingLogger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        struct Collect(Vec<(String, String)>);
+
+        impl<'kvs> log::kv::VisitSource<'kvs> for Collect {
+            fn visit_pair(
+                &mut self,
+                key: Key<'kvs>,
+                value: log::kv::Value<'kvs>,
+            ) -> Result<(), log::kv::Error> {
+                self.0.push((key.to_string(), value.to_string()));
+                Ok(())
+            }
+        }
+
+        let mut fields = Collect(Vec::new());
+        record.key_values().visit(&mut fields).unwrap();
+        RECORDS
+            .lock()
+            .unwrap()
+            .push((record.args().to_string(), fields.0));
+    }
+
+    fn flush(&self) {}
+}
+
+fn load_borders() {
+    info!("loaded {} borders", 3);
+    event!(info, "reload", "config changed");
+}
+
+#[test]
+fn function_name_is_a_field() {
+    log::set_logger(&CapturingLogger).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    load_borders();
+
+    let records = RECORDS.lock().unwrap();
+    let field = |k: &str, v: &str| (k.to_owned(), v.to_owned());
+    assert_eq!(
+        records[0],
+        (
+            "loaded 3 borders".to_owned(),
+            vec![
+                field("function", "load_borders"),
+                field("module", "kv_function"),
+            ]
+        )
+    );
+    assert!(!records[1].0.contains("[fn "), "{}", records[1].0);
+    assert!(records[1].1.contains(&field("function", "load_borders")));
+}
+
+#[test]
+fn text_sinks_keep_fields_and_suffix() {
+    let lines = capture_logs!({
+        load_borders();
+    });
+    assert_eq!(
+        lines,
+        [
+            "loaded 3 borders [fn load_borders]",
+            "config changed event=reload [fn load_borders]",
+        ]
+    );
+}
@@ -0,0 +1,136 @@
+//! `#[logged]` logs a function's arguments on entry and its return value on exit, however the
+//! function returns.
+#![cfg(feature = "macros")]
+
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use tacky_borders_logger::{capture_logs, logged};
+
+/// Polls a future that never waits to completion.
+fn block_on<T>(future: impl Future<Output = T>) -> T {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    match future.as_mut().poll(&mut cx) {
+        Poll::Ready(value) => value,
+        Poll::Pending => panic!("future is not ready"),
+    }
+}
+
+#[logged]
+fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+struct Counter(u32);
+
+impl Counter {
+    #[logged(level = "debug")]
+    fn get(&self, offset: u32) -> u32 {
+        self.0 + offset
+    }
+}
+
+#[logged]
+fn clamp(value: i32) -> i32 {
+    if value < 0 {
+        return 0;
+    }
+    value
+}
+
+#[logged]
+fn parse(text: &str) -> Result<u32, std::num::ParseIntError> {
+    let value = text.parse::<u32>()?;
+    Ok(value * 2)
+}
+
+#[logged(level = "info")]
+async fn fetch(id: u32) -> String {
+    format!("item-{id}")
+}
+
+#[logged(skip(password))]
+fn login(user: &str, password: &str) -> bool {
+    !user.is_empty() && !password.is_empty()
+}
+
+struct Token;
+
+#[logged(skip_ret)]
+fn issue(user: &str) -> Token {
+    let _ = user;
+    Token
+}
+
+#[test]
+fn free_fn() {
+    let lines = capture_logs!({
+        assert_eq!(add(1, 2), 3);
+    });
+    assert_eq!(lines, ["enter a=1, b=2 [fn add]", "exit -> 3 [fn add]"]);
+}
+
+#[test]
+fn method_on_self() {
+    let lines = capture_logs!({
+        assert_eq!(Counter(40).get(2), 42);
+    });
+    assert_eq!(lines, ["enter offset=2 [fn get]", "exit -> 42 [fn get]"]);
+}
+
+#[test]
+fn early_return() {
+    let lines = capture_logs!({
+        assert_eq!(clamp(-5), 0);
+    });
+    assert_eq!(lines, ["enter value=-5 [fn clamp]", "exit -> 0 [fn clamp]"]);
+}
+
+#[test]
+fn question_mark() {
+    let lines = capture_logs!({
+        assert!(parse("x").is_err());
+    });
+    assert_eq!(
+        lines,
+        [
+            "enter text=\"x\" [fn parse]",
+            "exit -> Err(ParseIntError { kind: InvalidDigit }) [fn parse]",
+        ]
+    );
+}
+
+#[test]
+fn async_fn() {
+    let lines = capture_logs!({
+        assert_eq!(block_on(fetch(7)), "item-7");
+    });
+    assert_eq!(
+        lines,
+        ["enter id=7 [fn fetch]", "exit -> \"item-7\" [fn fetch]"]
+    );
+}
+
+#[test]
+fn skipped_argument() {
+    let lines = capture_logs!({
+        assert!(login("admin", "hunter2"));
+    });
+    assert_eq!(
+        lines,
+        ["enter user=\"admin\" [fn login]", "exit -> true [fn login]"]
+    );
+}
+
+#[test]
+fn skipped_return_value() {
+    let lines = capture_logs!({
+        let _ = issue("admin");
+    });
+    assert_eq!(
+        lines,
+        ["enter user=\"admin\" [fn issue]", "exit [fn issue]"]
+    );
+}
@@ -0,0 +1,37 @@
+//! `module_and_fn!()` joins the innermost module of `module_path!()` with `function_name!()`.
+
+use tacky_borders_logger::module_and_fn;
+
+mod net {
+    use tacky_borders_logger::module_and_fn;
+
+    pub fn retry() -> String {
+        module_and_fn!()
+    }
+
+    pub mod tcp {
+        use tacky_borders_logger::module_and_fn;
+
+        pub fn connect() -> String {
+            module_and_fn!()
+        }
+    }
+}
+
+#[test]
+fn crate_root_uses_crate_name() {
+    assert_eq!(
+        module_and_fn!(),
+        "module_and_fn::crate_root_uses_crate_name"
+    );
+}
+
+#[test]
+fn nested_module() {
+    assert_eq!(net::retry(), "net::retry");
+}
+
+#[test]
+fn only_innermost_module_is_kept() {
+    assert_eq!(net::tcp::connect(), "tcp::connect");
+}
@@ -0,0 +1,61 @@
+//! The macros must accept arguments borrowing temporaries, exactly like `log`'s own macros.
+//!
+//! The `defmt` backend only takes `defmt::Format` arguments, which `String` is not.
+#![cfg(not(feature = "defmt"))]
+
+use std::sync::Mutex;
+
+use log::{LevelFilter, Log, Metadata, Record};
+use tacky_borders_logger::{debug, error, info, trace, warn};
+
+static LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct CapturingLogger;
+
+impl Log for CapturingLogger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        // With `kv`, the function name is a field of the record rather than part of the message.
+        #[cfg(feature = "kv")]
+        let suffix = (record.key_values())
+            .get(log::kv::Key::from_str("function"))
+            .map(|fn_name| format!(" [fn {fn_name}]"))
+            .unwrap_or_default();
+        #[cfg(not(feature = "kv"))]
+        let suffix = "";
+        let line = format!("{} {}{suffix}", record.level(), record.args());
+        LINES.lock().unwrap().push(line);
+    }
+
+    fn flush(&self) {}
+}
+
+fn compute() -> String {
+    String::from("computed")
+}
+
+#[test]
+fn temporaries_at_each_level() {
+    log::set_logger(&CapturingLogger).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    trace!("{}", &compute());
+    debug!("{}", compute().as_str());
+    info!("{:?}", &[compute(), compute()][..]);
+    warn!("{} {}", &compute(), compute().len());
+    error!("{}", &*compute());
+
+    assert_eq!(
+        *LINES.lock().unwrap(),
+        [
+            "TRACE computed [fn temporaries_at_each_level]",
+            "DEBUG computed [fn temporaries_at_each_level]",
+            "INFO [\"computed\", \"computed\"] [fn temporaries_at_each_level]",
+            "WARN computed 8 [fn temporaries_at_each_level]",
+            "ERROR computed [fn temporaries_at_each_level]",
+        ]
+    );
+}